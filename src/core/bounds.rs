@@ -0,0 +1,148 @@
+use crate::core::matrix::Matrix4x4;
+use crate::core::vector::Vec4;
+use crate::misc::axis::Axis;
+use crate::ray_tracing::ray::Ray;
+
+//BoundingBox is an axis aligned box which fully contains an object
+#[derive(Debug, PartialEq, Clone)]
+pub struct BoundingBox {
+    pub min: Vec4,
+    pub max: Vec4,
+}
+
+impl BoundingBox {
+    //Creates a new BoundingBox from two corners
+    pub fn new(min: Vec4, max: Vec4) -> BoundingBox {
+        BoundingBox { min, max }
+    }
+
+    //Creates a box which contains nothing
+    pub fn empty() -> BoundingBox {
+        BoundingBox {
+            min: Vec4(f32::INFINITY, f32::INFINITY, f32::INFINITY, 1.0),
+            max: Vec4(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY, 1.0),
+        }
+    }
+
+    //Creates a box which contains all of space
+    pub fn infinite() -> BoundingBox {
+        BoundingBox {
+            min: Vec4(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY, 1.0),
+            max: Vec4(f32::INFINITY, f32::INFINITY, f32::INFINITY, 1.0),
+        }
+    }
+
+    //Checks if the box contains no points
+    pub fn is_empty(&self) -> bool {
+        self.min.0 > self.max.0 || self.min.1 > self.max.1 || self.min.2 > self.max.2
+    }
+
+    //Checks if every side of the box has a finite position
+    pub fn is_finite(&self) -> bool {
+        [self.min.0, self.min.1, self.min.2, self.max.0, self.max.1, self.max.2]
+            .iter()
+            .all(|value| value.is_finite())
+    }
+
+    //Grows the box to contain a point
+    pub fn add_point(&mut self, point: &Vec4) {
+        self.min = Vec4(self.min.0.min(point.0), self.min.1.min(point.1), self.min.2.min(point.2), 1.0);
+        self.max = Vec4(self.max.0.max(point.0), self.max.1.max(point.1), self.max.2.max(point.2), 1.0);
+    }
+
+    //Grows the box to contain another box
+    pub fn merge(&mut self, other: &BoundingBox) {
+        if !other.is_empty() {
+            self.add_point(&other.min);
+            self.add_point(&other.max);
+        }
+    }
+
+    //Finds the box containing this box after a transformation is applied
+    pub fn transform(&self, matrix: &Matrix4x4) -> BoundingBox {
+        if self.is_empty() {
+            return BoundingBox::empty();
+        }
+        //Infinite sides would produce NaN when multiplied by zero matrix entries
+        if !self.is_finite() {
+            return BoundingBox::infinite();
+        }
+        let mut result = BoundingBox::empty();
+        for x in &[self.min.0, self.max.0] {
+            for y in &[self.min.1, self.max.1] {
+                for z in &[self.min.2, self.max.2] {
+                    result.add_point(&(matrix * Vec4(*x, *y, *z, 1.0)));
+                }
+            }
+        }
+        result
+    }
+
+    //Grows every side of the box by a given amount
+    pub fn padded(&self, amount: f32) -> BoundingBox {
+        if self.is_empty() {
+            return BoundingBox::empty();
+        }
+        BoundingBox {
+            min: Vec4(self.min.0 - amount, self.min.1 - amount, self.min.2 - amount, 1.0),
+            max: Vec4(self.max.0 + amount, self.max.1 + amount, self.max.2 + amount, 1.0),
+        }
+    }
+
+    //Finds the center of the box
+    pub fn center(&self) -> Vec4 {
+        Vec4(
+            (self.min.0 + self.max.0) * 0.5,
+            (self.min.1 + self.max.1) * 0.5,
+            (self.min.2 + self.max.2) * 0.5,
+            1.0,
+        )
+    }
+
+    //Checks if a point lies within the box
+    pub fn contains(&self, point: &Vec4) -> bool {
+        point.0 >= self.min.0 && point.0 <= self.max.0
+            && point.1 >= self.min.1 && point.1 <= self.max.1
+            && point.2 >= self.min.2 && point.2 <= self.max.2
+    }
+
+    //Checks if the line along a ray passes through the box
+    //Intersections behind the ray origin are still needed for refraction, so t is not limited to positive values
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        let mut tmin = f32::NEG_INFINITY;
+        let mut tmax = f32::INFINITY;
+        for axis in &[Axis::X, Axis::Y, Axis::Z] {
+            let origin = component(&ray.origin, axis);
+            let direction = component(&ray.direction, axis);
+            let min = component(&self.min, axis);
+            let max = component(&self.max, axis);
+            if direction == 0.0 {
+                if origin < min || origin > max {
+                    return false;
+                }
+            }
+            else {
+                let t1 = (min - origin) / direction;
+                let t2 = (max - origin) / direction;
+                tmin = tmin.max(t1.min(t2));
+                tmax = tmax.min(t1.max(t2));
+                if tmin > tmax {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+//Gets the value of a point along a given axis
+pub fn component(point: &Vec4, axis: &Axis) -> f32 {
+    match axis {
+        Axis::X => point.0,
+        Axis::Y => point.1,
+        Axis::Z => point.2,
+    }
+}
@@ -1,9 +1,16 @@
 use crate::core::color::Color;
+use crate::image::format::*;
+use crate::image::hdr::*;
+use crate::image::png::*;
+use crate::image::ppm::decode_ppm;
+use crate::image::tone::srgb_decode;
+use std::fs;
 use std::fs::File;
 use std::io::prelude::*;
 use std::path::Path;
 
 //Canvas stores the color for each pixel
+#[derive(Debug, PartialEq, Clone)]
 pub struct Canvas {
     pub width: usize,
     pub height: usize,
@@ -51,21 +58,25 @@ impl Canvas {
 
     //Formats the canvas contents
     pub fn format_ppm(canvas: Canvas) -> String {
+        canvas.to_ppm()
+    }
+
+    //Formats the canvas contents without consuming the canvas
+    pub fn to_ppm(&self) -> String {
         let mut current_line_length = 0;
         let mut current_item = 0;
-        let slice = &canvas.contents[..];
-        let mut result = format!("P3\n{} {}\n255\n", &canvas.width, &canvas.height);
-        for color in slice {
-            if &current_line_length + color.ppm_length() > 70 || &current_item >= &canvas.width {
-                result.push_str("\n");
-                current_item = 0; 
+        let mut result = format!("P3\n{} {}\n255\n", self.width, self.height);
+        for color in &self.contents {
+            if current_line_length + color.ppm_length() > 70 || current_item >= self.width {
+                result.push('\n');
+                current_item = 0;
                 current_line_length = 0;
             }
-            result.push_str(&*format!("{} ", color.ppm_string()));
+            result.push_str(&format!("{} ", color.ppm_string()));
             current_item += 1;
             current_line_length += color.ppm_length();
-        };
-        result.push_str("\n");
+        }
+        result.push('\n');
         result
     }
 
@@ -86,6 +97,74 @@ impl Canvas {
         result
     }
 
+    //Writes the canvas to the given path, picking the image format from its extension
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        self.save_with(path, &ImageSettings::default())
+    }
+
+    //Writes the canvas to the given path using the given encoding settings
+    //The image is written next to the path and then renamed, so stopping partway never leaves a broken file behind
+    pub fn save_with(&self, path: &str, settings: &ImageSettings) -> std::io::Result<()> {
+        let format = ImageFormat::from_path(path)?;
+        let tone_mapped;
+        let canvas = if settings.tone.is_identity() {
+            self
+        }
+        else {
+            tone_mapped = settings.tone.apply_canvas(self);
+            &tone_mapped
+        };
+        let bytes = match format {
+            ImageFormat::Ppm => canvas.to_ppm().into_bytes(),
+            ImageFormat::Png => encode_png(canvas, settings.bit_depth),
+            ImageFormat::Pfm => encode_pfm(canvas),
+            ImageFormat::Hdr => encode_hdr(canvas),
+        };
+        let partial_path = format!("{}.partial", path);
+        fs::write(&partial_path, bytes)?;
+        fs::rename(&partial_path, path)
+    }
+
+    //Reads a ppm, png, pfm or hdr file into a canvas
+    pub fn load(path: &str) -> std::io::Result<Canvas> {
+        Canvas::decode(&fs::read(path)?)
+    }
+
+    //Reads an image to use as a texture, converting 8 bit ppm and png files from sRGB to linear colors
+    pub fn load_texture(path: &str) -> std::io::Result<Canvas> {
+        let bytes = fs::read(path)?;
+        let mut canvas = Canvas::decode(&bytes)?;
+        if ImageFormat::is_srgb(&bytes) {
+            for color in canvas.contents.iter_mut() {
+                *color = Color(srgb_decode(color.0), srgb_decode(color.1), srgb_decode(color.2));
+            }
+        }
+        Ok(canvas)
+    }
+
+    //Decodes the contents of an image file, detecting its format from the data
+    pub fn decode(bytes: &[u8]) -> std::io::Result<Canvas> {
+        match ImageFormat::from_contents(bytes)? {
+            ImageFormat::Ppm => decode_ppm(bytes),
+            ImageFormat::Png => decode_png(bytes),
+            ImageFormat::Pfm => decode_pfm(bytes),
+            ImageFormat::Hdr => decode_hdr(bytes),
+        }
+    }
+
+    //Finds the largest difference between any channel of two canvases, or None if their sizes differ
+    //Used to compare renders against reference images
+    pub fn max_difference(&self, other: &Canvas) -> Option<f32> {
+        if self.width != other.width || self.height != other.height {
+            return None;
+        }
+        let mut result: f32 = 0.0;
+        for (first, second) in self.contents.iter().zip(&other.contents) {
+            result = result.max((first.0 - second.0).abs()).max((first.1 - second.1).abs()).max((first.2 - second.2).abs());
+        }
+        Some(result)
+    }
+
     //Write the canvas to a ppm file
     pub fn write_file(canvas: Canvas, filename: &str) {
         let filename_formatted = &*format!("{}.ppm", filename);
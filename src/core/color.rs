@@ -29,6 +29,12 @@ impl Color {
         Color(self.0 * 255.0, self.1 * 255.0, self.2 * 255.0)
     }
 
+    //Clamps the color and scales it to whole numbers between 0 and max_value
+    pub fn quantize(&self, max_value: u16) -> [u16; 3] {
+        let fixed_color = self.clamp() * (max_value as f32);
+        [fixed_color.0.round() as u16, fixed_color.1.round() as u16, fixed_color.2.round() as u16]
+    }
+
     //Takes a color and fully converts it to a string valid for a ppm file
     pub fn ppm_string(&self) -> String {
         let fixed_color = self.clamp().convert();
@@ -1,9 +1,11 @@
+use crate::core::color::*;
 use crate::core::matrix::Matrix4x4;
 use crate::core::vector::Vec4;
 use crate::misc::utils::*;
 use crate::objects::object::*;
 use crate::ray_tracing::intersection::Intersection;
-use crate::materials::material::Material;
+use crate::materials::bsdf::*;
+use crate::materials::material::*;
 use crate::ray_tracing::ray::Ray;
 
 //Stores values for lighting computations
@@ -21,7 +23,9 @@ pub struct Comp {
     pub material: Material,
     pub n1: f32, //Refraction index of the object the ray is passing form
     pub n2: f32, //Refraction index of the object the ray is passing to
-    pub parent_inverses: Vec<Matrix4x4>
+    pub parent_inverses: Vec<Matrix4x4>,
+    pub uv: Option<(f32, f32)>, //Texture coordinates stored on the object at the intersection
+    pub time: f32, //Time of the ray, which secondary rays are cast at
 }
 
 impl Comp {
@@ -39,7 +43,8 @@ impl Comp {
         under_point: Vec4,
         n1: f32,
         n2: f32,
-        parent_inverses: Vec<Matrix4x4> 
+        parent_inverses: Vec<Matrix4x4>,
+        uv: Option<(f32, f32)>,
     ) -> Comp {
         Comp {
             t,
@@ -55,6 +60,8 @@ impl Comp {
             n1,
             n2,
             parent_inverses,
+            uv,
+            time: 0.0,
         }
     }
 
@@ -117,7 +124,11 @@ impl Comp {
                 }
             }
         }
-        Comp::new(
+        //Moving objects add transforms between the object and the scene which depend on the ray's time
+        let mut parent_inverses = intersection.object.get_parent_inverses().clone();
+        parent_inverses.extend(intersection.motion_inverses.iter().cloned());
+
+        let mut comps = Comp::new(
             t,
             object_material,
             intersection.object.get_inverse().clone(),
@@ -130,7 +141,68 @@ impl Comp {
             under_point,
             n1,
             n2,
-            intersection.object.get_parent_inverses().clone(),
-        )
+            parent_inverses,
+            intersection.object.texture_uv(intersection.u, intersection.v),
+        );
+        comps.time = ray.time;
+        comps
+    }
+
+    //Gets the color of the surface at the point being shaded
+    pub fn surface_color(&self) -> Color {
+        match &self.material.pattern {
+            Some(pattern) => pattern.color_at_uv(&self.parent_inverses, &self.object_inverse, &self.over_point, self.uv),
+            None => self.material.color.clone(),
+        }
+    }
+
+    //Creates the BSDF which scatters light at the point being shaded
+    //Phong materials are matte with a mirror for their reflectivity, which is glossy when they are rough
+    //Microfacet materials are plastic or metal or a mix of both
+    //Either kind refracts like glass for its transparency, which then also gives its reflections
+    //Phong glass reflects with its reflectivity as in the Phong model, and microfacet glass with its transparency
+    pub fn bsdf(&self) -> Box<dyn Bsdf> {
+        let material = &self.material;
+        let color = self.surface_color();
+        let mut lobes: Vec<(f32, Box<dyn Bsdf>)> = vec![];
+        match material.model {
+            ReflectionModel::Phong => {
+                lobes.push((1.0, Box::new(Lambertian { albedo: &color * material.diffuse })));
+                if material.reflectivity > 0.0 && material.transparency == 0.0 && material.roughness > 0.0 {
+                    lobes.push((material.reflectivity, Box::new(RoughConductor { color: WHITE, roughness: material.roughness })));
+                }
+                else if material.reflectivity > 0.0 && material.transparency == 0.0 {
+                    lobes.push((material.reflectivity, Box::new(Mirror { color: WHITE })));
+                }
+            }
+            ReflectionModel::Microfacet => {
+                if material.metallic < 1.0 {
+                    lobes.push((1.0 - material.metallic, Box::new(Plastic { color: color.clone(), roughness: material.roughness })));
+                }
+                if material.metallic > 0.0 {
+                    lobes.push((material.metallic, Box::new(RoughConductor { color, roughness: material.roughness })));
+                }
+            }
+        }
+        if material.transparency > 0.0 {
+            let reflection = match material.model {
+                ReflectionModel::Phong => material.reflectivity,
+                ReflectionModel::Microfacet => material.transparency,
+            };
+            lobes.push((
+                1.0,
+                Box::new(Dielectric {
+                    n1: self.n1,
+                    n2: self.n2,
+                    roughness: material.roughness,
+                    reflection,
+                    transmission: material.transparency,
+                }),
+            ));
+        }
+        if lobes.len() == 1 && lobes[0].0 == 1.0 {
+            return lobes.remove(0).1;
+        }
+        Box::new(Mix { lobes })
     }
 }
@@ -50,6 +50,19 @@ impl Matrix4x4 {
         )
     }
 
+    //Blends two matrices entry by entry, where an amount of 0 gives self and 1 gives other
+    pub fn lerp(&self, other: &Matrix4x4, amount: f32) -> Matrix4x4 {
+        let row = |a: &(f32, f32, f32, f32), b: &(f32, f32, f32, f32)| {
+            (
+                a.0 + (b.0 - a.0) * amount,
+                a.1 + (b.1 - a.1) * amount,
+                a.2 + (b.2 - a.2) * amount,
+                a.3 + (b.3 - a.3) * amount,
+            )
+        };
+        Matrix4x4(row(&self.0, &other.0), row(&self.1, &other.1), row(&self.2, &other.2), row(&self.3, &other.3))
+    }
+
     //Finds a sub matrix of a Matrix4x4 given a row and column to remove
     pub fn sub_matrix(matrix: &Matrix4x4, row: usize, column: usize) -> Matrix3x3 {
         let mut m = vec![
@@ -162,6 +175,26 @@ impl Matrix4x4 {
         }
     }
 
+    //Finds the inverse of a matrix whose bottom row is 0 0 0 1, which holds for any combination of transformations
+    //Avoids the sub matrices used by inverse, so it is fast enough to run for every ray
+    pub fn affine_inverse(&self) -> Option<Matrix4x4> {
+        let (a, b, c) = (&self.0, &self.1, &self.2);
+        let cofactor = (
+            (b.1 * c.2 - b.2 * c.1, a.2 * c.1 - a.1 * c.2, a.1 * b.2 - a.2 * b.1),
+            (b.2 * c.0 - b.0 * c.2, a.0 * c.2 - a.2 * c.0, a.2 * b.0 - a.0 * b.2),
+            (b.0 * c.1 - b.1 * c.0, a.1 * c.0 - a.0 * c.1, a.0 * b.1 - a.1 * b.0),
+        );
+        let det = a.0 * cofactor.0 .0 + a.1 * cofactor.1 .0 + a.2 * cofactor.2 .0;
+        if det == 0.0 {
+            return None;
+        }
+        let row = |r: (f32, f32, f32)| {
+            let (x, y, z) = (r.0 / det, r.1 / det, r.2 / det);
+            (x, y, z, -(x * a.3 + y * b.3 + z * c.3))
+        };
+        Some(Matrix4x4(row(cofactor.0), row(cofactor.1), row(cofactor.2), (0.0, 0.0, 0.0, 1.0)))
+    }
+
     //Creates a translation matrix
     pub fn translation(x: f32, y: f32, z: f32) -> Matrix4x4 {
         Matrix4x4::new(
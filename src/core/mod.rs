@@ -7,3 +7,5 @@ pub mod vector;
 pub mod comp;
 
 pub mod sequence;
+
+pub mod bounds;
@@ -0,0 +1,80 @@
+use crate::image::png::PNG_SIGNATURE;
+use crate::image::ppm::ppm_max_value;
+use crate::image::tone::ToneSettings;
+use std::io;
+use std::path::Path;
+
+//Image file formats a Canvas can be saved as
+#[derive(Debug, PartialEq, Clone)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+    Pfm, //Unclamped 32 bit floats
+    Hdr, //Unclamped radiance rgbe
+}
+
+impl ImageFormat {
+    //Picks the format from the extension of a path
+    pub fn from_path(path: &str) -> io::Result<ImageFormat> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        match extension.as_deref() {
+            Some("ppm") => Ok(ImageFormat::Ppm),
+            Some("png") => Ok(ImageFormat::Png),
+            Some("pfm") => Ok(ImageFormat::Pfm),
+            Some("hdr") => Ok(ImageFormat::Hdr),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} does not end in a supported image extension (.ppm, .png, .pfm or .hdr)", path),
+            )),
+        }
+    }
+
+    //Picks the format from the first bytes of a file, which is more reliable than the extension when reading
+    pub fn from_contents(bytes: &[u8]) -> io::Result<ImageFormat> {
+        if bytes.starts_with(&PNG_SIGNATURE) {
+            Ok(ImageFormat::Png)
+        }
+        else if bytes.starts_with(b"PF") {
+            Ok(ImageFormat::Pfm)
+        }
+        else if bytes.starts_with(b"#?") {
+            Ok(ImageFormat::Hdr)
+        }
+        else if bytes.len() >= 2 && bytes[0] == b'P' && matches!(bytes[1], b'2' | b'3' | b'5' | b'6') {
+            Ok(ImageFormat::Ppm)
+        }
+        else {
+            Err(io::Error::new(io::ErrorKind::InvalidData, "Unrecognized image file"))
+        }
+    }
+
+    //Checks if an image file holds 8 bit samples, which are taken to be sRGB encoded as is usual for textures
+    //Pfm and hdr files and 16 bit ppm and png files are read as linear
+    pub fn is_srgb(bytes: &[u8]) -> bool {
+        match ImageFormat::from_contents(bytes) {
+            //The bit depth is the first byte after the width and height of the IHDR chunk
+            Ok(ImageFormat::Png) => bytes.get(24).is_some_and(|bit_depth| *bit_depth <= 8),
+            Ok(ImageFormat::Ppm) => ppm_max_value(bytes).is_ok_and(|max_value| max_value <= 255),
+            _ => false,
+        }
+    }
+}
+
+//Settings used when encoding a canvas to an image file
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImageSettings {
+    pub bit_depth: u8, //Bits per channel of png files, either 8 or 16
+    pub tone: ToneSettings, //Exposure, tone mapping and gamma applied before encoding any format
+}
+
+impl Default for ImageSettings {
+    fn default() -> ImageSettings {
+        ImageSettings {
+            bit_depth: 8,
+            tone: ToneSettings::default(),
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use crate::core::canvas::Canvas;
+use crate::core::color::Color;
+use crate::image::ppm::HeaderReader;
+use std::io;
+
+//Encodes a canvas as a little endian color pfm file which keeps the exact unclamped values
+pub fn encode_pfm(canvas: &Canvas) -> Vec<u8> {
+    //A negative scale marks the data as little endian
+    let mut output = format!("PF\n{} {}\n-1.0\n", canvas.width, canvas.height).into_bytes();
+    output.reserve(canvas.contents.len() * 12);
+    //Pfm rows are stored from the bottom of the image to the top
+    for row in canvas.contents.chunks(canvas.width.max(1)).rev() {
+        for color in row {
+            for channel in &[color.0, color.1, color.2] {
+                output.extend(&channel.to_le_bytes());
+            }
+        }
+    }
+    output
+}
+
+//Converts a color to the shared exponent rgbe form used by radiance hdr files
+//Negative channels can not be stored so they become 0
+pub fn to_rgbe(color: &Color) -> [u8; 4] {
+    let largest = color.0.max(color.1).max(color.2);
+    if largest.is_nan() || largest < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+    let mut exponent = largest.log2().floor() as i32 + 1;
+    //Rounding in log2 can leave the largest channel just outside [0.5, 1) once scaled
+    if largest / 2f32.powi(exponent) >= 1.0 {
+        exponent += 1;
+    }
+    else if largest / 2f32.powi(exponent) < 0.5 {
+        exponent -= 1;
+    }
+    let exponent = exponent.clamp(-128, 127);
+    let scale = 256.0 / 2f32.powi(exponent);
+    let channel = |value: f32| (value.max(0.0) * scale).min(255.0) as u8;
+    [channel(color.0), channel(color.1), channel(color.2), (exponent + 128) as u8]
+}
+
+//Converts a shared exponent rgbe pixel back to a color
+pub fn from_rgbe(rgbe: &[u8; 4]) -> Color {
+    if rgbe[3] == 0 {
+        return Color(0.0, 0.0, 0.0);
+    }
+    let scale = 2f32.powi(rgbe[3] as i32 - 136);
+    Color(
+        (rgbe[0] as f32 + 0.5) * scale,
+        (rgbe[1] as f32 + 0.5) * scale,
+        (rgbe[2] as f32 + 0.5) * scale,
+    )
+}
+
+//Run length encodes one channel of a scanline the way radiance does
+//Runs of four or more equal bytes are stored as a count above 128 and the byte, everything else as a count and literal bytes
+fn encode_channel(output: &mut Vec<u8>, values: &[u8]) {
+    let mut position = 0;
+    while position < values.len() {
+        //Finds the next run long enough to be worth encoding
+        let mut run_start = position;
+        let mut run_length = 0;
+        while run_start < values.len() {
+            run_length = 1;
+            while run_start + run_length < values.len()
+                && run_length < 127
+                && values[run_start + run_length] == values[run_start]
+            {
+                run_length += 1;
+            }
+            if run_length >= 4 {
+                break;
+            }
+            run_start += run_length;
+        }
+        if run_start >= values.len() {
+            run_length = 0;
+        }
+
+        //Everything before the run is written as literals
+        while position < run_start {
+            let count = (run_start - position).min(128);
+            output.push(count as u8);
+            output.extend(&values[position..position + count]);
+            position += count;
+        }
+        if run_length >= 4 {
+            output.push(128 + run_length as u8);
+            output.push(values[run_start]);
+            position += run_length;
+        }
+    }
+}
+
+//Encodes a canvas as a run length encoded radiance hdr file
+pub fn encode_hdr(canvas: &Canvas) -> Vec<u8> {
+    let mut output =
+        format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n", canvas.height, canvas.width).into_bytes();
+    for row in canvas.contents.chunks(canvas.width.max(1)) {
+        let pixels: Vec<[u8; 4]> = row.iter().map(to_rgbe).collect();
+        //Run length encoding is only defined for scanlines between 8 and 32767 pixels wide
+        if row.len() < 8 || row.len() > 0x7fff {
+            for pixel in &pixels {
+                output.extend(pixel);
+            }
+            continue;
+        }
+        output.extend(&[2, 2, (row.len() >> 8) as u8, (row.len() & 0xff) as u8]);
+        for channel in 0..4 {
+            let values: Vec<u8> = pixels.iter().map(|pixel| pixel[channel]).collect();
+            encode_channel(&mut output, &values);
+        }
+    }
+    output
+}
+
+//Creates the error returned for malformed hdr and pfm files
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+//Decodes a color pfm file into a canvas
+pub fn decode_pfm(bytes: &[u8]) -> io::Result<Canvas> {
+    let mut reader = HeaderReader::new(bytes);
+    if reader.token()? != "PF" {
+        return Err(invalid("Only color pfm files are supported"));
+    }
+    let width = reader.number()?;
+    let height = reader.number()?;
+    let scale_token = reader.token()?;
+    let scale: f32 = scale_token.parse().map_err(|_| invalid("Invalid pfm scale"))?;
+    let little_endian = scale < 0.0;
+
+    let start = reader.position + 1;
+    let end = width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(12))
+        .and_then(|size| size.checked_add(start))
+        .ok_or_else(|| invalid("Pfm image is too large"))?;
+    let data = bytes.get(start..end).ok_or_else(|| invalid("Pfm image data ended early"))?;
+    let floats: Vec<f32> = data
+        .chunks_exact(4)
+        .map(|value| {
+            let value = [value[0], value[1], value[2], value[3]];
+            if little_endian { f32::from_le_bytes(value) } else { f32::from_be_bytes(value) }
+        })
+        .collect();
+
+    let mut canvas = Canvas::new(width, height);
+    //Pfm rows are stored from the bottom of the image to the top
+    for (row_index, row) in floats.chunks_exact((width * 3).max(1)).enumerate() {
+        let y = height - 1 - row_index;
+        for (x, pixel) in row.chunks_exact(3).enumerate() {
+            canvas.contents[y * width + x] = Color(pixel[0], pixel[1], pixel[2]);
+        }
+    }
+    Ok(canvas)
+}
+
+//Reads one channel of a run length encoded scanline into every fourth byte of a row
+fn decode_channel(bytes: &[u8], position: &mut usize, row: &mut [[u8; 4]], channel: usize) -> io::Result<()> {
+    let mut index = 0;
+    while index < row.len() {
+        let count = *bytes.get(*position).ok_or_else(|| invalid("Hdr image data ended early"))? as usize;
+        *position += 1;
+        if count > 128 {
+            let run = count - 128;
+            let value = *bytes.get(*position).ok_or_else(|| invalid("Hdr image data ended early"))?;
+            *position += 1;
+            if index + run > row.len() {
+                return Err(invalid("Hdr run overruns its scanline"));
+            }
+            for pixel in &mut row[index..index + run] {
+                pixel[channel] = value;
+            }
+            index += run;
+        }
+        else {
+            if count == 0 || index + count > row.len() {
+                return Err(invalid("Invalid hdr run length"));
+            }
+            let values = bytes
+                .get(*position..*position + count)
+                .ok_or_else(|| invalid("Hdr image data ended early"))?;
+            for (pixel, value) in row[index..index + count].iter_mut().zip(values) {
+                pixel[channel] = *value;
+            }
+            *position += count;
+            index += count;
+        }
+    }
+    Ok(())
+}
+
+//Decodes a radiance hdr file with the standard -Y +X orientation into a canvas
+//Scanlines may be flat or use the run length encoding written by encode_hdr
+pub fn decode_hdr(bytes: &[u8]) -> io::Result<Canvas> {
+    if !bytes.starts_with(b"#?") {
+        return Err(invalid("Missing radiance header"));
+    }
+
+    //The header is a list of lines ended by a blank line, followed by the resolution line
+    let mut position = 0;
+    let mut line = || -> io::Result<&[u8]> {
+        let start = position;
+        let end = start + bytes[start..].iter().position(|byte| *byte == b'\n').ok_or_else(|| invalid("Hdr header ended early"))?;
+        position = end + 1;
+        Ok(&bytes[start..end])
+    };
+    loop {
+        let text = line()?;
+        if text.is_empty() {
+            break;
+        }
+        if text.starts_with(b"FORMAT=") && text != b"FORMAT=32-bit_rle_rgbe" {
+            return Err(invalid("Only rgbe hdr files are supported"));
+        }
+    }
+    let resolution = String::from_utf8_lossy(line()?).into_owned();
+    let tokens: Vec<&str> = resolution.split_whitespace().collect();
+    let (height, width) = match tokens.as_slice() {
+        ["-Y", height, "+X", width] => (
+            height.parse::<usize>().map_err(|_| invalid("Invalid hdr height"))?,
+            width.parse::<usize>().map_err(|_| invalid("Invalid hdr width"))?,
+        ),
+        _ => return Err(invalid("Only -Y +X hdr orientation is supported")),
+    };
+    //Run length encoding stores up to 127 values of a channel in two bytes, so a pixel takes at least 8 / 127 bytes
+    //Bounding the size by the remaining bytes rejects a huge header before the canvas is allocated
+    let pixels = width.checked_mul(height).ok_or_else(|| invalid("Hdr image is too large"))?;
+    if pixels.max(width) > (bytes.len() - position).saturating_mul(16) {
+        return Err(invalid("Hdr image data ended early"));
+    }
+
+    let mut canvas = Canvas::new(width, height);
+    let mut row = vec![[0u8; 4]; width];
+    for y in 0..height {
+        let marker = bytes.get(position..position + 4);
+        let encoded = (8..=0x7fff).contains(&width)
+            && marker.is_some_and(|marker| marker[0] == 2 && marker[1] == 2 && marker[2] < 128);
+        if encoded {
+            let marker = marker.unwrap();
+            if ((marker[2] as usize) << 8 | marker[3] as usize) != width {
+                return Err(invalid("Hdr scanline width does not match the image"));
+            }
+            position += 4;
+            for channel in 0..4 {
+                decode_channel(bytes, &mut position, &mut row, channel)?;
+            }
+        }
+        else {
+            let data = bytes
+                .get(position..position + width * 4)
+                .ok_or_else(|| invalid("Hdr image data ended early"))?;
+            for (pixel, values) in row.iter_mut().zip(data.chunks_exact(4)) {
+                pixel.copy_from_slice(values);
+            }
+            position += width * 4;
+        }
+        for (x, pixel) in row.iter().enumerate() {
+            canvas.contents[y * width + x] = from_rgbe(pixel);
+        }
+    }
+    Ok(canvas)
+}
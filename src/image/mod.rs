@@ -0,0 +1,6 @@
+pub mod format;
+pub mod hdr;
+pub mod png;
+pub mod ppm;
+pub mod tone;
+pub mod zlib;
@@ -0,0 +1,275 @@
+use crate::core::canvas::Canvas;
+use crate::core::color::Color;
+use crate::image::zlib::*;
+use std::io;
+
+//The eight bytes every png file starts with
+pub const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+//Predicts a byte from its left, upper and upper left neighbours
+pub fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let left_distance = (estimate - left as i16).abs();
+    let up_distance = (estimate - up as i16).abs();
+    let up_left_distance = (estimate - up_left as i16).abs();
+    if left_distance <= up_distance && left_distance <= up_left_distance {
+        left
+    }
+    else if up_distance <= up_left_distance {
+        up
+    }
+    else {
+        up_left
+    }
+}
+
+//Applies one of the five png filters to a row
+fn filter_row(filter: u8, row: &[u8], previous: &[u8], pixel_size: usize) -> Vec<u8> {
+    let mut result = Vec::with_capacity(row.len() + 1);
+    result.push(filter);
+    for index in 0..row.len() {
+        let left = if index >= pixel_size { row[index - pixel_size] } else { 0 };
+        let up = previous[index];
+        let up_left = if index >= pixel_size { previous[index - pixel_size] } else { 0 };
+        let prediction = match filter {
+            0 => 0,
+            1 => left,
+            2 => up,
+            3 => ((left as u16 + up as u16) / 2) as u8,
+            _ => paeth(left, up, up_left),
+        };
+        result.push(row[index].wrapping_sub(prediction));
+    }
+    result
+}
+
+//Picks the filter for each row which gives the smallest sum of signed differences
+fn filter_rows(raw: &[u8], row_size: usize, pixel_size: usize) -> Vec<u8> {
+    let mut result = vec![];
+    if row_size == 0 {
+        return result;
+    }
+    let mut previous = vec![0; row_size];
+    for row in raw.chunks(row_size) {
+        let mut best: Option<(u64, Vec<u8>)> = None;
+        for filter in 0..5 {
+            let filtered = filter_row(filter, row, &previous, pixel_size);
+            let cost: u64 = filtered[1..].iter().map(|byte| (*byte as i8).unsigned_abs() as u64).sum();
+            if best.as_ref().is_none_or(|(best_cost, _)| cost < *best_cost) {
+                best = Some((cost, filtered));
+            }
+        }
+        result.extend(best.unwrap().1);
+        previous = row.to_vec();
+    }
+    result
+}
+
+//Appends a chunk with its length and checksum
+fn write_chunk(output: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    output.extend(&(data.len() as u32).to_be_bytes());
+    let mut checked = kind.to_vec();
+    checked.extend(data);
+    output.extend(&checked);
+    output.extend(&crc32(&checked).to_be_bytes());
+}
+
+//Encodes a canvas as an rgb png with 8 or 16 bits per channel
+pub fn encode_png(canvas: &Canvas, bit_depth: u8) -> Vec<u8> {
+    let channel_size = if bit_depth == 16 { 2 } else { 1 };
+    let max_value = if bit_depth == 16 { 65535 } else { 255 };
+    let pixel_size = 3 * channel_size;
+
+    let mut raw = Vec::with_capacity(canvas.contents.len() * pixel_size);
+    for color in &canvas.contents {
+        for channel in &color.quantize(max_value) {
+            if bit_depth == 16 {
+                raw.extend(&channel.to_be_bytes());
+            }
+            else {
+                raw.push(*channel as u8);
+            }
+        }
+    }
+
+    let mut header = vec![];
+    header.extend(&(canvas.width as u32).to_be_bytes());
+    header.extend(&(canvas.height as u32).to_be_bytes());
+    //Bit depth, truecolor, deflate, adaptive filtering and no interlacing
+    header.extend(&[channel_size as u8 * 8, 2, 0, 0, 0]);
+
+    let mut output = PNG_SIGNATURE.to_vec();
+    write_chunk(&mut output, b"IHDR", &header);
+    let filtered = filter_rows(&raw, canvas.width * pixel_size, pixel_size);
+    write_chunk(&mut output, b"IDAT", &compress(&filtered));
+    write_chunk(&mut output, b"IEND", &[]);
+    output
+}
+
+//Starting column, starting row, column step and row step of the seven Adam7 interlacing passes
+const ADAM7_PASSES: [(usize, usize, usize, usize); 7] =
+    [(0, 0, 8, 8), (4, 0, 8, 8), (0, 4, 4, 8), (2, 0, 4, 4), (0, 2, 2, 4), (1, 0, 2, 2), (0, 1, 1, 2)];
+
+//Creates the error returned for malformed png files
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+//Reads a big endian u32 from the start of a byte slice
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+//Reverses the filter applied to a row in place
+fn unfilter_row(filter: u8, row: &mut [u8], previous: &[u8], pixel_size: usize) -> io::Result<()> {
+    for index in 0..row.len() {
+        let left = if index >= pixel_size { row[index - pixel_size] } else { 0 };
+        let up = previous[index];
+        let up_left = if index >= pixel_size { previous[index - pixel_size] } else { 0 };
+        let prediction = match filter {
+            0 => 0,
+            1 => left,
+            2 => up,
+            3 => ((left as u16 + up as u16) / 2) as u8,
+            4 => paeth(left, up, up_left),
+            _ => return Err(invalid("Invalid png filter type")),
+        };
+        row[index] = row[index].wrapping_add(prediction);
+    }
+    Ok(())
+}
+
+//Reads the sample at an index from an unfiltered row
+fn sample(row: &[u8], index: usize, bit_depth: usize) -> u16 {
+    match bit_depth {
+        16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+        8 => row[index] as u16,
+        //Samples smaller than a byte are packed starting from the most significant bit
+        _ => {
+            let bit = index * bit_depth;
+            let shift = 8 - bit_depth - bit % 8;
+            ((row[bit / 8] >> shift) & ((1 << bit_depth) - 1) as u8) as u16
+        }
+    }
+}
+
+//Decodes a png file into a canvas, dropping any alpha channel
+pub fn decode_png(bytes: &[u8]) -> io::Result<Canvas> {
+    if bytes.len() < 8 || bytes[0..8] != PNG_SIGNATURE {
+        return Err(invalid("Missing png signature"));
+    }
+
+    let mut header = None;
+    let mut palette = vec![];
+    let mut compressed = vec![];
+    let mut position = 8;
+    loop {
+        let chunk = bytes.get(position..position + 8).ok_or_else(|| invalid("Png file ended before IEND"))?;
+        let length = read_u32(chunk) as usize;
+        let kind = &bytes[position + 4..position + 8];
+        let end = position + 8 + length;
+        let data = bytes.get(position + 8..end).ok_or_else(|| invalid("Png chunk runs past the end of the file"))?;
+        let checksum = bytes.get(end..end + 4).ok_or_else(|| invalid("Png chunk is missing its checksum"))?;
+        if crc32(&bytes[position + 4..end]) != read_u32(checksum) {
+            return Err(invalid("Png chunk checksum does not match"));
+        }
+        match kind {
+            b"IHDR" if length == 13 => header = Some(data.to_vec()),
+            b"PLTE" => {
+                palette = data
+                    .chunks_exact(3)
+                    .map(|entry| Color::new_255(entry[0] as i32, entry[1] as i32, entry[2] as i32))
+                    .collect()
+            }
+            b"IDAT" => compressed.extend(data),
+            b"IEND" => break,
+            //Ancillary chunks such as gamma and transparency are ignored
+            _ => {}
+        }
+        position = end + 4;
+    }
+
+    let header = header.ok_or_else(|| invalid("Png file has no IHDR chunk"))?;
+    let width = read_u32(&header[0..4]) as usize;
+    let height = read_u32(&header[4..8]) as usize;
+    let bit_depth = header[8] as usize;
+    let color_type = header[9];
+    let channels = match (color_type, bit_depth) {
+        (0, 1 | 2 | 4 | 8 | 16) => 1,
+        (2, 8 | 16) => 3,
+        (3, 1 | 2 | 4 | 8) => 1,
+        (4, 8 | 16) => 2,
+        (6, 8 | 16) => 4,
+        _ => return Err(invalid("Unsupported png color type and bit depth")),
+    };
+    if color_type == 3 && palette.is_empty() {
+        return Err(invalid("Indexed png file has no palette"));
+    }
+    let passes: &[(usize, usize, usize, usize)] = match header[12] {
+        0 => &[(0, 0, 1, 1)],
+        1 => &ADAM7_PASSES,
+        _ => return Err(invalid("Invalid png interlace method")),
+    };
+
+    let raw = decompress(&compressed)?;
+    //Sizes from the header are checked against the scanlines so a huge image is rejected before the canvas is allocated
+    width.checked_mul(height).ok_or_else(|| invalid("Png image is too large"))?;
+    let scanline_bytes = passes.iter().try_fold(0usize, |total, (start_x, start_y, step_x, step_y)| {
+        let pass_width = width.saturating_sub(*start_x).div_ceil(*step_x);
+        let pass_height = height.saturating_sub(*start_y).div_ceil(*step_y);
+        if pass_width == 0 || pass_height == 0 {
+            return Some(total);
+        }
+        let row_size = pass_width.checked_mul(channels * bit_depth)?.div_ceil(8);
+        total.checked_add(pass_height.checked_mul(row_size + 1)?)
+    });
+    if scanline_bytes.is_none_or(|size| raw.len() < size) {
+        return Err(invalid("Png image data ended early"));
+    }
+    let max_value = ((1u32 << bit_depth) - 1) as f32;
+    let pixel_size = (channels * bit_depth).div_ceil(8);
+    let mut canvas = Canvas::new(width, height);
+    let mut offset = 0;
+    for (start_x, start_y, step_x, step_y) in passes {
+        let pass_width = width.saturating_sub(*start_x).div_ceil(*step_x);
+        let pass_height = height.saturating_sub(*start_y).div_ceil(*step_y);
+        if pass_width == 0 || pass_height == 0 {
+            continue;
+        }
+        let row_size = (pass_width * channels * bit_depth).div_ceil(8);
+        let mut previous = vec![0; row_size];
+        for row_index in 0..pass_height {
+            let filter = *raw.get(offset).ok_or_else(|| invalid("Png image data ended early"))?;
+            let mut row = raw
+                .get(offset + 1..offset + 1 + row_size)
+                .ok_or_else(|| invalid("Png image data ended early"))?
+                .to_vec();
+            offset += row_size + 1;
+            unfilter_row(filter, &mut row, &previous, pixel_size)?;
+
+            for column in 0..pass_width {
+                let first = column * channels;
+                let color = match color_type {
+                    0 | 4 => {
+                        let value = sample(&row, first, bit_depth) as f32 / max_value;
+                        Color(value, value, value)
+                    }
+                    3 => {
+                        let index = sample(&row, first, bit_depth) as usize;
+                        palette.get(index).cloned().ok_or_else(|| invalid("Png palette index out of range"))?
+                    }
+                    _ => Color(
+                        sample(&row, first, bit_depth) as f32 / max_value,
+                        sample(&row, first + 1, bit_depth) as f32 / max_value,
+                        sample(&row, first + 2, bit_depth) as f32 / max_value,
+                    ),
+                };
+                let x = start_x + column * step_x;
+                let y = start_y + row_index * step_y;
+                canvas.set(color, x as i32, y as i32);
+            }
+            previous = row;
+        }
+    }
+    Ok(canvas)
+}
@@ -0,0 +1,118 @@
+use crate::core::canvas::Canvas;
+use crate::core::color::Color;
+use std::io;
+
+//Creates the error returned for malformed ppm files
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+//Reads whitespace separated tokens from a netpbm style header, skipping comments
+pub struct HeaderReader<'a> {
+    pub bytes: &'a [u8],
+    pub position: usize,
+}
+
+impl<'a> HeaderReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> HeaderReader<'a> {
+        HeaderReader { bytes, position: 0 }
+    }
+
+    //Reads the next token
+    pub fn token(&mut self) -> io::Result<&'a str> {
+        while self.position < self.bytes.len() {
+            match self.bytes[self.position] {
+                b'#' => {
+                    while self.position < self.bytes.len() && self.bytes[self.position] != b'\n' {
+                        self.position += 1;
+                    }
+                }
+                byte if byte.is_ascii_whitespace() => self.position += 1,
+                _ => break,
+            }
+        }
+        let start = self.position;
+        while self.position < self.bytes.len() && !self.bytes[self.position].is_ascii_whitespace() {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(invalid("Image header ended early"));
+        }
+        std::str::from_utf8(&self.bytes[start..self.position]).map_err(|_| invalid("Invalid text in image header"))
+    }
+
+    //Reads the next token as a number
+    pub fn number(&mut self) -> io::Result<usize> {
+        let token = self.token()?;
+        token.parse().map_err(|_| invalid(&format!("{} is not a valid number in an image header", token)))
+    }
+}
+
+//Reads the maximum sample value from the header of a ppm file
+pub fn ppm_max_value(bytes: &[u8]) -> io::Result<usize> {
+    let mut reader = HeaderReader::new(bytes);
+    reader.token()?;
+    reader.number()?;
+    reader.number()?;
+    reader.number()
+}
+
+//Decodes an ascii (P3) or binary (P6) ppm file into a canvas
+//Grayscale pgm files (P2 and P5) are also accepted
+pub fn decode_ppm(bytes: &[u8]) -> io::Result<Canvas> {
+    let mut reader = HeaderReader::new(bytes);
+    let (channels, binary) = match reader.token()? {
+        "P2" => (1, false),
+        "P3" => (3, false),
+        "P5" => (1, true),
+        "P6" => (3, true),
+        _ => return Err(invalid("Unsupported ppm type")),
+    };
+    let width = reader.number()?;
+    let height = reader.number()?;
+    let max_value = reader.number()?;
+    if max_value == 0 || max_value > 65535 {
+        return Err(invalid("Ppm maximum value must be between 1 and 65535"));
+    }
+
+    //Sizes from the header are checked so a huge image is rejected rather than overflowing
+    let count = width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(channels))
+        .ok_or_else(|| invalid("Ppm image is too large"))?;
+    let mut samples = Vec::with_capacity(count.min(bytes.len()));
+    if binary {
+        //A single whitespace byte separates the header from the data
+        let start = reader.position + 1;
+        let sample_size = if max_value > 255 { 2 } else { 1 };
+        let end = count
+            .checked_mul(sample_size)
+            .and_then(|size| size.checked_add(start))
+            .ok_or_else(|| invalid("Ppm image is too large"))?;
+        let data = bytes.get(start..end).ok_or_else(|| invalid("Ppm image data ended early"))?;
+        if sample_size == 2 {
+            samples.extend(data.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]]) as usize));
+        }
+        else {
+            samples.extend(data.iter().map(|byte| *byte as usize));
+        }
+    }
+    else {
+        for _ in 0..count {
+            samples.push(reader.number()?);
+        }
+    }
+
+    let mut canvas = Canvas::new(width, height);
+    let scale = max_value as f32;
+    for (index, pixel) in samples.chunks_exact(channels).enumerate() {
+        canvas.contents[index] = if channels == 1 {
+            let value = pixel[0] as f32 / scale;
+            Color(value, value, value)
+        }
+        else {
+            Color(pixel[0] as f32 / scale, pixel[1] as f32 / scale, pixel[2] as f32 / scale)
+        };
+    }
+    Ok(canvas)
+}
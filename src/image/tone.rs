@@ -0,0 +1,107 @@
+use crate::core::canvas::Canvas;
+use crate::core::color::Color;
+
+//Operators which compress unbounded scene colors into the 0 to 1 range of a display
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ToneMap {
+    Clamp,    //Leaves colors as they are so anything above 1 clips
+    Reinhard, //Maps each channel with x / (1 + x), which never reaches 1
+    Aces,     //Filmic curve fitted to the ACES reference transform, with a toe and a soft shoulder
+}
+
+impl ToneMap {
+    //Applies the operator to a single channel
+    pub fn apply(&self, value: f32) -> f32 {
+        let value = value.max(0.0);
+        match self {
+            ToneMap::Clamp => value,
+            ToneMap::Reinhard => value / (1.0 + value),
+            //Approximation of the ACES curve by Krzysztof Narkowicz
+            ToneMap::Aces => {
+                let result = (value * (2.51 * value + 0.03)) / (value * (2.43 * value + 0.59) + 0.14);
+                result.clamp(0.0, 1.0)
+            }
+        }
+    }
+
+    //Looks up an operator by name
+    pub fn from_name(name: &str) -> Option<ToneMap> {
+        match name {
+            "clamp" => Some(ToneMap::Clamp),
+            "reinhard" => Some(ToneMap::Reinhard),
+            "aces" => Some(ToneMap::Aces),
+            _ => None,
+        }
+    }
+}
+
+//Encodes a linear channel with the sRGB transfer curve expected by most image viewers
+pub fn srgb_encode(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    }
+    else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+//Converts an sRGB encoded channel back to linear
+pub fn srgb_decode(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    }
+    else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+//Post process applied to a rendered canvas before it is written
+//Colors are scaled by the exposure, compressed by the tone map and then optionally sRGB encoded
+#[derive(Debug, PartialEq, Clone)]
+pub struct ToneSettings {
+    pub exposure: f32, //Exposure adjustment in stops, where each stop doubles the brightness
+    pub tone_map: ToneMap,
+    pub srgb: bool,
+}
+
+impl Default for ToneSettings {
+    //The default leaves colors unchanged
+    fn default() -> ToneSettings {
+        ToneSettings {
+            exposure: 0.0,
+            tone_map: ToneMap::Clamp,
+            srgb: false,
+        }
+    }
+}
+
+impl ToneSettings {
+    //Checks if the settings would leave every color unchanged
+    pub fn is_identity(&self) -> bool {
+        self == &ToneSettings::default()
+    }
+
+    //Maps a single color
+    pub fn apply(&self, color: &Color) -> Color {
+        let scale = 2f32.powf(self.exposure);
+        let channel = |value: f32| {
+            let mapped = self.tone_map.apply(value * scale);
+            if self.srgb {
+                srgb_encode(mapped.min(1.0))
+            }
+            else {
+                mapped
+            }
+        };
+        Color(channel(color.0), channel(color.1), channel(color.2))
+    }
+
+    //Maps every color in a canvas
+    pub fn apply_canvas(&self, canvas: &Canvas) -> Canvas {
+        let mut result = Canvas::new(canvas.width, canvas.height);
+        for (target, color) in result.contents.iter_mut().zip(&canvas.contents) {
+            *target = self.apply(color);
+        }
+        result
+    }
+}
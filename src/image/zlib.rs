@@ -0,0 +1,444 @@
+//Compresses data into a zlib stream using a single deflate block with the fixed Huffman codes
+//Matches are found with hash chains over a 32KB window
+//Decompression supports every deflate block type so files from other encoders can be read
+
+use std::io;
+
+//Size of the window searched for earlier matches
+const WINDOW_SIZE: usize = 32768;
+
+//Number of earlier positions checked for each match
+const MAX_CHAIN: usize = 64;
+
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+//Number of bits used to hash the next three bytes
+const HASH_BITS: usize = 15;
+
+//Base values and extra bits of the deflate length codes 257-285
+pub const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+pub const LENGTH_EXTRA_BITS: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+//Base values and extra bits of the deflate distance codes 0-29
+pub const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097,
+    6145, 8193, 12289, 16385, 24577,
+];
+pub const DISTANCE_EXTRA_BITS: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13,
+];
+
+//Writes values into a byte vector starting from the least significant bit
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn new() -> BitWriter {
+        BitWriter {
+            bytes: vec![],
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    //Writes the lowest bits of a value, least significant bit first
+    fn write(&mut self, value: u32, bits: u32) {
+        self.buffer |= value << self.count;
+        self.count += bits;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    //Writes a Huffman code, which deflate stores most significant bit first
+    fn write_code(&mut self, code: u32, bits: u32) {
+        let mut reversed = 0;
+        for bit in 0..bits {
+            reversed |= ((code >> bit) & 1) << (bits - 1 - bit);
+        }
+        self.write(reversed, bits);
+    }
+
+    //Writes any remaining bits padded to a whole byte
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+//Writes a literal byte or length symbol using the fixed Huffman codes
+fn write_symbol(writer: &mut BitWriter, symbol: u16) {
+    let symbol = symbol as u32;
+    match symbol {
+        0..=143 => writer.write_code(0x30 + symbol, 8),
+        144..=255 => writer.write_code(0x190 + symbol - 144, 9),
+        256..=279 => writer.write_code(symbol - 256, 7),
+        _ => writer.write_code(0xC0 + symbol - 280, 8),
+    }
+}
+
+//Finds the index of the code whose base value range contains a value
+fn code_index(bases: &[u16], value: usize) -> usize {
+    let mut index = bases.len() - 1;
+    while bases[index] as usize > value {
+        index -= 1;
+    }
+    index
+}
+
+//Writes a match of a given length and distance
+fn write_match(writer: &mut BitWriter, length: usize, distance: usize) {
+    let length_index = code_index(&LENGTH_BASES, length);
+    write_symbol(writer, 257 + length_index as u16);
+    writer.write(
+        (length - LENGTH_BASES[length_index] as usize) as u32,
+        LENGTH_EXTRA_BITS[length_index] as u32,
+    );
+
+    let distance_index = code_index(&DISTANCE_BASES, distance);
+    writer.write_code(distance_index as u32, 5);
+    writer.write(
+        (distance - DISTANCE_BASES[distance_index] as usize) as u32,
+        DISTANCE_EXTRA_BITS[distance_index] as u32,
+    );
+}
+
+//Hashes the three bytes starting at a position
+fn hash(data: &[u8], position: usize) -> usize {
+    let value = (data[position] as usize) << 16 | (data[position + 1] as usize) << 8 | data[position + 2] as usize;
+    (value.wrapping_mul(2654435761) >> 8) & ((1 << HASH_BITS) - 1)
+}
+
+//Compresses data into a raw deflate stream
+pub fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+    //A single final block using the fixed Huffman codes
+    writer.write(1, 1);
+    writer.write(1, 2);
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut previous = vec![usize::MAX; WINDOW_SIZE];
+    let mut position = 0;
+
+    while position < data.len() {
+        let mut best_length = 0;
+        let mut best_distance = 0;
+        if position + MIN_MATCH <= data.len() {
+            let key = hash(data, position);
+            let mut candidate = head[key];
+            let mut chain = 0;
+            let max_length = MAX_MATCH.min(data.len() - position);
+            while candidate != usize::MAX && position - candidate <= WINDOW_SIZE && chain < MAX_CHAIN {
+                let mut length = 0;
+                while length < max_length && data[candidate + length] == data[position + length] {
+                    length += 1;
+                }
+                if length > best_length {
+                    best_length = length;
+                    best_distance = position - candidate;
+                    if length == max_length {
+                        break;
+                    }
+                }
+                let next = previous[candidate % WINDOW_SIZE];
+                if next == usize::MAX || next >= candidate {
+                    break;
+                }
+                candidate = next;
+                chain += 1;
+            }
+        }
+
+        let step = if best_length >= MIN_MATCH {
+            write_match(&mut writer, best_length, best_distance);
+            best_length
+        }
+        else {
+            write_symbol(&mut writer, data[position] as u16);
+            1
+        };
+
+        //Every position passed over is added to the hash chains
+        for index in position..(position + step) {
+            if index + MIN_MATCH <= data.len() {
+                let key = hash(data, index);
+                previous[index % WINDOW_SIZE] = head[key];
+                head[key] = index;
+            }
+        }
+        position += step;
+    }
+
+    write_symbol(&mut writer, 256);
+    writer.finish()
+}
+
+//Compresses data into a zlib stream
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    //Deflate with a 32KB window and no preset dictionary
+    let mut result = vec![0x78, 0x01];
+    result.extend(deflate(data));
+    result.extend(&adler32(data).to_be_bytes());
+    result
+}
+
+//Computes the Adler-32 checksum used by zlib
+pub fn adler32(data: &[u8]) -> u32 {
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+//Computes the CRC-32 checksum used by png chunks
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+//Order the code length code lengths are stored in by dynamic Huffman blocks
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+//Creates the error returned for malformed compressed data
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+//Reads values from a byte slice starting from the least significant bit
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize, //Position in bits
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> BitReader<'a> {
+        BitReader { data, position: 0 }
+    }
+
+    //Reads a single bit
+    fn bit(&mut self) -> io::Result<u32> {
+        let byte = self.data.get(self.position / 8).ok_or_else(|| invalid("Compressed data ended early"))?;
+        let bit = (byte >> (self.position % 8)) & 1;
+        self.position += 1;
+        Ok(bit as u32)
+    }
+
+    //Reads a value stored least significant bit first
+    fn read(&mut self, bits: u32) -> io::Result<u32> {
+        let mut value = 0;
+        for bit in 0..bits {
+            value |= self.bit()? << bit;
+        }
+        Ok(value)
+    }
+
+    //Skips to the start of the next byte
+    fn align(&mut self) {
+        self.position = self.position.div_ceil(8) * 8;
+    }
+}
+
+//Canonical Huffman code stored as the number of codes of each length and the symbols in code order
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    //Builds the code from the length of each symbol's code, where 0 means the symbol is unused
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0; 16];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut symbols = vec![];
+        for length in 1..16 {
+            for (symbol, symbol_length) in lengths.iter().enumerate() {
+                if *symbol_length as usize == length {
+                    symbols.push(symbol as u16);
+                }
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    //Decodes one symbol, reading a bit at a time
+    fn decode(&self, reader: &mut BitReader) -> io::Result<u16> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for length in 1..16 {
+            code |= reader.bit()? as i32;
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(invalid("Invalid Huffman code"))
+    }
+}
+
+//Builds the fixed literal/length and distance codes
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0; 288];
+    for (symbol, length) in lengths.iter_mut().enumerate() {
+        *length = match symbol {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8,
+        };
+    }
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+//Reads the code descriptions at the start of a dynamic Huffman block
+fn dynamic_codes(reader: &mut BitReader) -> io::Result<(Huffman, Huffman)> {
+    let literal_count = reader.read(5)? as usize + 257;
+    let distance_count = reader.read(5)? as usize + 1;
+    let code_length_count = reader.read(4)? as usize + 4;
+
+    let mut code_lengths = [0; 19];
+    for index in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+        code_lengths[*index] = reader.read(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_lengths);
+
+    //Literal and distance code lengths are stored together and may share repeats
+    let mut lengths = vec![];
+    while lengths.len() < literal_count + distance_count {
+        let symbol = code_length_code.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths.last().ok_or_else(|| invalid("Repeated code length with no previous length"))?;
+                (previous, 3 + reader.read(2)?)
+            }
+            17 => (0, 3 + reader.read(3)?),
+            _ => (0, 11 + reader.read(7)?),
+        };
+        for _ in 0..repeat {
+            lengths.push(value);
+        }
+    }
+    if lengths.len() > literal_count + distance_count {
+        return Err(invalid("Code lengths overrun the code"));
+    }
+    Ok((
+        Huffman::new(&lengths[..literal_count]),
+        Huffman::new(&lengths[literal_count..]),
+    ))
+}
+
+//Decompresses a raw deflate stream
+pub fn inflate(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut reader = BitReader::new(data);
+    let mut output = vec![];
+    loop {
+        let last = reader.read(1)? == 1;
+        match reader.read(2)? {
+            //Stored block with its length and the complement of its length
+            0 => {
+                reader.align();
+                let start = reader.position / 8;
+                let header = data.get(start..start + 4).ok_or_else(|| invalid("Compressed data ended early"))?;
+                let length = u16::from_le_bytes([header[0], header[1]]) as usize;
+                if length != !u16::from_le_bytes([header[2], header[3]]) as usize {
+                    return Err(invalid("Stored block length does not match its complement"));
+                }
+                let block = data
+                    .get(start + 4..start + 4 + length)
+                    .ok_or_else(|| invalid("Compressed data ended early"))?;
+                output.extend(block);
+                reader.position = (start + 4 + length) * 8;
+            }
+            kind @ (1 | 2) => {
+                let (literals, distances) = if kind == 1 { fixed_codes() } else { dynamic_codes(&mut reader)? };
+                loop {
+                    let symbol = literals.decode(&mut reader)? as usize;
+                    if symbol < 256 {
+                        output.push(symbol as u8);
+                        continue;
+                    }
+                    if symbol == 256 {
+                        break;
+                    }
+                    let length_index = symbol - 257;
+                    if length_index >= LENGTH_BASES.len() {
+                        return Err(invalid("Invalid length code"));
+                    }
+                    let length = LENGTH_BASES[length_index] as usize
+                        + reader.read(LENGTH_EXTRA_BITS[length_index] as u32)? as usize;
+                    let distance_index = distances.decode(&mut reader)? as usize;
+                    if distance_index >= DISTANCE_BASES.len() {
+                        return Err(invalid("Invalid distance code"));
+                    }
+                    let distance = DISTANCE_BASES[distance_index] as usize
+                        + reader.read(DISTANCE_EXTRA_BITS[distance_index] as u32)? as usize;
+                    if distance > output.len() {
+                        return Err(invalid("Match distance is before the start of the data"));
+                    }
+                    //Matches may overlap the bytes they produce so they are copied one byte at a time
+                    let start = output.len() - distance;
+                    for index in 0..length {
+                        output.push(output[start + index]);
+                    }
+                }
+            }
+            _ => return Err(invalid("Invalid block type")),
+        }
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+//Decompresses a zlib stream and checks its checksum
+pub fn decompress(data: &[u8]) -> io::Result<Vec<u8>> {
+    if data.len() < 6 {
+        return Err(invalid("Zlib stream is too short"));
+    }
+    let (method, flags) = (data[0], data[1]);
+    if method & 0x0f != 8 || !(method as u16 * 256 + flags as u16).is_multiple_of(31) {
+        return Err(invalid("Invalid zlib header"));
+    }
+    if flags & 0x20 != 0 {
+        return Err(invalid("Zlib preset dictionaries are not supported"));
+    }
+    let output = inflate(&data[2..])?;
+    let end = data.len();
+    let checksum = u32::from_be_bytes([data[end - 4], data[end - 3], data[end - 2], data[end - 1]]);
+    if checksum != adler32(&output) {
+        return Err(invalid("Zlib checksum does not match"));
+    }
+    Ok(output)
+}
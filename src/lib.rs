@@ -13,6 +13,9 @@ pub mod world;
 //Objects rendered with ray tracing
 pub mod objects;
 
+//Image file formats which a Canvas can be saved as
+pub mod image;
+
 //Miscellaneous methods and structs
 pub mod misc;
 
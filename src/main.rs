@@ -1,101 +1,214 @@
 use rust_ray_tracer::core::canvas::Canvas;
-use rust_ray_tracer::core::color::*;
-use rust_ray_tracer::core::matrix::Matrix4x4;
-use rust_ray_tracer::core::vector::Vec4;
-use rust_ray_tracer::objects::plane::Plane;
-use rust_ray_tracer::misc::axis::Axis;
-use rust_ray_tracer::objects::cube::Cube;
-use rust_ray_tracer::objects::cylinder::Cylinder;
-use rust_ray_tracer::objects::sphere::Sphere;
+use rust_ray_tracer::image::format::ImageSettings;
+use rust_ray_tracer::misc::options::*;
+use rust_ray_tracer::world::animation::frame_path;
 use rust_ray_tracer::world::camera::Camera;
-use rust_ray_tracer::world::lighting::*;
-use rust_ray_tracer::materials::material::Material;
-use rust_ray_tracer::world::scene::Scene;
-use std::{default, time::Instant};
+use rust_ray_tracer::world::checkpoint::*;
+use rust_ray_tracer::world::scene_file::SceneFile;
+use std::env;
+use std::fs;
+use std::io;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
 
 fn main() {
-    //Width and height of the scene
-    const WIDTH: usize = 100;
-    const HEIGHT: usize = 100;
-
-    //Canvas where color is stored
-    let mut canvas = Canvas::new(WIDTH, HEIGHT);
-
-    //Creating an AreaLight
-    let corner = Vec4(-3.0, 5.0, -5.0, 1.0);
-    let v1 = Vec4::new(0.5, 0.0, 0.0, 0.0);
-    let v2 = Vec4::new(0.0, 0.5, 0.0, 0.0);
-    let light = AreaLight::new(corner, v1, 5, v2, 5, Color(0.8, 0.8, 0.8));
-
-    let pl = PointLight::new(Color::new(0.4, 0.4, 0.4), Vec4::new(5.0, 0.0, 20.0, 1.0));
-
-    //Creating a plane
-    let mut white_material = Material::default();
-    white_material.ambient = 0.5;
-    white_material.diffuse = 0.3;
-    white_material.specular = 0.0;
-    white_material.casts_shadows = false;
-
-    let mut wm2 = Material::default();
-    wm2.ambient = 0.6;
-    wm2.specular = 0.0;
-
-    let mut m1 = Material::default();
-    m1.color = Color::new_255(255, 163, 64);
-    m1.ambient = 0.5;
-    m1.diffuse = 0.85;
-    m1.reflectivity = 0.1;
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match RenderOptions::parse(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+    if let Err(message) = run(&options) {
+        eprintln!("error: {}", message);
+        process::exit(1);
+    }
+}
 
-    let mut m2 = Material::default();
-    m2.color = Color::new_255(64, 169, 255);
-    m2.ambient = 0.5;
-    m2.reflectivity = 0.1;
+//Renders the scene described by the options, or every frame of it when rendering an animation
+fn run(options: &RenderOptions) -> Result<(), String> {
+    match options.frames {
+        Some(frames) => {
+            for frame in 0..frames {
+                println!("Frame {} of {}", frame, frames - 1);
+                render(options, frame as f32, &frame_path(&options.output_path, frame))?;
+            }
+            Ok(())
+        }
+        None => render(options, 0.0, &options.output_path),
+    }
+}
 
-    let mut lm = Material::default();
-    lm.specular = 0.0;
-    lm.color = Color::new(1.5, 1.5, 1.5);
-    lm.ambient = 1.5;
-    lm.casts_shadows = false;
-    lm.diffuse = 0.0;
+//Loads, renders and saves the scene as it is at a time
+fn render(options: &RenderOptions, time: f32, output_path: &str) -> Result<(), String> {
+    let mut file = SceneFile::load_at(&options.scene_path, time).map_err(|error| format!("{}: {}", options.scene_path, error))?;
 
-    let sphere1 = Sphere::new(Matrix4x4::translation(-0.3, 1.0, -0.5), m1.clone());
-    let sphere2 = Sphere::new(Matrix4x4::translation(1.3, 0.5, -0.1) * Matrix4x4::scaling(0.5, 0.5, 0.5), m2.clone());
-    let sphere3 = Sphere::new(Matrix4x4::translation(-1.2, 0.4, -1.7) * Matrix4x4::scaling(0.4, 0.4, 0.4), m2.clone());
-    let cube1 = Cube::new(Matrix4x4::translation(0.0, 20.0, -15.0) * Matrix4x4::scaling(20.0, 20.0, 20.0), wm2.clone());
-    
-    //Creates a new scene using the area light, a plane, and a sphere
-    let scene: Scene = Scene {
-        light_sources: vec![
-            Box::new(pl),
-            Box::new(light),
-        ],
-        objects: vec![
-            Box::new(sphere1),
-            Box::new(sphere2),
-            Box::new(sphere3),
-            Box::new(cube1),
-        ],
+    //Command line options override the camera from the scene file
+    if let Some(width) = options.width {
+        file.camera.width = width;
+    }
+    if let Some(height) = options.height {
+        file.camera.height = height;
+    }
+    if let Some(fov) = options.fov {
+        file.camera.fov = fov;
+    }
+    if let Some(projection) = options.projection {
+        file.camera.projection = projection;
+    }
+    if !file.camera.fov_is_valid() {
+        return Err(String::from("A field of view of 180 degrees or more needs the fisheye projection"));
+    }
+    if let Some(aperture) = options.aperture {
+        file.camera.aperture = aperture;
+    }
+    if let Some(focal_distance) = options.focal_distance {
+        file.camera.focal_distance = Some(focal_distance);
+    }
+    let mut camera = file.camera();
+    camera.depth = options.depth;
+    camera.set_integrator(options.integrator.integrator());
+    if let Some(crop) = &options.crop {
+        //A window whose edge overflows can not fit either
+        let right = crop.x.checked_add(crop.width);
+        let bottom = crop.y.checked_add(crop.height);
+        if right.is_none_or(|right| right > camera.hsize) || bottom.is_none_or(|bottom| bottom > camera.vsize) {
+            return Err(format!("The crop window must fit inside the {}x{} image", camera.hsize, camera.vsize));
+        }
+        camera.set_crop(crop.x, crop.y, crop.width, crop.height);
+    }
+    let checkpoint = match &options.checkpoint {
+        Some(path) => {
+            let path = match options.frames {
+                Some(_) => frame_path(path, time as usize),
+                None => path.clone(),
+            };
+            let mut checkpoint = Checkpoint::new(&path, render_fingerprint(options, &file, time)?);
+            checkpoint.interval = options.checkpoint_interval;
+            camera.set_checkpoint(checkpoint.clone());
+            Some(checkpoint)
+        }
+        None => None,
     };
-    
-    //Creates a camera and defines its properties
-    let mut camera = Camera::new(WIDTH, HEIGHT, 45.0);
-    let start_pos = Vec4::new(0.0, 1.6, -5.5, 1.0);
-    let end_pos = Vec4::new(0.0, 1.0, 1.0, 1.0);
-    let up_vec = Vec4::new(0.0, 1.0, 0.0, 0.0);
 
-    //Transforms the view according to the camera transformation
-    camera.transform(Matrix4x4::view_transform(start_pos, end_pos, up_vec));
+    //Canvas where color is stored, which only covers the crop window when writing a cropped image
+    let mut canvas = match &options.crop {
+        Some(crop) if options.cropped => Canvas::new(crop.width as usize, crop.height as usize),
+        _ => Canvas::new(file.camera.width, file.camera.height),
+    };
 
     println!("Render started...");
     let now = Instant::now();
+    let mut stopped = false;
 
-    Camera::render_supersampled(&camera, &scene, &mut canvas);
+    if options.quick {
+        Camera::quick_render(&camera, &mut file.scene, &mut canvas);
+    }
+    else if let Some(adaptive) = &options.adaptive {
+        let stats = Camera::render_adaptive_parallel(&camera, &file.scene, &mut canvas, adaptive, options.threads);
+        println!(
+            "Traced {} camera rays, {:.2} per pixel, refining {} of {} pixels",
+            stats.rays,
+            stats.rays_per_pixel(),
+            stats.refined_pixels,
+            stats.pixels
+        );
+    }
+    else if let Some(progressive) = &options.progressive {
+        let stop = stop_on_enter();
+        println!("Press Enter to stop and keep the image so far");
+        let stats = Camera::render_progressive(
+            &camera,
+            &file.scene,
+            &mut canvas,
+            progressive,
+            options.threads,
+            &stop,
+            |snapshot, samples| match snapshot.save_with(output_path, &image_settings(options)) {
+                Ok(()) => println!("Wrote snapshot with {} samples per pixel to {}", samples, output_path),
+                Err(error) => eprintln!("Failed to write snapshot {}: {}", output_path, error),
+            },
+        );
+        println!("Traced {} camera rays, {:.2} per pixel", stats.rays, stats.rays_per_pixel());
+        stopped = stats.stopped;
+    }
+    else if options.samples.is_some() {
+        Camera::render_sampled_parallel(&camera, &file.scene, &mut canvas, &options.sampler, options.threads);
+    }
+    else {
+        Camera::render_grid_parallel(&camera, &file.scene, &mut canvas, options.supersample, options.threads);
+    }
 
     let duration = now.elapsed();
     println!("Image successfully rendered");
-    println!(
-        "{} milliseconds elapsed.",
-        duration.as_secs() * 1000 + u64::from(duration.subsec_millis())
+    println!("{} milliseconds elapsed.", duration.as_millis());
+
+    canvas
+        .save_with(output_path, &image_settings(options))
+        .map_err(|error| format!("Failed to write {}: {}", output_path, error))?;
+    println!("Wrote canvas to {}", output_path);
+    //A progressive render which was stopped keeps its checkpoint so it can be continued
+    if let Some(checkpoint) = checkpoint.filter(|_| !stopped) {
+        checkpoint
+            .remove()
+            .map_err(|error| format!("Failed to remove checkpoint {}: {}", checkpoint.path, error))?;
+    }
+    Ok(())
+}
+
+//Identifies a render by its scene file and every setting which changes the traced rays
+//Obj and image files used by the scene are not included, so changing them needs a new checkpoint
+//The sample count, snapshot interval and time limit of progressive renders are left out so a stopped render can be continued further
+fn render_fingerprint(options: &RenderOptions, file: &SceneFile, time: f32) -> Result<u64, String> {
+    let text = fs::read_to_string(&options.scene_path).map_err(|error| format!("{}: {}", options.scene_path, error))?;
+    let progressive = options.progressive.as_ref().map(|progressive| {
+        (progressive.sampler.pattern, progressive.sampler.filter, progressive.sampler.seed)
+    });
+    let description = format!(
+        "{}\n{:?}\n{:?} {} {:?} {} {:?} {:?} {:?} {:?} {}",
+        text,
+        file.camera,
+        options.crop,
+        options.depth,
+        options.integrator,
+        options.supersample,
+        options.samples.map(|_| &options.sampler),
+        options.adaptive,
+        progressive,
+        options.frames,
+        time
     );
-    Canvas::write_file(canvas, "image");
+    Ok(fingerprint(&description))
+}
+
+//Gets the settings used to encode the output image
+fn image_settings(options: &RenderOptions) -> ImageSettings {
+    ImageSettings {
+        bit_depth: options.bit_depth,
+        tone: options.tone.clone(),
+    }
+}
+
+//Creates a flag which is set when a line is entered, so a progressive render can be stopped without losing it
+//Reaching the end of the input, as when it is not a terminal, leaves the flag unset
+fn stop_on_enter() -> Arc<AtomicBool> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let flag = stop.clone();
+    thread::spawn(move || {
+        let mut line = String::new();
+        if let Ok(count) = io::stdin().read_line(&mut line) {
+            if count > 0 {
+                flag.store(true, Ordering::Relaxed);
+            }
+        }
+    });
+    stop
 }
@@ -0,0 +1,500 @@
+use crate::core::color::*;
+use crate::core::vector::Vec4;
+use crate::misc::random::random_float;
+use crate::world::lighting::*;
+use crate::world::sampler::sample_disk;
+use std::f32::consts::PI;
+use std::fmt::Debug;
+
+//A BSDF describes how a surface scatters the light arriving at it, both reflecting and transmitting it
+//Directions point away from the surface, with e_vec towards the eye and n_vec the normal on the side of the eye
+//BSDFs are created while shading, which may happen on any render thread, so they must be Send and Sync
+pub trait Bsdf: Debug + Send + Sync {
+    //Finds how much light arriving along light_vec is scattered along e_vec, per unit of solid angle
+    //Perfectly specular BSDFs scatter light along single directions, so they always give black
+    fn eval(&self, n_vec: &Vec4, e_vec: &Vec4, light_vec: &Vec4) -> Color;
+
+    //Picks a random direction for light to arrive from, returning None if the light is absorbed
+    fn sample(&self, n_vec: &Vec4, e_vec: &Vec4) -> Option<BsdfSample>;
+
+    //Finds the probability density of sample picking light_vec, which is 0 for perfectly specular BSDFs
+    fn pdf(&self, n_vec: &Vec4, e_vec: &Vec4, light_vec: &Vec4) -> f32;
+
+    //Finds the directions light arrives along for the parts of the BSDF which reflect and refract like mirrors and glass,
+    //which Whitted shading traces rays along while lights give the rest
+    //Smooth parts give each of their directions once, and rough parts give the given number of random directions
+    //whose weights add up to their average
+    fn specular(&self, _n_vec: &Vec4, _e_vec: &Vec4, _samples: usize) -> Vec<BsdfSample> {
+        vec![]
+    }
+}
+
+//A direction picked by a BSDF
+#[derive(Debug, PartialEq, Clone)]
+pub struct BsdfSample {
+    pub direction: Vec4,
+    pub weight: Color, //The BSDF times the cosine with the normal divided by the pdf, which scales the light arriving along the direction
+    pub pdf: f32,
+    pub specular: bool, //Whether the direction was the only one possible, as with mirrors and smooth glass
+}
+
+//A perfectly matte surface which scatters light equally in every direction
+#[derive(Debug, PartialEq, Clone)]
+pub struct Lambertian {
+    pub albedo: Color, //Fraction of the light which is scattered rather than absorbed
+}
+
+impl Bsdf for Lambertian {
+    fn eval(&self, n_vec: &Vec4, e_vec: &Vec4, light_vec: &Vec4) -> Color {
+        if !same_side(n_vec, e_vec, light_vec) {
+            return BLACK;
+        }
+        &self.albedo * (1.0 / PI)
+    }
+
+    fn sample(&self, n_vec: &Vec4, _e_vec: &Vec4) -> Option<BsdfSample> {
+        let direction = cosine_sample_hemisphere(n_vec, random_float(), random_float());
+        let pdf = Vec4::dot(&direction, n_vec).max(0.0) / PI;
+        Some(BsdfSample {
+            direction,
+            weight: self.albedo.clone(),
+            pdf,
+            specular: false,
+        })
+    }
+
+    fn pdf(&self, n_vec: &Vec4, e_vec: &Vec4, light_vec: &Vec4) -> f32 {
+        if !same_side(n_vec, e_vec, light_vec) {
+            return 0.0;
+        }
+        Vec4::dot(light_vec, n_vec) / PI
+    }
+}
+
+//A perfect mirror
+#[derive(Debug, PartialEq, Clone)]
+pub struct Mirror {
+    pub color: Color, //Tint of the reflection
+}
+
+impl Bsdf for Mirror {
+    fn eval(&self, _n_vec: &Vec4, _e_vec: &Vec4, _light_vec: &Vec4) -> Color {
+        BLACK
+    }
+
+    fn sample(&self, n_vec: &Vec4, e_vec: &Vec4) -> Option<BsdfSample> {
+        Some(BsdfSample {
+            direction: Vec4::reflect(&e_vec.negate(), n_vec),
+            weight: self.color.clone(),
+            pdf: 0.0,
+            specular: true,
+        })
+    }
+
+    fn pdf(&self, _n_vec: &Vec4, _e_vec: &Vec4, _light_vec: &Vec4) -> f32 {
+        0.0
+    }
+
+    fn specular(&self, n_vec: &Vec4, e_vec: &Vec4, _samples: usize) -> Vec<BsdfSample> {
+        self.sample(n_vec, e_vec).into_iter().collect()
+    }
+}
+
+//Glass, which reflects some of the light and refracts the rest depending on the angle
+//Rough glass bends light through a random microfacet rather than the surface itself, which blurs what is seen through it
+#[derive(Debug, PartialEq, Clone)]
+pub struct Dielectric {
+    pub n1: f32, //Refraction index on the side of the eye
+    pub n2: f32, //Refraction index on the other side of the surface
+    pub roughness: f32,
+    pub reflection: f32,   //Scales the reflected light, which the Phong model takes from the reflectivity
+    pub transmission: f32, //Scales the refracted light, which the Phong model takes from the transparency
+}
+
+impl Dielectric {
+    //Finds the directions light is reflected and refracted along through a normal, with the share of light along each
+    //The reflectance splits the light between them, except that glass which does not reflect refracts all of its light
+    //as with the Phong model, and only the reflection is left when the light is totally internally reflected
+    fn split(&self, n_vec: &Vec4, normal: &Vec4, e_vec: &Vec4) -> Vec<(Vec4, f32)> {
+        let mut reflected = Vec4::reflect(&e_vec.negate(), normal);
+        //Microfacets near the horizon may send light to the wrong side of the surface, which then takes the smooth direction
+        if Vec4::dot(&reflected, n_vec) <= 0.0 {
+            reflected = Vec4::reflect(&e_vec.negate(), n_vec);
+        }
+        let refracted = match refract(e_vec, normal, self.n1 / self.n2) {
+            Some(direction) if Vec4::dot(&direction, n_vec) < 0.0 => Some(direction),
+            Some(_) => refract(e_vec, n_vec, self.n1 / self.n2),
+            None => None,
+        };
+        let refracted = match refracted {
+            Some(direction) => direction,
+            None => return vec![(reflected, self.reflection)],
+        };
+        let reflectance = reflectance(Vec4::dot(e_vec, normal), self.n1, self.n2);
+        let transmitted = if self.reflection > 0.0 {
+            1.0 - reflectance
+        }
+        else {
+            1.0
+        };
+        vec![(reflected, self.reflection * reflectance), (refracted, self.transmission * transmitted)]
+    }
+
+    //Picks a microfacet normal facing the eye, which is the normal itself for smooth glass
+    fn microfacet(&self, n_vec: &Vec4, e_vec: &Vec4) -> Vec4 {
+        if self.roughness > 0.0 {
+            let microfacet = sample_ggx_normal(roughness_alpha(self.roughness), n_vec);
+            if Vec4::dot(&microfacet, e_vec) > 0.0 {
+                return microfacet;
+            }
+        }
+        n_vec.clone()
+    }
+}
+
+impl Bsdf for Dielectric {
+    fn eval(&self, _n_vec: &Vec4, _e_vec: &Vec4, _light_vec: &Vec4) -> Color {
+        BLACK
+    }
+
+    //Chooses between reflecting and refracting in proportion to their shares of the light
+    fn sample(&self, n_vec: &Vec4, e_vec: &Vec4) -> Option<BsdfSample> {
+        let directions = self.split(n_vec, &self.microfacet(n_vec, e_vec), e_vec);
+        let total: f32 = directions.iter().map(|(_, share)| share).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut choice = random_float() * total;
+        let (direction, _) = directions
+            .iter()
+            .find(|(_, share)| {
+                choice -= share;
+                choice < 0.0
+            })
+            .unwrap_or(directions.last()?);
+        Some(BsdfSample {
+            direction: direction.clone(),
+            weight: WHITE * total,
+            pdf: 0.0,
+            specular: true,
+        })
+    }
+
+    fn pdf(&self, _n_vec: &Vec4, _e_vec: &Vec4, _light_vec: &Vec4) -> f32 {
+        0.0
+    }
+
+    fn specular(&self, n_vec: &Vec4, e_vec: &Vec4, samples: usize) -> Vec<BsdfSample> {
+        let samples = if self.roughness > 0.0 {
+            samples.max(1)
+        }
+        else {
+            1
+        };
+        let mut list = vec![];
+        for _ in 0..samples {
+            for (direction, share) in self.split(n_vec, &self.microfacet(n_vec, e_vec), e_vec) {
+                if share > 0.0 {
+                    list.push(BsdfSample {
+                        direction,
+                        weight: WHITE * (share / samples as f32),
+                        pdf: 0.0,
+                        specular: true,
+                    });
+                }
+            }
+        }
+        list
+    }
+}
+
+//A rough metal, whose microfacets reflect light tinted by its color
+#[derive(Debug, PartialEq, Clone)]
+pub struct RoughConductor {
+    pub color: Color, //Reflectance when looking straight at the surface
+    pub roughness: f32,
+}
+
+impl Bsdf for RoughConductor {
+    fn eval(&self, n_vec: &Vec4, e_vec: &Vec4, light_vec: &Vec4) -> Color {
+        microfacet_brdf(&self.color, 1.0, self.roughness, n_vec, e_vec, light_vec)
+    }
+
+    fn sample(&self, n_vec: &Vec4, e_vec: &Vec4) -> Option<BsdfSample> {
+        let direction = sample_ggx_reflection(roughness_alpha(self.roughness), n_vec, e_vec)?;
+        weighted_sample(self, n_vec, e_vec, direction)
+    }
+
+    fn pdf(&self, n_vec: &Vec4, e_vec: &Vec4, light_vec: &Vec4) -> f32 {
+        ggx_reflection_pdf(roughness_alpha(self.roughness), n_vec, e_vec, light_vec)
+    }
+
+    fn specular(&self, n_vec: &Vec4, e_vec: &Vec4, samples: usize) -> Vec<BsdfSample> {
+        microfacet_specular(&self.color, self.roughness, n_vec, e_vec, samples, |light_vec| self.eval(n_vec, e_vec, light_vec))
+    }
+}
+
+//A diffuse base under a clear coat, as with plastic, where the coat reflects more light at grazing angles
+#[derive(Debug, PartialEq, Clone)]
+pub struct Plastic {
+    pub color: Color, //Color of the diffuse base
+    pub roughness: f32, //Roughness of the coat
+}
+
+impl Plastic {
+    //Finds the chance of sampling the coat rather than the base, which follows how much of the light the coat reflects
+    fn coat_chance(&self, n_vec: &Vec4, e_vec: &Vec4) -> f32 {
+        let coat = fresnel_schlick(&Color::new(0.04, 0.04, 0.04), Vec4::dot(n_vec, e_vec)).0;
+        //The coat is always sampled sometimes, since its highlights are much brighter than the base
+        coat.clamp(0.25, 0.9)
+    }
+}
+
+impl Bsdf for Plastic {
+    fn eval(&self, n_vec: &Vec4, e_vec: &Vec4, light_vec: &Vec4) -> Color {
+        microfacet_brdf(&self.color, 0.0, self.roughness, n_vec, e_vec, light_vec)
+    }
+
+    fn sample(&self, n_vec: &Vec4, e_vec: &Vec4) -> Option<BsdfSample> {
+        let direction = if random_float() < self.coat_chance(n_vec, e_vec) {
+            sample_ggx_reflection(roughness_alpha(self.roughness), n_vec, e_vec)?
+        }
+        else {
+            cosine_sample_hemisphere(n_vec, random_float(), random_float())
+        };
+        weighted_sample(self, n_vec, e_vec, direction)
+    }
+
+    fn pdf(&self, n_vec: &Vec4, e_vec: &Vec4, light_vec: &Vec4) -> f32 {
+        if !same_side(n_vec, e_vec, light_vec) {
+            return 0.0;
+        }
+        let coat = self.coat_chance(n_vec, e_vec);
+        let diffuse_pdf = Vec4::dot(light_vec, n_vec) / PI;
+        coat * ggx_reflection_pdf(roughness_alpha(self.roughness), n_vec, e_vec, light_vec) + (1.0 - coat) * diffuse_pdf
+    }
+
+    //Only the coat reflects like a mirror, while the base is matte
+    fn specular(&self, n_vec: &Vec4, e_vec: &Vec4, samples: usize) -> Vec<BsdfSample> {
+        microfacet_specular(&Color::new(0.04, 0.04, 0.04), self.roughness, n_vec, e_vec, samples, |light_vec| {
+            microfacet_brdf(&BLACK, 0.0, self.roughness, n_vec, e_vec, light_vec)
+        })
+    }
+}
+
+//Several BSDFs on the same surface, each scaled by a weight
+//Weights need not add up to 1, so a surface can be both fully diffuse and partly reflective as with the Phong model
+#[derive(Debug)]
+pub struct Mix {
+    pub lobes: Vec<(f32, Box<dyn Bsdf>)>,
+}
+
+impl Mix {
+    //Sums the weights, which sample uses to choose a lobe in proportion to its weight
+    fn total_weight(&self) -> f32 {
+        self.lobes.iter().map(|(weight, _)| weight).sum()
+    }
+}
+
+impl Bsdf for Mix {
+    fn eval(&self, n_vec: &Vec4, e_vec: &Vec4, light_vec: &Vec4) -> Color {
+        let mut total = BLACK;
+        for (weight, lobe) in &self.lobes {
+            total = total + lobe.eval(n_vec, e_vec, light_vec) * *weight;
+        }
+        total
+    }
+
+    fn sample(&self, n_vec: &Vec4, e_vec: &Vec4) -> Option<BsdfSample> {
+        let total_weight = self.total_weight();
+        if total_weight <= 0.0 {
+            return None;
+        }
+        let mut choice = random_float() * total_weight;
+        let (_, lobe) = self
+            .lobes
+            .iter()
+            .find(|(weight, _)| {
+                choice -= weight;
+                choice < 0.0
+            })
+            .unwrap_or(self.lobes.last()?);
+        let sample = lobe.sample(n_vec, e_vec)?;
+        //A specular direction can only come from the chosen lobe, so only the chance of choosing it is divided out
+        if sample.specular {
+            return Some(BsdfSample {
+                weight: sample.weight * total_weight,
+                ..sample
+            });
+        }
+        weighted_sample(self, n_vec, e_vec, sample.direction)
+    }
+
+    fn pdf(&self, n_vec: &Vec4, e_vec: &Vec4, light_vec: &Vec4) -> f32 {
+        let total_weight = self.total_weight();
+        if total_weight <= 0.0 {
+            return 0.0;
+        }
+        self.lobes
+            .iter()
+            .map(|(weight, lobe)| weight / total_weight * lobe.pdf(n_vec, e_vec, light_vec))
+            .sum()
+    }
+
+    fn specular(&self, n_vec: &Vec4, e_vec: &Vec4, samples: usize) -> Vec<BsdfSample> {
+        let mut list = vec![];
+        for (weight, lobe) in &self.lobes {
+            for sample in lobe.specular(n_vec, e_vec, samples) {
+                list.push(BsdfSample {
+                    weight: sample.weight * *weight,
+                    ..sample
+                });
+            }
+        }
+        list
+    }
+}
+
+//Finds the fraction of light reflected by a smooth boundary between refraction indices with Schlick's approximation
+//cos_i is the cosine between the eye and normal vectors, and total internal reflection reflects everything
+pub fn reflectance(cos_i: f32, n1: f32, n2: f32) -> f32 {
+    let mut cos = cos_i;
+    if n1 > n2 {
+        //Ratio between refraction indices
+        let n_ratio = n1 / n2;
+        //sin2_t is used to detect internal refraction
+        let sin2_t = (n_ratio * n_ratio) * (1.0 - (cos_i * cos_i));
+        if sin2_t > 1.0 {
+            return 1.0;
+        }
+        cos = (1.0 - sin2_t).sqrt();
+    }
+    let r0 = ((n1 - n2) / (n1 + n2)).powi(2);
+    r0 + (1.0 - r0) * ((1.0 - cos).powi(5))
+}
+
+//Finds the direction light from the eye bends to as it passes through a surface, where n_ratio is n1 / n2
+//Returns None when the light is totally internally reflected
+pub fn refract(e_vec: &Vec4, n_vec: &Vec4, n_ratio: f32) -> Option<Vec4> {
+    let cos_i = Vec4::dot(e_vec, n_vec);
+    //sin2_t is used to detect internal refraction
+    let sin2_t = (n_ratio.powi(2)) * (1.0 - (cos_i.powi(2)));
+    if sin2_t > 1.0 {
+        return None;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+    Some((n_vec * (n_ratio * cos_i - cos_t)) - (e_vec * n_ratio))
+}
+
+//Maps a point on the unit square to a direction on the hemisphere around a normal
+//Directions are spread in proportion to their cosine with the normal, matching how much light a diffuse surface takes from them
+pub fn cosine_sample_hemisphere(normal: &Vec4, u: f32, v: f32) -> Vec4 {
+    let (x, y) = sample_disk(u, v);
+    let z = (1.0 - x * x - y * y).max(0.0).sqrt();
+    let (tangent, bitangent) = tangents(normal);
+    (&tangent * x + &bitangent * y + normal * z).normalize()
+}
+
+//Picks a microfacet normal in proportion to the GGX distribution of microfacets around a normal
+pub fn sample_ggx_normal(alpha: f32, n_vec: &Vec4) -> Vec4 {
+    let u = random_float();
+    let phi = 2.0 * PI * random_float();
+    let cos_theta = ((1.0 - u) / (1.0 + (alpha * alpha - 1.0) * u)).sqrt();
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let (tangent, bitangent) = tangents(n_vec);
+    &tangent * (sin_theta * phi.cos()) + &bitangent * (sin_theta * phi.sin()) + n_vec * cos_theta
+}
+
+//Picks a microfacet normal and reflects the eye vector about it
+//Returns None if the reflection points into the surface
+fn sample_ggx_reflection(alpha: f32, n_vec: &Vec4, e_vec: &Vec4) -> Option<Vec4> {
+    let half_vec = sample_ggx_normal(alpha, n_vec);
+    let direction = Vec4::reflect(&e_vec.negate(), &half_vec);
+    if Vec4::dot(&direction, n_vec) <= 0.0 {
+        return None;
+    }
+    Some(direction)
+}
+
+//Finds the probability density of sample_ggx_reflection picking light_vec
+fn ggx_reflection_pdf(alpha: f32, n_vec: &Vec4, e_vec: &Vec4, light_vec: &Vec4) -> f32 {
+    if !same_side(n_vec, e_vec, light_vec) {
+        return 0.0;
+    }
+    let half_vec = (e_vec + light_vec).normalize();
+    let cos_half = Vec4::dot(&half_vec, n_vec);
+    ggx_distribution(alpha, cos_half) * cos_half / (4.0 * Vec4::dot(e_vec, &half_vec).abs())
+}
+
+//Finds the directions Whitted shading traces for a microfacet reflection with the given reflectance straight on
+//A smooth surface reflects like a mirror tinted by the Fresnel reflectance, while a rough one averages random directions
+//picked by the GGX distribution, where eval gives the value of the reflection in a direction
+//Microfacets which reflect into the surface give the smooth reflection instead
+fn microfacet_specular<F>(f0: &Color, roughness: f32, n_vec: &Vec4, e_vec: &Vec4, samples: usize, eval: F) -> Vec<BsdfSample>
+where
+    F: Fn(&Vec4) -> Color,
+{
+    let mirror = |share: f32| BsdfSample {
+        direction: Vec4::reflect(&e_vec.negate(), n_vec),
+        weight: fresnel_schlick(f0, Vec4::dot(n_vec, e_vec)) * share,
+        pdf: 0.0,
+        specular: true,
+    };
+    if roughness <= 0.0 {
+        return vec![mirror(1.0)];
+    }
+    let alpha = roughness_alpha(roughness);
+    let samples = samples.max(1);
+    let mut list = vec![];
+    for _ in 0..samples {
+        let sample = sample_ggx_reflection(alpha, n_vec, e_vec).and_then(|direction| {
+            let pdf = ggx_reflection_pdf(alpha, n_vec, e_vec, &direction);
+            if pdf <= 0.0 {
+                return None;
+            }
+            let weight = eval(&direction) * (Vec4::dot(&direction, n_vec) / (pdf * samples as f32));
+            Some(BsdfSample {
+                direction,
+                weight,
+                pdf,
+                specular: false,
+            })
+        });
+        list.push(sample.unwrap_or_else(|| mirror(1.0 / samples as f32)));
+    }
+    list
+}
+
+//Creates a sample for a direction picked by a BSDF from its value and pdf in that direction
+fn weighted_sample(bsdf: &dyn Bsdf, n_vec: &Vec4, e_vec: &Vec4, direction: Vec4) -> Option<BsdfSample> {
+    let pdf = bsdf.pdf(n_vec, e_vec, &direction);
+    if pdf <= 0.0 {
+        return None;
+    }
+    let weight = bsdf.eval(n_vec, e_vec, &direction) * (Vec4::dot(&direction, n_vec).abs() / pdf);
+    Some(BsdfSample {
+        direction,
+        weight,
+        pdf,
+        specular: false,
+    })
+}
+
+//Finds two vectors which form a right handed basis with a normal
+pub fn tangents(normal: &Vec4) -> (Vec4, Vec4) {
+    //Any vector which is not parallel to the normal gives a tangent
+    let helper = if normal.0.abs() > 0.9 {
+        Vec4::new(0.0, 1.0, 0.0, 0.0)
+    }
+    else {
+        Vec4::new(1.0, 0.0, 0.0, 0.0)
+    };
+    let tangent = (&helper * normal).normalize();
+    let bitangent = normal * &tangent;
+    (tangent, bitangent)
+}
+
+//Tests whether the eye and light are both above the surface
+fn same_side(n_vec: &Vec4, e_vec: &Vec4, light_vec: &Vec4) -> bool {
+    Vec4::dot(n_vec, e_vec) > 0.0 && Vec4::dot(n_vec, light_vec) > 0.0
+}
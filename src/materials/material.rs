@@ -1,9 +1,27 @@
-use crate::core::color::Color;
+use crate::core::color::*;
 use crate::misc::utils::clamp_float;
 use crate::materials::patterns::*;
 
+//Ways a surface can reflect the light which falls on it
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ReflectionModel {
+    Phong,      //Uses ambient, diffuse, specular and shininess
+    Microfacet, //Uses the color as the base color along with metallic and roughness, with a GGX specular lobe
+}
+
+impl ReflectionModel {
+    //Gets a reflection model from the name used in scene files
+    pub fn from_name(name: &str) -> Option<ReflectionModel> {
+        match name {
+            "phong" => Some(ReflectionModel::Phong),
+            "microfacet" => Some(ReflectionModel::Microfacet),
+            _ => None,
+        }
+    }
+}
+
 //A Material holds a bunch of properties for an object
-//Lighting properties are based on the Phong Reflection Model
+//Lighting properties are based on the Phong Reflection Model unless the microfacet model is chosen
 #[derive(Debug, Clone, PartialEq)]
 pub struct Material {
     pub color: Color,
@@ -17,6 +35,11 @@ pub struct Material {
     pub environment_lighting: f32,
     pub casts_shadows: bool,
     pub pattern: Option<Box<dyn Pattern>>,
+    pub emission: Color, //Light given off by the surface, which is black for surfaces which only reflect light
+    pub model: ReflectionModel,
+    pub metallic: f32,  //From 0 for a dielectric such as plastic to 1 for a metal, which tints its reflections with the color
+    pub roughness: f32, //From 0 for a smooth surface with sharp highlights and reflections to 1 for a rough one which blurs them
+    pub glossy_samples: usize, //Number of rays traced for each reflection or refraction of a rough surface
 }
 
 impl Material {
@@ -46,6 +69,11 @@ impl Material {
             environment_lighting,
             casts_shadows,
             pattern,
+            emission: BLACK,
+            model: ReflectionModel::Phong,
+            metallic: 0.0,
+            roughness: 0.0,
+            glossy_samples: 8,
         }
     }
 
@@ -83,6 +111,11 @@ impl Material {
             environment_lighting: 0.0,
             casts_shadows: true,
             pattern: None,
+            emission: BLACK,
+            model: ReflectionModel::Phong,
+            metallic: 0.0,
+            roughness: 0.0,
+            glossy_samples: 8,
         }
     }
 }
@@ -1,2 +1,4 @@
+pub mod bsdf;
 pub mod material;
-pub mod patterns;
\ No newline at end of file
+pub mod patterns;
+pub mod texture;
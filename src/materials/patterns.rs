@@ -1,12 +1,15 @@
+use crate::core::canvas::Canvas;
 use crate::core::color::Color;
 use crate::core::vector::Vec4;
 use crate::core::matrix::Matrix4x4;
+use crate::materials::texture::*;
 use crate::objects::object::*;
 use std::fmt::Debug;
 use std::any::Any;
+use std::sync::Arc;
 
 //Generic enum pattern which matches to specific patterns
-pub trait Pattern: Debug + PatternClone {
+pub trait Pattern: Debug + PatternClone + Send + Sync {
     //Gets the color at a point on the pattern
     fn color_at(&self, point: &Vec4) -> Color;
     
@@ -16,6 +19,12 @@ pub trait Pattern: Debug + PatternClone {
     //Finds the color on a pattern given the transformations of the matrix the pattern is on
     fn color_at_object(&self, list: &Vec<Matrix4x4>, object_inverse: &Matrix4x4, point: &Vec4) -> Color;
 
+    //Finds the color on a pattern given the texture coordinates stored on the object, if it has any
+    //Only patterns which use surface coordinates need to override this
+    fn color_at_uv(&self, list: &Vec<Matrix4x4>, object_inverse: &Matrix4x4, point: &Vec4, _uv: Option<(f32, f32)>) -> Color {
+        self.color_at_object(list, object_inverse, point)
+    }
+
     //Methods used to allow PartialEq between objects
     fn eq(&self, other: &dyn Pattern) -> bool;
     fn as_any(&self) -> &dyn Any;
@@ -291,4 +300,66 @@ impl Pattern for CheckerboardPattern  {
     }
 
     fn as_any(&self) -> &dyn Any { self }
-}
\ No newline at end of file
+}
+
+//A pattern which samples an image using texture coordinates
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImagePattern {
+    image: Arc<Canvas>, //Shared so cloning materials does not copy the image
+    mapping: UvMapping,
+    transform: Matrix4x4,
+    inverse: Matrix4x4,
+}
+
+impl ImagePattern {
+    //Creates a new ImagePattern
+    pub fn new(image: Arc<Canvas>, mapping: UvMapping, transform: Matrix4x4) -> ImagePattern {
+        ImagePattern {
+            inverse: transform.inverse().unwrap(),
+            image,
+            mapping,
+            transform,
+        }
+    }
+
+    //Gets the color of the image at texture coordinates
+    pub fn color_at_texture(&self, u: f32, v: f32) -> Color {
+        //Latitude does not wrap around a sphere so the poles are clamped instead
+        sample_bilinear(&self.image, u, v, self.mapping != UvMapping::Spherical)
+    }
+}
+
+impl Pattern for ImagePattern {
+    //Gets the color at a specific point
+    fn color_at(&self, point: &Vec4) -> Color {
+        let (u, v) = self.mapping.map(point);
+        self.color_at_texture(u, v)
+    }
+
+    //Transforms the pattern
+    fn transform(&mut self, matrix: Matrix4x4) {
+        self.transform = &self.transform * matrix;
+    }
+
+    //Gets the color at a specific point taking into account pattern and object transformations
+    fn color_at_object(&self, list: &Vec<Matrix4x4>, object_inverse: &Matrix4x4, point: &Vec4) -> Color {
+        let group_point = world_to_object(list, point);
+        let object_point = object_inverse * group_point;
+        let pattern_point = &self.inverse * object_point;
+        self.color_at(&pattern_point)
+    }
+
+    //Uses the object's texture coordinates when the pattern is mapped by surface
+    fn color_at_uv(&self, list: &Vec<Matrix4x4>, object_inverse: &Matrix4x4, point: &Vec4, uv: Option<(f32, f32)>) -> Color {
+        match (self.mapping, uv) {
+            (UvMapping::Surface, Some((u, v))) => self.color_at_texture(u, v),
+            _ => self.color_at_object(list, object_inverse, point),
+        }
+    }
+
+    fn eq(&self, other: &dyn Pattern) -> bool {
+        other.as_any().downcast_ref::<Self>().is_some_and(|x| x == self)
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+}
@@ -0,0 +1,108 @@
+use crate::core::canvas::Canvas;
+use crate::core::color::Color;
+use crate::core::vector::Vec4;
+use std::f32::consts::PI;
+
+//Ways of turning a point in pattern space into (u, v) texture coordinates between 0 and 1
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum UvMapping {
+    Spherical,   //Wraps the texture around a unit sphere like a globe
+    Planar,      //Repeats the texture every unit across the xz plane
+    Cylindrical, //Wraps the texture around the y axis and repeats it every unit of height
+    Cube,        //Maps the whole texture onto each face of a unit cube
+    Surface,     //Uses coordinates stored on the object, such as obj texture vertices
+}
+
+impl UvMapping {
+    //Finds the texture coordinates of a point
+    //Surface coordinates can not be found from a point alone so planar mapping is used instead
+    pub fn map(&self, point: &Vec4) -> (f32, f32) {
+        match self {
+            UvMapping::Spherical => spherical_map(point),
+            UvMapping::Planar | UvMapping::Surface => planar_map(point),
+            UvMapping::Cylindrical => cylindrical_map(point),
+            UvMapping::Cube => cube_map(point),
+        }
+    }
+}
+
+//Finds how far around the y axis a point is, between 0 and 1
+fn azimuth(point: &Vec4) -> f32 {
+    let theta = point.0.atan2(point.2);
+    1.0 - (theta / (2.0 * PI) + 0.5)
+}
+
+//Maps a point on a sphere centered at the origin using its longitude and latitude
+pub fn spherical_map(point: &Vec4) -> (f32, f32) {
+    let radius = (point.0 * point.0 + point.1 * point.1 + point.2 * point.2).sqrt();
+    if radius == 0.0 {
+        return (0.0, 0.5);
+    }
+    let phi = (point.1 / radius).clamp(-1.0, 1.0).acos();
+    (azimuth(point), 1.0 - phi / PI)
+}
+
+//Maps a point using its x and z coordinates
+pub fn planar_map(point: &Vec4) -> (f32, f32) {
+    (point.0.rem_euclid(1.0), point.2.rem_euclid(1.0))
+}
+
+//Maps a point using its angle around the y axis and its height
+pub fn cylindrical_map(point: &Vec4) -> (f32, f32) {
+    (azimuth(point), point.1.rem_euclid(1.0))
+}
+
+//Maps a point on a cube from -1 to 1 onto the face with the largest coordinate
+pub fn cube_map(point: &Vec4) -> (f32, f32) {
+    let Vec4(x, y, z, _) = *point;
+    let largest = x.abs().max(y.abs()).max(z.abs());
+    let wrap = |value: f32| value.rem_euclid(2.0) / 2.0;
+    if largest == x {
+        (wrap(1.0 - z), wrap(y + 1.0))
+    }
+    else if largest == -x {
+        (wrap(z + 1.0), wrap(y + 1.0))
+    }
+    else if largest == y {
+        (wrap(x + 1.0), wrap(1.0 - z))
+    }
+    else if largest == -y {
+        (wrap(x + 1.0), wrap(z + 1.0))
+    }
+    else if largest == z {
+        (wrap(x + 1.0), wrap(y + 1.0))
+    }
+    else {
+        (wrap(1.0 - x), wrap(y + 1.0))
+    }
+}
+
+//Samples a canvas at texture coordinates by blending the four nearest pixels
+//u runs left to right and v bottom to top; u always repeats while v repeats only if wrap_v is set
+pub fn sample_bilinear(canvas: &Canvas, u: f32, v: f32, wrap_v: bool) -> Color {
+    if canvas.width == 0 || canvas.height == 0 {
+        return Color(0.0, 0.0, 0.0);
+    }
+    let x = u * canvas.width as f32 - 0.5;
+    let y = (1.0 - v) * canvas.height as f32 - 0.5;
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let x_fraction = x - x0;
+    let y_fraction = y - y0;
+
+    let column = |offset: f32| (x0 + offset).rem_euclid(canvas.width as f32) as usize % canvas.width;
+    let row = |offset: f32| {
+        if wrap_v {
+            (y0 + offset).rem_euclid(canvas.height as f32) as usize % canvas.height
+        }
+        else {
+            (y0 + offset).clamp(0.0, (canvas.height - 1) as f32) as usize
+        }
+    };
+    let pixel = |column: usize, row: usize| &canvas.contents[row * canvas.width + column];
+
+    let (left, right, top, bottom) = (column(0.0), column(1.0), row(0.0), row(1.0));
+    let upper = pixel(left, top) * (1.0 - x_fraction) + pixel(right, top) * x_fraction;
+    let lower = pixel(left, bottom) * (1.0 - x_fraction) + pixel(right, bottom) * x_fraction;
+    upper * (1.0 - y_fraction) + lower * y_fraction
+}
@@ -1,2 +1,4 @@
 pub mod axis;
-pub mod utils;
\ No newline at end of file
+pub mod utils;
+pub mod options;
+pub mod random;
@@ -0,0 +1,346 @@
+use crate::image::tone::*;
+use crate::world::camera::{Projection, Tile};
+use crate::world::integrator::IntegratorKind;
+use crate::world::sampler::*;
+use std::convert::TryFrom;
+
+//Usage message printed by the renderer binary
+pub const USAGE: &str = "Usage: rust_ray_tracer [OPTIONS] <SCENE>
+
+Renders a scene file to an image.
+
+Options:
+  -o, --output <PATH>        Output image path, .ppm, .png, .pfm or .hdr (default image.ppm)
+      --bit-depth <BITS>     Bits per channel of png output, 8 or 16 (default 8)
+      --exposure <STOPS>     Brighten or darken the image before tone mapping (default 0)
+      --tonemap <OPERATOR>   Tone mapping operator: clamp, reinhard or aces (default clamp)
+      --srgb                 Encode the output with the sRGB gamma curve
+      --width <PIXELS>       Override the image width from the scene file
+      --height <PIXELS>      Override the image height from the scene file
+      --fov <DEGREES>        Override the field of view from the scene file
+      --projection <NAME>    Override the projection from the scene file: perspective, orthographic, fisheye or
+                             equirectangular
+      --aperture <RADIUS>    Override the lens radius from the scene file, 0 keeps everything in focus
+      --focal-distance <DISTANCE>
+                             Override the distance to the plane in focus from the scene file
+      --crop <X,Y,WIDTH,HEIGHT>
+                             Only render the pixels inside a rectangle, leaving the rest of the image black
+      --cropped              Write only the pixels inside the crop rectangle
+  -s, --supersample <LEVEL>  Trace a LEVEL x LEVEL grid of rays per pixel (default 1)
+      --samples <COUNT>      Trace COUNT randomly placed rays per pixel instead of a grid
+      --sampler <PATTERN>    Placement of the random rays: random or stratified (default stratified)
+      --filter <FILTER>      Reconstruction filter: box, tent, gaussian or mitchell (default box)
+      --seed <NUMBER>        Seed for random sampling, the same seed gives the same image (default 0)
+      --progressive <COUNT>  Refine the whole image pass after pass until COUNT samples per pixel, writing the image
+                             so far as it goes, and stopping early when Enter is pressed
+      --snapshot <SECONDS>   Time between writing progressive snapshots (default 10)
+      --time-limit <SECONDS> Stop a progressive render after SECONDS, keeping the image so far
+      --adaptive <THRESHOLD> Only refine pixels whose color differs from a neighbour by more than THRESHOLD
+      --adaptive-depth <LEVELS>
+                             Number of times a refined pixel may be split in four (default 2)
+      --checkpoint <PATH>    Save progress to PATH as the render goes, and resume from it if it holds an unfinished
+                             render of the same scene and settings, deleting it once the image is written
+      --checkpoint-interval <SECONDS>
+                             Time between saving the checkpoint (default 60)
+      --frames <COUNT>       Render frames 0 to COUNT - 1 of an animation, numbering each output file by replacing
+                             a run of # in the output path or adding the frame before the extension
+      --integrator <NAME>    How rays are shaded: whitted, path, flat, normals, depth or ao (default whitted)
+                             The path tracer lights objects with light bounced off other objects and emissive
+                             surfaces, and needs many samples per pixel to smooth out its noise
+  -d, --depth <COUNT>        Maximum reflection and refraction depth, or number of bounces when path tracing
+                             (default 5)
+  -t, --threads <COUNT>      Number of worker threads, 0 uses every core (default 0)
+  -q, --quick                Render object colors without lighting
+  -h, --help                 Print this message";
+
+//Options given to the renderer binary on the command line
+#[derive(Debug, PartialEq, Clone)]
+pub struct RenderOptions {
+    pub scene_path: String,
+    pub output_path: String,
+    pub bit_depth: u8,
+    pub tone: ToneSettings,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub fov: Option<f32>,
+    pub projection: Option<Projection>,
+    pub aperture: Option<f32>,
+    pub focal_distance: Option<f32>,
+    pub crop: Option<Tile>,
+    pub cropped: bool,
+    pub supersample: usize,
+    pub samples: Option<usize>,
+    pub sampler: SamplerSettings,
+    pub adaptive: Option<AdaptiveSettings>,
+    pub progressive: Option<ProgressiveSettings>,
+    pub checkpoint: Option<String>,
+    pub checkpoint_interval: f32,
+    pub frames: Option<usize>,
+    pub integrator: IntegratorKind,
+    pub depth: i32,
+    pub threads: usize,
+    pub quick: bool,
+    pub help: bool,
+}
+
+impl RenderOptions {
+    //Parses the command line arguments, not including the program name
+    pub fn parse(args: &[String]) -> Result<RenderOptions, String> {
+        let mut options = RenderOptions {
+            scene_path: String::new(),
+            output_path: String::from("image.ppm"),
+            bit_depth: 8,
+            tone: ToneSettings::default(),
+            width: None,
+            height: None,
+            fov: None,
+            projection: None,
+            aperture: None,
+            focal_distance: None,
+            crop: None,
+            cropped: false,
+            supersample: 1,
+            samples: None,
+            sampler: SamplerSettings::default(),
+            adaptive: None,
+            progressive: None,
+            checkpoint: None,
+            checkpoint_interval: 60.0,
+            frames: None,
+            integrator: IntegratorKind::Whitted,
+            depth: 5,
+            threads: 0,
+            quick: false,
+            help: false,
+        };
+        let mut scene_path = None;
+        let mut adaptive_depth = None;
+        let mut snapshot_interval = None;
+        let mut time_limit = None;
+        let mut checkpoint_interval = None;
+        let mut remaining = args.iter();
+        while let Some(arg) = remaining.next() {
+            match arg.as_str() {
+                "-h" | "--help" => options.help = true,
+                "-q" | "--quick" => options.quick = true,
+                "-o" | "--output" => options.output_path = value(arg, remaining.next())?,
+                "--bit-depth" => {
+                    let bit_depth = number::<u8>(arg, remaining.next())?;
+                    if bit_depth != 8 && bit_depth != 16 {
+                        return Err(format!("{} must be 8 or 16", arg));
+                    }
+                    options.bit_depth = bit_depth;
+                }
+                "--exposure" => options.tone.exposure = float(arg, remaining.next())?,
+                "--tonemap" => {
+                    let name = value(arg, remaining.next())?;
+                    options.tone.tone_map =
+                        ToneMap::from_name(&name).ok_or_else(|| format!("Unknown tone mapping operator {}", name))?;
+                }
+                "--srgb" => options.tone.srgb = true,
+                "--width" => options.width = Some(positive(arg, remaining.next())?),
+                "--height" => options.height = Some(positive(arg, remaining.next())?),
+                "--fov" => {
+                    let fov = float(arg, remaining.next())?;
+                    if fov <= 0.0 || fov >= 360.0 {
+                        return Err(format!("{} must be between 0 and 360 degrees", arg));
+                    }
+                    options.fov = Some(fov);
+                }
+                "--projection" => {
+                    let name = value(arg, remaining.next())?;
+                    options.projection =
+                        Some(Projection::from_name(&name).ok_or_else(|| format!("Unknown projection {}", name))?);
+                }
+                "--aperture" => {
+                    let aperture = float(arg, remaining.next())?;
+                    if aperture < 0.0 {
+                        return Err(format!("{} can not be negative", arg));
+                    }
+                    options.aperture = Some(aperture);
+                }
+                "--focal-distance" => {
+                    let distance = float(arg, remaining.next())?;
+                    if distance <= 0.0 {
+                        return Err(format!("{} must be greater than 0", arg));
+                    }
+                    options.focal_distance = Some(distance);
+                }
+                "--crop" => {
+                    let text = value(arg, remaining.next())?;
+                    //Values which are negative or do not fit in an i32 are rejected rather than wrapped
+                    let numbers: Vec<i32> = text
+                        .split(',')
+                        .filter_map(|number| number.trim().parse::<u32>().ok().and_then(|number| i32::try_from(number).ok()))
+                        .collect();
+                    if numbers.len() != 4 || text.split(',').count() != 4 {
+                        return Err(format!("{} is not a valid value for {}, expected X,Y,WIDTH,HEIGHT", text, arg));
+                    }
+                    if numbers[2] == 0 || numbers[3] == 0 {
+                        return Err(format!("{} width and height must be greater than 0", arg));
+                    }
+                    options.crop = Some(Tile {
+                        x: numbers[0],
+                        y: numbers[1],
+                        width: numbers[2],
+                        height: numbers[3],
+                    });
+                }
+                "--cropped" => options.cropped = true,
+                "-s" | "--supersample" => options.supersample = positive(arg, remaining.next())?,
+                "--samples" => options.samples = Some(positive(arg, remaining.next())?),
+                "--sampler" => {
+                    let name = value(arg, remaining.next())?;
+                    options.sampler.pattern =
+                        SamplePattern::from_name(&name).ok_or_else(|| format!("Unknown sample pattern {}", name))?;
+                }
+                "--filter" => {
+                    let name = value(arg, remaining.next())?;
+                    options.sampler.filter = Filter::from_name(&name).ok_or_else(|| format!("Unknown filter {}", name))?;
+                }
+                "--seed" => options.sampler.seed = number::<u64>(arg, remaining.next())?,
+                "--adaptive" => {
+                    let threshold = float(arg, remaining.next())?;
+                    if threshold < 0.0 {
+                        return Err(format!("{} can not be negative", arg));
+                    }
+                    options.adaptive = Some(AdaptiveSettings {
+                        threshold,
+                        ..AdaptiveSettings::default()
+                    });
+                }
+                "--progressive" => {
+                    options.progressive = Some(ProgressiveSettings {
+                        sampler: SamplerSettings::new(positive(arg, remaining.next())?),
+                        ..ProgressiveSettings::default()
+                    });
+                }
+                "--snapshot" => snapshot_interval = Some(seconds(arg, remaining.next())?),
+                "--time-limit" => time_limit = Some(seconds(arg, remaining.next())?),
+                "--adaptive-depth" => adaptive_depth = Some(number::<u32>(arg, remaining.next())?),
+                "--checkpoint" => options.checkpoint = Some(value(arg, remaining.next())?),
+                "--checkpoint-interval" => checkpoint_interval = Some(seconds(arg, remaining.next())?),
+                "--frames" => options.frames = Some(positive(arg, remaining.next())?),
+                "--integrator" => {
+                    let name = value(arg, remaining.next())?;
+                    options.integrator =
+                        IntegratorKind::from_name(&name).ok_or_else(|| format!("Unknown integrator {}", name))?;
+                }
+                "-d" | "--depth" => options.depth = number::<i32>(arg, remaining.next())?,
+                "-t" | "--threads" => options.threads = number::<usize>(arg, remaining.next())?,
+                flag if flag.starts_with('-') => return Err(format!("Unknown option {}", flag)),
+                path => {
+                    if scene_path.is_some() {
+                        return Err(format!("Unexpected argument {}", path));
+                    }
+                    scene_path = Some(String::from(path));
+                }
+            }
+        }
+        if options.samples.is_some() && options.supersample > 1 {
+            return Err(String::from("--supersample and --samples can not be used together"));
+        }
+        if options.adaptive.is_some() && (options.samples.is_some() || options.supersample > 1) {
+            return Err(String::from("--adaptive can not be used with --supersample or --samples"));
+        }
+        match (&mut options.adaptive, adaptive_depth) {
+            (Some(adaptive), Some(depth)) => {
+                if depth > 8 {
+                    return Err(String::from("--adaptive-depth must be at most 8"));
+                }
+                adaptive.max_depth = depth;
+            }
+            (None, Some(_)) => return Err(String::from("--adaptive-depth needs --adaptive")),
+            _ => {}
+        }
+        if options.progressive.is_some()
+            && (options.samples.is_some() || options.supersample > 1 || options.adaptive.is_some())
+        {
+            return Err(String::from("--progressive can not be used with --supersample, --samples or --adaptive"));
+        }
+        if let Some(progressive) = &mut options.progressive {
+            //Progressive renders take their pattern, filter and seed from the same options as --samples
+            progressive.sampler = SamplerSettings {
+                samples: progressive.sampler.samples,
+                ..options.sampler.clone()
+            };
+            progressive.snapshot_interval = snapshot_interval.unwrap_or(progressive.snapshot_interval);
+            progressive.time_limit = time_limit;
+        }
+        else if snapshot_interval.is_some() || time_limit.is_some() {
+            return Err(String::from("--snapshot and --time-limit need --progressive"));
+        }
+        if options.cropped && options.crop.is_none() {
+            return Err(String::from("--cropped needs --crop"));
+        }
+        match (&options.checkpoint, checkpoint_interval) {
+            (Some(_), Some(interval)) => options.checkpoint_interval = interval,
+            (None, Some(_)) => return Err(String::from("--checkpoint-interval needs --checkpoint")),
+            _ => {}
+        }
+        if options.integrator != IntegratorKind::Whitted && options.quick {
+            return Err(String::from("--integrator can not be used with --quick"));
+        }
+        if options.integrator == IntegratorKind::PathTracer && options.adaptive.is_some() {
+            return Err(String::from("--integrator path can not be used with --adaptive"));
+        }
+        if options.checkpoint.is_some() && options.quick {
+            return Err(String::from("--checkpoint can not be used with --quick"));
+        }
+        match options.samples {
+            Some(samples) => options.sampler.samples = samples,
+            None if options.progressive.is_some() => {}
+            None if options.sampler != SamplerSettings::default() => {
+                return Err(String::from("--sampler, --filter and --seed need --samples or --progressive"));
+            }
+            None => {}
+        }
+        match scene_path {
+            Some(path) => options.scene_path = path,
+            None if options.help => {}
+            None => return Err(String::from("No scene file given")),
+        }
+        Ok(options)
+    }
+}
+
+//Gets the value following an option
+fn value(option: &str, next: Option<&String>) -> Result<String, String> {
+    match next {
+        Some(value) => Ok(value.clone()),
+        None => Err(format!("{} needs a value", option)),
+    }
+}
+
+//Parses the value following an option as a number
+fn number<T: std::str::FromStr>(option: &str, next: Option<&String>) -> Result<T, String> {
+    let text = value(option, next)?;
+    text.parse::<T>().map_err(|_| format!("{} is not a valid value for {}", text, option))
+}
+
+//Parses the value following an option as a finite number, rejecting values such as nan and inf
+fn float(option: &str, next: Option<&String>) -> Result<f32, String> {
+    let result = number::<f32>(option, next)?;
+    if !result.is_finite() {
+        return Err(format!("{} must be a finite number", option));
+    }
+    Ok(result)
+}
+
+//Parses the value following an option as a number of seconds, which can not be negative
+fn seconds(option: &str, next: Option<&String>) -> Result<f32, String> {
+    let result = float(option, next)?;
+    if result < 0.0 {
+        return Err(format!("{} must be a number of seconds", option));
+    }
+    Ok(result)
+}
+
+//Parses the value following an option as a number greater than zero
+fn positive(option: &str, next: Option<&String>) -> Result<usize, String> {
+    let result = number::<usize>(option, next)?;
+    if result == 0 {
+        return Err(format!("{} must be greater than 0", option));
+    }
+    Ok(result)
+}
@@ -0,0 +1,33 @@
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+
+//Every thread has its own generator so random sampling does not need locking
+//Renderers reseed it before each pixel so the result does not depend on which thread shades the pixel
+thread_local! {
+    static GENERATOR: RefCell<SmallRng> = RefCell::new(SmallRng::from_rng(rand::thread_rng()).unwrap());
+}
+
+//Restarts the current thread's generator from a seed
+pub fn reseed(seed: u64) {
+    GENERATOR.with(|generator| *generator.borrow_mut() = SmallRng::seed_from_u64(seed));
+}
+
+//Gets a random number between low (inclusive) and high (exclusive)
+pub fn random_range(low: f32, high: f32) -> f32 {
+    GENERATOR.with(|generator| generator.borrow_mut().gen_range(low, high))
+}
+
+//Gets a random number between 0 and 1
+pub fn random_float() -> f32 {
+    random_range(0.0, 1.0)
+}
+
+//Mixes a seed with pixel coordinates into a well distributed seed for that pixel
+pub fn pixel_seed(seed: u64, x: i32, y: i32) -> u64 {
+    let mut value = seed ^ ((x as u32 as u64) << 32 | y as u32 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    //Finalizer from splitmix64
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
+}
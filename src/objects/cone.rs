@@ -1,5 +1,6 @@
 use crate::core::matrix::Matrix4x4;
 use crate::core::vector::Vec4;
+use crate::core::bounds::BoundingBox;
 use crate::objects::object::*;
 use crate::materials::material::*;
 use crate::ray_tracing::ray::Ray;
@@ -67,6 +68,19 @@ impl Object for Cone {
         &self.inverse
     }
 
+    //Returns the cone transform
+    fn get_transform(&self) -> &Matrix4x4 {
+        &self.transform
+    }
+
+    //Finds the bounds of the cone in object space
+    fn bounds(&self) -> BoundingBox {
+        let limit = self.minimum.abs().max(self.maximum.abs());
+        //Caps are checked against the unsquared radius so they can reach past the sides of the cone
+        let radius = limit.max(limit.sqrt());
+        BoundingBox::new(Vec4(-radius, self.minimum, -radius, 1.0), Vec4(radius, self.maximum, radius, 1.0))
+    }
+
     //Intersects a ray with a cone
     fn intersect(&self, ray: &Ray) -> Option<Vec<Intersection>> {
         let transformed_ray = Ray::transform(ray, &self.inverse);
@@ -1,5 +1,6 @@
 use crate::core::matrix::Matrix4x4;
 use crate::core::vector::Vec4;
+use crate::core::bounds::BoundingBox;
 use crate::objects::object::*;
 use crate::ray_tracing::intersection::Intersection;
 use crate::materials::material::*;
@@ -94,6 +95,20 @@ impl Object for CSG {
         &self.inverse
     }
 
+    //Returns the csg transform
+    fn get_transform(&self) -> &Matrix4x4 {
+        &self.transform
+    }
+
+    //Finds the bounds of the csg in object space
+    fn bounds(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::empty();
+        for object in &self.objects {
+            bounds.merge(&object.parent_space_bounds());
+        }
+        bounds
+    }
+
     //Intersects a ray with a csg
     fn intersect(&self, ray: &Ray) -> Option<Vec<Intersection>> {
         let transformed_ray = Ray::transform(ray, &self.inverse);
@@ -171,6 +186,10 @@ impl Object for CSG {
         let mut final_intersections = vec![];
 
         for intersection in &mut valid_intersections {
+            if !intersection.motion_inverses.is_empty() {
+                final_intersections.push(intersection.clone().into_parent_space(&self.inverse));
+                continue;
+            }
             let new_intersection;
             if intersection.u == None {
                 new_intersection = Intersection::new(
@@ -231,4 +250,8 @@ impl Object for CSG {
     }
 
     fn as_any(&self) -> &dyn Any { self }
+
+    fn includes(&self, object: &dyn Object) -> bool {
+        self.objects.iter().any(|child| child.includes(object))
+    }
 }
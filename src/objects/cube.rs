@@ -1,5 +1,6 @@
 use crate::core::matrix::Matrix4x4;
 use crate::core::vector::Vec4;
+use crate::core::bounds::BoundingBox;
 use crate::objects::object::*;
 use crate::materials::material::*;
 use crate::ray_tracing::ray::Ray;
@@ -78,6 +79,16 @@ impl Object for Cube {
         &self.inverse
     }
 
+    //Returns the cube transform
+    fn get_transform(&self) -> &Matrix4x4 {
+        &self.transform
+    }
+
+    //Finds the bounds of the cube in object space
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(Vec4(-1.0, -1.0, -1.0, 1.0), Vec4(1.0, 1.0, 1.0, 1.0))
+    }
+
     //Intersects a ray with a cube
     fn intersect(&self, ray: &Ray) -> Option<Vec<Intersection>> {
         let transformed_ray = Ray::transform(ray, &self.inverse);
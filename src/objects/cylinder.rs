@@ -1,5 +1,6 @@
 use crate::core::matrix::Matrix4x4;
 use crate::core::vector::Vec4;
+use crate::core::bounds::BoundingBox;
 use crate::objects::object::*;
 use crate::materials::material::*;
 use crate::ray_tracing::ray::Ray;
@@ -67,6 +68,16 @@ impl Object for Cylinder {
         &self.inverse
     }
 
+    //Returns the cylinder transform
+    fn get_transform(&self) -> &Matrix4x4 {
+        &self.transform
+    }
+
+    //Finds the bounds of the cylinder in object space
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(Vec4(-1.0, self.minimum, -1.0, 1.0), Vec4(1.0, self.maximum, 1.0, 1.0))
+    }
+
     //Intersects a ray with a cylinder
     fn intersect(&self, ray: &Ray) -> Option<Vec<Intersection>> {
         let transformed_ray = Ray::transform(ray, &self.inverse);
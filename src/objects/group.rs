@@ -1,12 +1,16 @@
 use crate::core::matrix::Matrix4x4;
 use crate::core::vector::Vec4;
+use crate::core::bounds::BoundingBox;
 use crate::objects::object::*;
 use crate::materials::material::*;
 use crate::ray_tracing::ray::Ray;
 use crate::ray_tracing::intersection::Intersection;
+use crate::ray_tracing::bvh::Bvh;
 use std::any::Any;
 
-#[derive(Debug, PartialEq, Clone)]
+//A list of objects which share a transform and material
+//Replacing or transforming one of the objects does not update the bvh, so build_bvh must be called again after any such edit
+#[derive(Debug, Clone)]
 pub struct Group {
     pub transform: Matrix4x4,
     pub inverse: Matrix4x4,
@@ -14,6 +18,19 @@ pub struct Group {
     pub objects: Vec<Box<dyn Object>>,
     parent_inverses: Vec<Matrix4x4>,
     pub parent_material: Option<Material>,
+    pub bvh: Option<Bvh>, //Cached hierarchy over objects, which is not compared by PartialEq
+}
+
+impl PartialEq for Group {
+    //Compares everything but the bvh, which is only a cache of the objects
+    fn eq(&self, other: &Group) -> bool {
+        self.transform == other.transform
+            && self.inverse == other.inverse
+            && self.material == other.material
+            && self.objects == other.objects
+            && self.parent_inverses == other.parent_inverses
+            && self.parent_material == other.parent_material
+    }
 }
 
 impl Group {
@@ -26,6 +43,7 @@ impl Group {
             objects: vec![],
             parent_inverses: vec![],
             parent_material: None,
+            bvh: None,
         };
         group
     }
@@ -39,6 +57,28 @@ impl Group {
             objects: vec![],
             parent_inverses: vec![],
             parent_material: None,
+            bvh: None,
+        }
+    }
+
+    //Adds an object which has already been boxed to the group
+    pub fn add_object(&mut self, mut object: Box<dyn Object>) {
+        object.push_parent_inverse(self.inverse.clone());
+        object.set_parent_material(&self.material);
+        self.objects.push(object);
+    }
+
+    //Builds a bounding volume hierarchy over the objects currently in the group
+    //Objects added afterwards are still intersected, but the hierarchy is ignored until it is rebuilt
+    pub fn build_bvh(&mut self) {
+        self.bvh = Some(Bvh::build(&self.objects));
+    }
+
+    //Finds the indices of the objects which a ray could intersect
+    fn candidates(&self, ray: &Ray) -> Vec<usize> {
+        match &self.bvh {
+            Some(bvh) if bvh.is_current(&self.objects) => bvh.candidates(ray),
+            _ => (0..self.objects.len()).collect(),
         }
     }
 }
@@ -54,14 +94,32 @@ impl<'a> Object for Group {
         &self.inverse
     }
 
+    //Returns the group transform
+    fn get_transform(&self) -> &Matrix4x4 {
+        &self.transform
+    }
+
+    //Finds the bounds of the group in object space
+    fn bounds(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::empty();
+        for object in &self.objects {
+            bounds.merge(&object.parent_space_bounds());
+        }
+        bounds
+    }
+
     //Intersects a ray with a group
     fn intersect(&self, ray: &Ray) -> Option<Vec<Intersection>> {
         let transformed_ray = Ray::transform(ray, &self.inverse);
         let mut intersections: Vec<Intersection> = vec![];
-        for object in &self.objects {
-            let object_intersections = object.intersect(&transformed_ray);
+        for index in self.candidates(&transformed_ray) {
+            let object_intersections = self.objects[index].intersect(&transformed_ray);
             if object_intersections != None {
                 for intersection in object_intersections.unwrap() {
+                    if !intersection.motion_inverses.is_empty() {
+                        intersections.push(intersection.into_parent_space(&self.inverse));
+                        continue;
+                    }
                     let new_intersection;
                     if intersection.u == None {
                         new_intersection = Intersection::new(
@@ -72,11 +130,13 @@ impl<'a> Object for Group {
                         );
                     }
                     else {
-                        new_intersection = Intersection::new(
+                        new_intersection = Intersection::new_uv(
                             intersection.t,
                             Ray::position(&transformed_ray, intersection.t),
                             (intersection.object).normal(&Ray::position(&ray, intersection.t), intersection.u, intersection.v),
                             intersection.object,
+                            intersection.u.unwrap(),
+                            intersection.v.unwrap(),
                         );
                     }
                     intersections.push(new_intersection);
@@ -127,4 +187,8 @@ impl<'a> Object for Group {
     }
 
     fn as_any(&self) -> &dyn Any { self }
+
+    fn includes(&self, object: &dyn Object) -> bool {
+        self.objects.iter().any(|child| child.includes(object))
+    }
 }
\ No newline at end of file
@@ -9,6 +9,7 @@ pub mod smooth_triangle;
 
 pub mod group;
 pub mod csg;
+pub mod moving;
 
 pub mod parser;
 
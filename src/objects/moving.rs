@@ -0,0 +1,174 @@
+use crate::core::bounds::BoundingBox;
+use crate::core::matrix::Matrix4x4;
+use crate::core::vector::Vec4;
+use crate::materials::material::*;
+use crate::objects::group::Group;
+use crate::objects::object::*;
+use crate::ray_tracing::intersection::Intersection;
+use crate::ray_tracing::ray::Ray;
+use crate::world::animation::*;
+use std::any::Any;
+
+//A time and the transform at that time
+pub type Keyframe = (f32, Matrix4x4);
+
+//Transforms at points in time, blended between neighbouring keyframes
+//Matrices are blended entry by entry, which is exact for translation and scaling but shrinks objects partway through large rotations,
+//so turning objects need a keyframe every few degrees
+#[derive(Debug, PartialEq, Clone)]
+pub struct MotionPath {
+    pub track: Track<Matrix4x4>,
+}
+
+impl MotionPath {
+    //Creates a path from keyframes in any order, moving in a straight line between them
+    pub fn new(keyframes: Vec<Keyframe>) -> MotionPath {
+        MotionPath::from_track(Track::new(
+            keyframes.into_iter().map(|(time, transform)| Key::new(time, transform)).collect(),
+        ))
+    }
+
+    //Creates a path from a track of transforms, which may ease between keyframes
+    pub fn from_track(track: Track<Matrix4x4>) -> MotionPath {
+        MotionPath { track }
+    }
+
+    //Creates a path which moves from one transform at time 0 to another at time 1
+    pub fn linear(start: Matrix4x4, end: Matrix4x4) -> MotionPath {
+        MotionPath::new(vec![(0.0, start), (1.0, end)])
+    }
+
+    //Finds the transform at a time, holding still before the first keyframe and after the last
+    pub fn at(&self, time: f32) -> Matrix4x4 {
+        self.track.at(time).unwrap_or_else(Matrix4x4::identity)
+    }
+}
+
+//Moves an object along a path over time, so rays cast at different times see it in different places
+//The object is intersected in the space of the path, so its own transform and any groups inside it are applied after the motion
+#[derive(Debug, Clone)]
+pub struct Moving {
+    pub object: Box<dyn Object>,
+    pub path: MotionPath,
+    pub transform: Matrix4x4,
+    pub inverse: Matrix4x4,
+    pub material: Material,
+    pub parent_inverses: Vec<Matrix4x4>,
+    pub parent_material: Option<Material>,
+}
+
+impl Moving {
+    //Creates a new Moving object
+    pub fn new(object: Box<dyn Object>, path: MotionPath) -> Moving {
+        Moving {
+            material: object.get_material().clone(),
+            object,
+            path,
+            transform: Matrix4x4::identity(),
+            inverse: Matrix4x4::identity(),
+            parent_inverses: vec![],
+            parent_material: None,
+        }
+    }
+}
+
+impl PartialEq for Moving {
+    fn eq(&self, other: &Moving) -> bool {
+        self.object.as_ref() == other.object.as_ref()
+            && self.path == other.path
+            && self.parent_inverses == other.parent_inverses
+            && self.parent_material == other.parent_material
+    }
+}
+
+impl Object for Moving {
+    //Returns the material of the moving object
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+
+    //Returns the inverse, which is always the identity as the motion depends on time
+    fn get_inverse(&self) -> &Matrix4x4 {
+        &self.inverse
+    }
+
+    //Returns the transform, which is always the identity as the motion depends on time
+    fn get_transform(&self) -> &Matrix4x4 {
+        &self.transform
+    }
+
+    //Finds the bounds of the object across every keyframe
+    //Blending matrices keeps every point between its keyframe positions, so the box covers the whole path
+    fn bounds(&self) -> BoundingBox {
+        let object_bounds = self.object.parent_space_bounds();
+        if self.path.track.keys.is_empty() {
+            return object_bounds;
+        }
+        let mut bounds = BoundingBox::empty();
+        for key in &self.path.track.keys {
+            bounds.merge(&object_bounds.transform(&key.value));
+        }
+        bounds
+    }
+
+    //Intersects a ray with the object where it is at the ray's time
+    fn intersect(&self, ray: &Ray) -> Option<Vec<Intersection<'_>>> {
+        let inverse = self.path.at(ray.time).affine_inverse()?;
+        let transformed_ray = Ray::transform(ray, &inverse);
+        let intersections = self.object.intersect(&transformed_ray)?;
+        Some(
+            intersections
+                .into_iter()
+                .map(|intersection| {
+                    let mut intersection = intersection.into_parent_space(&inverse);
+                    intersection.hit = Ray::position(ray, intersection.t);
+                    intersection
+                })
+                .collect(),
+        )
+    }
+
+    //Finds the normal where the object is at time 0, when the shutter opens by default
+    //Intersections already carry the normal at the ray's time, so this is only used outside of rendering
+    fn normal(&self, world_point: &Vec4, u: Option<f32>, v: Option<f32>) -> Vec4 {
+        let inverse = self.path.at(0.0).affine_inverse().unwrap_or_else(Matrix4x4::identity);
+        let path_point = &inverse * world_to_object(&self.parent_inverses, world_point);
+        let mut normal = inverse.transpose() * &self.object.normal(&path_point, u, v);
+        normal.3 = 0.0;
+        normal_to_world(&self.parent_inverses, &normal.normalize())
+    }
+
+    fn get_parent_inverses(&self) -> &Vec<Matrix4x4> {
+        &self.parent_inverses
+    }
+
+    //Parent inverses are kept here rather than passed to the object, which works in the space of the path
+    fn push_parent_inverse(&mut self, inverse: Matrix4x4) {
+        self.parent_inverses.push(inverse);
+    }
+
+    fn get_parent_material(&self) -> &Option<Material> {
+        &self.parent_material
+    }
+
+    fn set_parent_material(&mut self, material: &Material) {
+        self.parent_material = Some(material.clone());
+        self.object.set_parent_material(material);
+    }
+
+    fn add_to_group(mut self, group: &mut Group) {
+        self.push_parent_inverse(group.get_inverse().clone());
+        self.set_parent_material(&group.material);
+        group.objects.push(Box::new(self));
+    }
+
+    fn eq(&self, other: &dyn Object) -> bool {
+        other.as_any().downcast_ref::<Self>().is_some_and(|x| x == self)
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn includes(&self, object: &dyn Object) -> bool {
+        self.object.includes(object)
+    }
+}
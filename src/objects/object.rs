@@ -1,4 +1,5 @@
 use crate::core::vector::Vec4;
+use crate::core::bounds::BoundingBox;
 use crate::core::matrix::Matrix4x4;
 use crate::ray_tracing::intersection::Intersection;
 use crate::materials::material::*;
@@ -8,11 +9,21 @@ use std::fmt::Debug;
 use std::any::Any;
 
 //Trait which holds necessary methods for an object
-pub trait Object: Debug + ObjectClone {
+pub trait Object: Debug + ObjectClone + Send + Sync {
     //Returns the object material
     fn get_material(&self) -> &Material;
 
     fn get_inverse(&self) -> &Matrix4x4;
+
+    fn get_transform(&self) -> &Matrix4x4;
+
+    //Finds the bounds of the object in object space
+    fn bounds(&self) -> BoundingBox;
+
+    //Finds the bounds of the object in the space of its parent
+    fn parent_space_bounds(&self) -> BoundingBox {
+        self.bounds().transform(self.get_transform())
+    }
     
     //Intersects a given object with a ray
     fn intersect(&self, ray: &Ray) -> Option<Vec<Intersection>>;
@@ -20,6 +31,11 @@ pub trait Object: Debug + ObjectClone {
     //Finds the normal of an object at a given point
     fn normal(&self, _world_point: &Vec4, u: Option<f32>, v: Option<f32>) -> Vec4;
 
+    //Finds the texture coordinates stored on the object at an intersection's u and v, if it has any
+    fn texture_uv(&self, _u: Option<f32>, _v: Option<f32>) -> Option<(f32, f32)> {
+        None
+    }
+
     //Adds a given object to a group
     fn add_to_group(self, group: &mut Group);
 
@@ -34,6 +50,11 @@ pub trait Object: Debug + ObjectClone {
     //Methods used to allow PartialEq between objects
     fn eq(&self, other: &dyn Object) -> bool;
     fn as_any(&self) -> &dyn Any;
+
+    //Tests whether an object is this one or one of the objects inside it, as with the object an intersection hit
+    fn includes(&self, object: &dyn Object) -> bool {
+        Object::eq(self, object)
+    }
 }
 
 pub fn world_to_object(list: &Vec<Matrix4x4>, point: &Vec4) -> Vec4 {
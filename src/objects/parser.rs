@@ -1,3 +1,4 @@
+use std::fmt;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use crate::core::vector::Vec4;
@@ -7,9 +8,50 @@ use crate::objects::group::Group;
 use crate::objects::object::*;
 use crate::materials::material::*;
 
+//Describes an error found while reading an obj file
+#[derive(Debug, PartialEq, Clone)]
+pub struct ObjError {
+    pub line: usize, //Line of the obj file, counting from 1
+    pub message: String,
+}
+
+impl ObjError {
+    //Creates a new ObjError
+    pub fn new(line: usize, message: &str) -> ObjError {
+        ObjError {
+            line,
+            message: String::from(message),
+        }
+    }
+
+    //Parses the numbers of a statement after its keyword, of which there must be at least count
+    fn floats(split: &[&str], count: usize, line: usize) -> Result<Vec<f32>, ObjError> {
+        if split.len() < count + 1 {
+            return Err(ObjError::new(line, &format!("'{}' needs at least {} numbers", split[0], count)));
+        }
+        split[1..]
+            .iter()
+            .map(|token| token.parse::<f32>().map_err(|_| ObjError::new(line, &format!("'{}' is not a number", token))))
+            .collect()
+    }
+
+    //Parses the first three numbers of a statement after its keyword
+    fn triple(split: &[&str], line: usize) -> Result<[f32; 3], ObjError> {
+        let numbers = ObjError::floats(split, 3, line)?;
+        Ok([numbers[0], numbers[1], numbers[2]])
+    }
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
 pub struct Parser {
     pub vertices: Vec<Vec4>,
     pub normals: Vec<Vec4>,
+    pub texture_coords: Vec<(f32, f32)>,
     pub triangles: Vec<Triangle>,
     pub smooth_triangles: Vec<SmoothTriangle>,
 }
@@ -22,14 +64,17 @@ impl Parser {
         for smooth_triangle in self.smooth_triangles {
             smooth_triangle.add_to_group(group);
         }
+        group.build_bvh();
     }
 
-    pub fn parse_obj(file: File) -> Parser {
+    //Reads the vertices, normals, texture vertices and faces of an obj file
+    //Returns an error naming the line of the file which could not be read
+    pub fn parse_obj(file: File) -> Result<Parser, ObjError> {
         let file = BufReader::new(file);
-        let lines: Vec<String> = file.lines().map(|line| line.unwrap()).collect();
         let mut vertices: Vec<Vec4> = vec![];
         let mut triangles: Vec<Triangle> = vec![];
         let mut normals: Vec<Vec4> = vec![];
+        let mut texture_coords: Vec<(f32, f32)> = vec![];
         let mut smooth_triangles: Vec<SmoothTriangle> = vec![];
 
         let mut min_x = f32::INFINITY;
@@ -41,32 +86,21 @@ impl Parser {
         let mut min_z = f32::INFINITY;
         let mut max_z = -f32::INFINITY;
 
-        for line in lines {
-            if line.chars().nth(0) == None {
-                continue
-            }
-            if line.chars().nth(0).unwrap() == 'v' {
-                if line.chars().nth(1).unwrap() == 'n' {
-                    let split: Vec<&str> = line.split(" ").collect();
-                    let mut starting_index = 1;
-                    while split[starting_index] == "" {
-                        starting_index += 1;
-                    }
-                    let x = split[starting_index].parse::<f32>().unwrap();
-
-                    let y = split[starting_index + 1].parse::<f32>().unwrap();
-
-                    let z = split[starting_index + 2].parse::<f32>().unwrap();
-
+        for (index, line) in file.lines().enumerate() {
+            let number = index + 1;
+            let line = line.map_err(|error| ObjError::new(number, &error.to_string()))?;
+            let split: Vec<&str> = line.split_whitespace().collect();
+            match split.first() {
+                Some(&"vt") => {
+                    let coords = ObjError::floats(&split, 1, number)?;
+                    texture_coords.push((coords[0], coords.get(1).copied().unwrap_or(0.0)));
+                }
+                Some(&"vn") => {
+                    let [x, y, z] = ObjError::triple(&split, number)?;
                     normals.push(Vec4(x, y, z, 0.0));
                 }
-                else {
-                    let split: Vec<&str> = line.split(" ").collect();
-                    let mut starting_index = 1;
-                    while split[starting_index] == "" {
-                        starting_index += 1;
-                    }
-                    let x = split[starting_index].parse::<f32>().unwrap();
+                Some(&"v") => {
+                    let [x, y, z] = ObjError::triple(&split, number)?;
                     if x > max_x {
                         max_x = x;
                     }
@@ -74,7 +108,6 @@ impl Parser {
                         min_x = x
                     }
 
-                    let y = split[starting_index + 1].parse::<f32>().unwrap();
                     if y > max_y {
                         max_y = y;
                     }
@@ -82,97 +115,104 @@ impl Parser {
                         min_y = y;
                     }
 
-                    let z = split[starting_index + 2].parse::<f32>().unwrap();
                     if z > max_z {
                         max_z = z;
                     }
                     else if z < min_z {
                         min_z = z;
                     }
-                    
-                    vertices.push(Vec4(x, y, z, 1.0));
-                }
-            }
-            if line.chars().nth(0).unwrap() == 'f' {
-                let mut split: Vec<&str> = line.split(" ").collect();
-                split.retain(|string| string != &"");
-                if split[1].contains("/") {
-                    if split.len() > 4 {
-                        let mut vertex_indices: Vec<usize> = vec![];
-                        let mut normal_indices: Vec<usize> = vec![];
-                        for index in 1..split.len() {
-                            let subsplit: Vec<&str> = split[index].split("/").collect();
-                            let v = subsplit[0].parse::<usize>().unwrap() - 1;
-                            let vn = subsplit[subsplit.len() - 1].parse::<usize>().unwrap() - 1;
-                            vertex_indices.push(v);
-                            normal_indices.push(vn);
-                        }
-                        let polygon_triangles = Parser::fan_triangulation_smooth(&vertices, &normals, vertex_indices, normal_indices); 
-                        for triangle in polygon_triangles {
-                            smooth_triangles.push(triangle);
-                        } 
-                    }
-                    else {
-                        let subsplit1: Vec<&str> = split[1].split("/").collect();
-                        let v1 = subsplit1[0].replace("-", "").parse::<usize>().unwrap() - 1;
-                        let vn1 = subsplit1[subsplit1.len() - 1].replace("-", "").parse::<usize>().unwrap() - 1;
-
-                        let subsplit2: Vec<&str> = split[2].split("/").collect();
-                        let v2 = subsplit2[0].replace("-", "").parse::<usize>().unwrap() - 1;
-                        let vn2 = subsplit2[subsplit2.len() - 1].replace("-", "").parse::<usize>().unwrap() - 1;
-
-                        let subsplit3: Vec<&str> = split[3].split("/").collect();
-                        let v3 = subsplit3[0].replace("-", "").parse::<usize>().unwrap() - 1;
-                        let vn3 = subsplit3[subsplit3.len() - 1].replace("-", "").parse::<usize>().unwrap() - 1;
 
-                        smooth_triangles.push(SmoothTriangle::new(vertices[v1].clone(), vertices[v2].clone(), vertices[v3].clone(), normals[vn1].clone(), normals[vn2].clone(), normals[vn3].clone(), Material::default()));
-                    } 
+                    vertices.push(Vec4(x, y, z, 1.0));
                 }
-                else {
-                    if split.len() > 4 {
-                        let indices: Vec<usize> = split[1..].iter().map(|index| index.parse::<usize>().unwrap() - 1).collect();
-                        let polygon_triangles = Parser::fan_triangulation(&vertices, indices);
-                        for triangle in polygon_triangles {
-                            triangles.push(triangle);
-                        }
-                    }
-                    else {
-                        let v1 = split[1].parse::<usize>().unwrap() - 1;
-                        let v2 = split[2].parse::<usize>().unwrap() - 1;
-                        let v3 = split[3].parse::<usize>().unwrap() - 1;
-                        triangles.push(Triangle::new(vertices[v1].clone(), vertices[v2].clone(), vertices[v3].clone(), Material::default()));
+                Some(&"f") => {
+                    if split.len() < 4 {
+                        return Err(ObjError::new(number, "faces need at least 3 vertices"));
                     }
+                    let face = split[1..]
+                        .iter()
+                        .map(|token| FaceVertex::parse(token, vertices.len(), texture_coords.len(), normals.len()))
+                        .collect::<Option<Vec<FaceVertex>>>()
+                        .ok_or_else(|| ObjError::new(number, "invalid vertex index in face"))?;
+                    Parser::fan_triangulation(&vertices, &texture_coords, &normals, &face, &mut triangles, &mut smooth_triangles);
                 }
-            } 
-            else {
-                continue
+                _ => continue,
             }
         }
         println!("Dimensions: ");
         println!("min x: {}, max x: {}", min_x, max_x);
         println!("min y: {}, max y: {}", min_y, max_y);
         println!("min z: {}, max z: {}", min_z, max_z);
-        Parser {
+        Ok(Parser {
             vertices,
             normals,
+            texture_coords,
             triangles,
             smooth_triangles,
-        }
+        })
     }
 
-    fn fan_triangulation(vertices: &Vec<Vec4>, indices: Vec<usize>) -> Vec<Triangle> {
-        let mut triangles = vec![];
-        for index in 1..(indices.len() - 1) {
-            triangles.push(Triangle::new(vertices[indices[0]].clone(), vertices[indices[index]].clone(), vertices[indices[index + 1]].clone(), Material::default()));
+    //Splits a polygon into triangles which all share its first vertex
+    //Faces with a normal on every vertex become smooth triangles, and faces with valid texture vertices get texture coordinates
+    fn fan_triangulation(
+        vertices: &[Vec4],
+        texture_coords: &[(f32, f32)],
+        normals: &[Vec4],
+        face: &[FaceVertex],
+        triangles: &mut Vec<Triangle>,
+        smooth_triangles: &mut Vec<SmoothTriangle>,
+    ) {
+        let smooth = face.iter().all(|corner| corner.normal.is_some());
+        let textured = face.iter().all(|corner| corner.texture.is_some());
+        for index in 1..(face.len() - 1) {
+            let corners = [&face[0], &face[index], &face[index + 1]];
+            let points = corners.map(|corner| vertices[corner.vertex].clone());
+            let [p1, p2, p3] = points;
+            let coords = if textured { Some(corners.map(|corner| texture_coords[corner.texture.unwrap()])) } else { None };
+            if smooth {
+                let [n1, n2, n3] = corners.map(|corner| normals[corner.normal.unwrap()].clone());
+                let mut triangle = SmoothTriangle::new(p1, p2, p3, n1, n2, n3, Material::default());
+                triangle.texture_coords = coords;
+                smooth_triangles.push(triangle);
+            }
+            else {
+                let mut triangle = Triangle::new(p1, p2, p3, Material::default());
+                triangle.texture_coords = coords;
+                triangles.push(triangle);
+            }
         }
-        triangles
+    }
+}
+
+//Indices of the vertex, texture vertex and normal used by one corner of a face
+struct FaceVertex {
+    vertex: usize,
+    texture: Option<usize>,
+    normal: Option<usize>,
+}
+
+impl FaceVertex {
+    //Parses a corner written as v, v/vt, v//vn or v/vt/vn, returning None if the vertex index is invalid
+    //Texture vertices and normals which are missing or out of range are ignored
+    fn parse(token: &str, vertex_count: usize, texture_count: usize, normal_count: usize) -> Option<FaceVertex> {
+        let parts: Vec<&str> = token.split("/").collect();
+        Some(FaceVertex {
+            vertex: FaceVertex::resolve(parts[0], vertex_count)?,
+            texture: parts.get(1).and_then(|part| FaceVertex::resolve(part, texture_count)),
+            normal: parts.get(2).and_then(|part| FaceVertex::resolve(part, normal_count)),
+        })
     }
 
-    fn fan_triangulation_smooth(vertices: &Vec<Vec4>, normals: &Vec<Vec4>, vertex_indices: Vec<usize>, normal_indices: Vec<usize>) -> Vec<SmoothTriangle> {
-        let mut triangles = vec![];
-        for index in 1..(vertex_indices.len() - 1) {
-            triangles.push(SmoothTriangle::new(vertices[vertex_indices[0]].clone(), vertices[vertex_indices[index]].clone(), vertices[vertex_indices[index + 1]].clone(), normals[normal_indices[0]].clone(), normals[normal_indices[index]].clone(), normals[normal_indices[index + 1]].clone(), Material::default()));
+    //Converts a 1 based index, or a negative index counting back from the end, into a list index
+    fn resolve(index: &str, count: usize) -> Option<usize> {
+        let index = index.parse::<i64>().ok()?;
+        if index > 0 && index as usize <= count {
+            Some(index as usize - 1)
+        }
+        else if index < 0 && (-index) as usize <= count {
+            Some(count - (-index) as usize)
+        }
+        else {
+            None
         }
-        triangles
     }
-}
\ No newline at end of file
+}
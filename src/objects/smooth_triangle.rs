@@ -1,7 +1,9 @@
 use crate::core::matrix::*;
 use crate::misc::utils::*;
 use crate::core::vector::Vec4;
+use crate::core::bounds::BoundingBox;
 use crate::objects::object::*;
+use crate::objects::triangle::interpolate_texture_coords;
 use crate::ray_tracing::intersection::Intersection;
 use crate::materials::material::*;
 use crate::objects::group::Group;
@@ -18,6 +20,7 @@ pub struct SmoothTriangle {
     pub n3: Vec4,
     pub e1: Vec4,
     pub e2: Vec4,
+    pub texture_coords: Option<[(f32, f32); 3]>, //Texture coordinates at p1, p2 and p3
     pub material: Material,
     pub parent_inverses: Vec<Matrix4x4>,
     pub parent_material: Option<Material>,
@@ -35,6 +38,7 @@ impl SmoothTriangle {
             n3: Vec4(1.0, 0.0, 0.0, 0.0),
             e1: Vec4(-1.0, -1.0, 0.0, 0.0),
             e2: Vec4(1.0, -1.0, 0.0, 0.0),
+            texture_coords: None,
             material: Material::default(),
             parent_inverses: vec![],
             parent_material: None,
@@ -51,6 +55,7 @@ impl SmoothTriangle {
            n1,
            n2,
            n3,
+           texture_coords: None,
            material,
            parent_inverses: vec![],
            parent_material: None,
@@ -69,6 +74,20 @@ impl Object for SmoothTriangle {
         &IDENTITY
     }
 
+    //Returns the smooth triangle transform
+    fn get_transform(&self) -> &Matrix4x4 {
+        &IDENTITY
+    }
+
+    //Finds the bounds of the smooth triangle in object space
+    fn bounds(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::empty();
+        bounds.add_point(&self.p1);
+        bounds.add_point(&self.p2);
+        bounds.add_point(&self.p3);
+        bounds
+    }
+
     //Intersects a ray with a smooth triangle
     fn intersect(&self, ray: &Ray) -> Option<Vec<Intersection>> {
         let dir_cross_e2 = &ray.direction * &self.e2;
@@ -107,6 +126,11 @@ impl Object for SmoothTriangle {
         normal_to_world(&self.parent_inverses, &(&self.n2 * u.unwrap() + &self.n3 * v.unwrap() + &self.n1 * (1.0 - u.unwrap() - v.unwrap())).normalize())
     }
 
+    //Finds the texture coordinates of an intersection, if the triangle has them
+    fn texture_uv(&self, u: Option<f32>, v: Option<f32>) -> Option<(f32, f32)> {
+        Some(interpolate_texture_coords(self.texture_coords.as_ref()?, u?, v?))
+    }
+
     fn get_parent_inverses(&self) -> &Vec<Matrix4x4> {
         &self.parent_inverses
     }
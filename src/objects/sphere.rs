@@ -1,5 +1,6 @@
 use crate::core::matrix::Matrix4x4;
 use crate::core::vector::Vec4;
+use crate::core::bounds::BoundingBox;
 use crate::objects::object::*;
 use crate::ray_tracing::intersection::Intersection;
 use crate::materials::material::*;
@@ -65,6 +66,16 @@ impl Object for Sphere {
         &self.inverse
     }
 
+    //Returns the sphere transform
+    fn get_transform(&self) -> &Matrix4x4 {
+        &self.transform
+    }
+
+    //Finds the bounds of the sphere in object space
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(Vec4(-1.0, -1.0, -1.0, 1.0), Vec4(1.0, 1.0, 1.0, 1.0))
+    }
+
     //Intersects a ray with a sphere
     fn intersect(&self, ray: &Ray) -> Option<Vec<Intersection>> {
         let transformed_ray = Ray::transform(ray, &self.inverse);
@@ -1,6 +1,7 @@
 use crate::core::matrix::*;
 use crate::misc::utils::*;
 use crate::core::vector::Vec4;
+use crate::core::bounds::BoundingBox;
 use crate::objects::object::*;
 use crate::ray_tracing::intersection::Intersection;
 use crate::materials::material::*;
@@ -8,6 +9,14 @@ use crate::objects::group::Group;
 use crate::ray_tracing::ray::Ray;
 use std::any::Any;
 
+//Blends the texture coordinates of a triangle's corners using the barycentric u and v of an intersection
+//Shared by Triangle and SmoothTriangle, which store their corners in the same order
+pub fn interpolate_texture_coords(coords: &[(f32, f32); 3], u: f32, v: f32) -> (f32, f32) {
+    let [t1, t2, t3] = coords;
+    let w = 1.0 - u - v;
+    (t1.0 * w + t2.0 * u + t3.0 * v, t1.1 * w + t2.1 * u + t3.1 * v)
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Triangle {
     pub p1: Vec4,
@@ -16,6 +25,7 @@ pub struct Triangle {
     pub e1: Vec4,
     pub e2: Vec4,
     pub normal: Vec4,
+    pub texture_coords: Option<[(f32, f32); 3]>, //Texture coordinates at p1, p2 and p3
     pub material: Material,
     pub parent_inverses: Vec<Matrix4x4>,
     pub parent_material: Option<Material>,
@@ -31,6 +41,7 @@ impl Triangle {
             e1: Vec4(-1.0, -1.0, 0.0, 0.0),
             e2: Vec4(1.0, -1.0, 0.0, 0.0),
             normal: Vec4(0.0, 0.0, -1.0, 0.0),
+            texture_coords: None,
             material: Material::default(),
             parent_inverses: vec![],
             parent_material: None,
@@ -45,6 +56,7 @@ impl Triangle {
            p1,
            p2,
            p3,
+           texture_coords: None,
            material,
            parent_inverses: vec![],
            parent_material: None,
@@ -63,6 +75,20 @@ impl Object for Triangle {
         &IDENTITY
     }
 
+    //Returns the triangle transform
+    fn get_transform(&self) -> &Matrix4x4 {
+        &IDENTITY
+    }
+
+    //Finds the bounds of the triangle in object space
+    fn bounds(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::empty();
+        bounds.add_point(&self.p1);
+        bounds.add_point(&self.p2);
+        bounds.add_point(&self.p3);
+        bounds
+    }
+
     //Intersects a ray with a triangle
     fn intersect(&self, ray: &Ray) -> Option<Vec<Intersection>> {
         let dir_cross_e2 = &ray.direction * &self.e2;
@@ -84,11 +110,13 @@ impl Object for Triangle {
         let t = f * Vec4::dot(&self.e2, &origin_cross_e1);
         Some(
             vec![
-                Intersection::new(
+                Intersection::new_uv(
                     t,
                     Ray::position(&ray, t),
                     self.normal(&Ray::position(&ray, t), None, None),
                     self,
+                    u,
+                    v,
                 )
             ]
         )
@@ -99,6 +127,11 @@ impl Object for Triangle {
        normal_to_world(&self.parent_inverses, &self.normal)
     }
 
+    //Finds the texture coordinates of an intersection, if the triangle has them
+    fn texture_uv(&self, u: Option<f32>, v: Option<f32>) -> Option<(f32, f32)> {
+        Some(interpolate_texture_coords(self.texture_coords.as_ref()?, u?, v?))
+    }
+
     fn get_parent_inverses(&self) -> &Vec<Matrix4x4> {
         &self.parent_inverses
     }
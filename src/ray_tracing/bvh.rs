@@ -0,0 +1,140 @@
+use crate::core::bounds::*;
+use crate::misc::axis::Axis;
+use crate::misc::utils::EPSILON_BUMP;
+use crate::objects::object::Object;
+use crate::ray_tracing::ray::Ray;
+
+//Maximum number of objects stored in a single leaf
+const LEAF_SIZE: usize = 4;
+
+//A node of the hierarchy stores either two child nodes or the indices and bounds of a few objects
+#[derive(Debug, PartialEq, Clone)]
+pub enum BvhNode {
+    Leaf {
+        bounds: BoundingBox,
+        entries: Vec<(usize, BoundingBox)>,
+    },
+    Branch {
+        bounds: BoundingBox,
+        left: Box<BvhNode>,
+        right: Box<BvhNode>,
+    },
+}
+
+//A bounding volume hierarchy over a list of objects
+//Objects are referenced by their index so the list itself is left untouched
+#[derive(Debug, PartialEq, Clone)]
+pub struct Bvh {
+    pub root: Option<BvhNode>,
+    pub unbounded: Vec<usize>, //Objects such as planes which have infinite bounds
+    pub object_count: usize,
+}
+
+impl Bvh {
+    //Builds a hierarchy from the bounds of each object in its parent space
+    pub fn build(objects: &[Box<dyn Object>]) -> Bvh {
+        let mut entries = vec![];
+        let mut unbounded = vec![];
+        for (index, object) in objects.iter().enumerate() {
+            let bounds = object.parent_space_bounds();
+            if bounds.is_empty() {
+                continue;
+            }
+            if bounds.is_finite() {
+                //Padding prevents rounding errors from culling rays which graze an object
+                entries.push((index, bounds.padded(EPSILON_BUMP)));
+            }
+            else {
+                unbounded.push(index);
+            }
+        }
+        let root = if entries.is_empty() {
+            None
+        }
+        else {
+            Some(Bvh::build_node(&mut entries))
+        };
+        Bvh {
+            root,
+            unbounded,
+            object_count: objects.len(),
+        }
+    }
+
+    //Recursively splits the entries along the longest axis of their centers
+    fn build_node(entries: &mut [(usize, BoundingBox)]) -> BvhNode {
+        let mut bounds = BoundingBox::empty();
+        let mut centers = BoundingBox::empty();
+        for (_, entry_bounds) in entries.iter() {
+            bounds.merge(entry_bounds);
+            centers.add_point(&entry_bounds.center());
+        }
+
+        if entries.len() <= LEAF_SIZE {
+            return BvhNode::Leaf {
+                bounds,
+                entries: entries.to_vec(),
+            };
+        }
+
+        let extent = &centers.max - &centers.min;
+        let axis = if extent.0 >= extent.1 && extent.0 >= extent.2 {
+            Axis::X
+        }
+        else if extent.1 >= extent.2 {
+            Axis::Y
+        }
+        else {
+            Axis::Z
+        };
+
+        entries.sort_by(|(_, a), (_, b)| {
+            component(&a.center(), &axis)
+                .partial_cmp(&component(&b.center(), &axis))
+                .unwrap()
+        });
+        let (left, right) = entries.split_at_mut(entries.len() / 2);
+        BvhNode::Branch {
+            bounds,
+            left: Box::new(Bvh::build_node(left)),
+            right: Box::new(Bvh::build_node(right)),
+        }
+    }
+
+    //Finds the indices of every object whose bounds the ray passes through
+    //Indices are returned in ascending order so intersections are found in the same order as a linear search
+    pub fn candidates(&self, ray: &Ray) -> Vec<usize> {
+        let mut result = self.unbounded.clone();
+        let mut stack = vec![];
+        if let Some(root) = &self.root {
+            stack.push(root);
+        }
+        while let Some(node) = stack.pop() {
+            match node {
+                BvhNode::Leaf { bounds, entries } => {
+                    if bounds.intersects(ray) {
+                        for (index, entry_bounds) in entries {
+                            if entry_bounds.intersects(ray) {
+                                result.push(*index);
+                            }
+                        }
+                    }
+                }
+                BvhNode::Branch { bounds, left, right } => {
+                    if bounds.intersects(ray) {
+                        stack.push(left);
+                        stack.push(right);
+                    }
+                }
+            }
+        }
+        result.sort_unstable();
+        result
+    }
+
+    //Checks if the hierarchy was built from a list of the given length
+    //Only the length is checked, as comparing bounds would cost as much as rebuilding, so edits which keep the length need a rebuild
+    pub fn is_current(&self, objects: &[Box<dyn Object>]) -> bool {
+        self.object_count == objects.len()
+    }
+}
@@ -1,3 +1,4 @@
+use crate::core::matrix::Matrix4x4;
 use crate::core::vector::Vec4;
 use crate::objects::object::*;
 
@@ -10,6 +11,9 @@ pub struct Intersection<'a> {
     pub object: &'a dyn Object,
     pub u: Option<f32>,
     pub v: Option<f32>,
+    //Inverse transforms at the ray's time of the moving objects containing the object, innermost first
+    //These are not in the object's parent inverses as they change from ray to ray
+    pub motion_inverses: Vec<Matrix4x4>,
 }
 
 impl<'a> Intersection<'a> {
@@ -27,6 +31,7 @@ impl<'a> Intersection<'a> {
             object,
             u: None,
             v: None,
+            motion_inverses: vec![],
         }
     }
 
@@ -45,8 +50,19 @@ impl<'a> Intersection<'a> {
             object,
             u: Some(u),
             v: Some(v),
+            motion_inverses: vec![],
         }
-    } 
+    }
+
+    //Carries an intersection found inside a moving object out through a container with the given inverse
+    //The normal was found relative to the moving object, so it is transformed rather than recomputed from the world point
+    pub fn into_parent_space(mut self, inverse: &Matrix4x4) -> Intersection<'a> {
+        let mut normal = inverse.transpose() * &self.normal;
+        normal.3 = 0.0;
+        self.normal = normal.normalize();
+        self.motion_inverses.push(inverse.clone());
+        self
+    }
 
     //Finds which intersection is visible given a list of intersection
     pub fn hit(list_ref: &'a Vec<Intersection>) -> Option<Intersection<'a>> {
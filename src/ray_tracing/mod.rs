@@ -1,2 +1,3 @@
 pub mod intersection;
-pub mod ray;
\ No newline at end of file
+pub mod ray;
+pub mod bvh;
\ No newline at end of file
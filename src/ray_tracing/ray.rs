@@ -8,6 +8,7 @@ use crate::world::scene::*;
 pub struct Ray {
     pub origin: Vec4,
     pub direction: Vec4,
+    pub time: f32, //Moment the ray is cast, which decides where moving objects are
 }
 
 impl Ray where {
@@ -16,6 +17,7 @@ impl Ray where {
         Ray {
             origin: Vec4::new(origin.0, origin.1, origin.2, 1.0),
             direction: Vec4::new(direction.0, direction.1, direction.2, 0.0),
+            time: 0.0,
         }
     }
 
@@ -24,9 +26,16 @@ impl Ray where {
         Ray {
             origin,
             direction,
+            time: 0.0,
         }
     }
 
+    //Sets the moment the ray is cast
+    pub fn with_time(mut self, time: f32) -> Ray {
+        self.time = time;
+        self
+    }
+
     //Calculates the position of a ray
     pub fn position(ray: &Ray, t: f32) -> Vec4 {
         &ray.origin + (&ray.direction * t)
@@ -37,15 +46,15 @@ impl Ray where {
         Ray {
             origin: matrix * &ray.origin,
             direction: matrix * &ray.direction,
+            time: ray.time,
         }
     }
 
     //Lists ray intersections within a scene
     pub fn intersect_scene<'a>(scene: &'a Scene, ray: Ray) -> Vec<Intersection> {
-        let objects = &scene.objects;
         let mut intersections: Vec<Intersection> = vec![];
-        for object in objects {
-            let object_intersections = object.intersect(&ray);
+        for index in scene.candidates(&ray) {
+            let object_intersections = scene.objects[index].intersect(&ray);
             if !object_intersections.is_none() {
                 let unwrapped_intersections = object_intersections.unwrap();
                 for x in unwrapped_intersections {
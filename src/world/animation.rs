@@ -0,0 +1,134 @@
+use crate::core::matrix::Matrix4x4;
+
+//Shapes how a value moves from one keyframe into the next
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Curve {
+    Linear,
+    Step,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Curve {
+    //Gets a curve from the name used in scene files
+    pub fn from_name(name: &str) -> Option<Curve> {
+        match name {
+            "linear" => Some(Curve::Linear),
+            "step" => Some(Curve::Step),
+            "ease_in" => Some(Curve::EaseIn),
+            "ease_out" => Some(Curve::EaseOut),
+            "ease_in_out" => Some(Curve::EaseInOut),
+            _ => None,
+        }
+    }
+
+    //Maps the progress between two keyframes, from 0 to 1, onto the curve
+    pub fn apply(&self, amount: f32) -> f32 {
+        match self {
+            Curve::Linear => amount,
+            Curve::Step => {
+                if amount < 1.0 {
+                    0.0
+                }
+                else {
+                    1.0
+                }
+            }
+            Curve::EaseIn => amount * amount,
+            Curve::EaseOut => amount * (2.0 - amount),
+            Curve::EaseInOut => amount * amount * (3.0 - 2.0 * amount),
+        }
+    }
+}
+
+//Values which can be blended between keyframes
+pub trait Blend: Clone {
+    fn blend(&self, other: &Self, amount: f32) -> Self;
+}
+
+impl Blend for f32 {
+    fn blend(&self, other: &f32, amount: f32) -> f32 {
+        self + (other - self) * amount
+    }
+}
+
+impl Blend for Vec<f32> {
+    fn blend(&self, other: &Vec<f32>, amount: f32) -> Vec<f32> {
+        self.iter().zip(other).map(|(a, b)| a.blend(b, amount)).collect()
+    }
+}
+
+impl Blend for Matrix4x4 {
+    fn blend(&self, other: &Matrix4x4, amount: f32) -> Matrix4x4 {
+        self.lerp(other, amount)
+    }
+}
+
+//A value at a point in time, reached from the previous key along a curve
+#[derive(Debug, PartialEq, Clone)]
+pub struct Key<T> {
+    pub time: f32,
+    pub value: T,
+    pub curve: Curve,
+}
+
+impl<T> Key<T> {
+    //Creates a key which is reached in a straight line
+    pub fn new(time: f32, value: T) -> Key<T> {
+        Key {
+            time,
+            value,
+            curve: Curve::Linear,
+        }
+    }
+}
+
+//Keys of a single value over time
+//Curves only ever move between two neighbouring keys, so every value along a track lies between the values of its keys
+#[derive(Debug, PartialEq, Clone)]
+pub struct Track<T> {
+    pub keys: Vec<Key<T>>, //Keys sorted by time
+}
+
+impl<T: Blend> Track<T> {
+    //Creates a track from keys in any order
+    pub fn new(mut keys: Vec<Key<T>>) -> Track<T> {
+        keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Track { keys }
+    }
+
+    //Finds the value at a time, holding still before the first key and after the last
+    pub fn at(&self, time: f32) -> Option<T> {
+        let first = self.keys.first()?;
+        let last = self.keys.last()?;
+        if time <= first.time {
+            return Some(first.value.clone());
+        }
+        if time >= last.time {
+            return Some(last.value.clone());
+        }
+        let next = self.keys.iter().position(|key| key.time > time).unwrap();
+        let start = &self.keys[next - 1];
+        let end = &self.keys[next];
+        let amount = end.curve.apply((time - start.time) / (end.time - start.time));
+        Some(start.value.blend(&end.value, amount))
+    }
+}
+
+//Finds the path of one frame of an image sequence
+//The frame number replaces a run of "#" in the path, padded to its length, or is added before the extension
+pub fn frame_path(path: &str, frame: usize) -> String {
+    if let Some(start) = path.find('#') {
+        let width = path[start..].chars().take_while(|c| *c == '#').count();
+        return format!("{}{:0width$}{}", &path[..start], frame, &path[start + width..], width = width);
+    }
+    let file_start = path.rfind(['/', '\\']).map_or(0, |index| index + 1);
+    match path[file_start..].rfind('.') {
+        Some(dot) if dot > 0 => {
+            let dot = file_start + dot;
+            format!("{}_{:04}{}", &path[..dot], frame, &path[dot..])
+        }
+        _ => format!("{}_{:04}", path, frame),
+    }
+}
@@ -1,9 +1,165 @@
 use crate::core::canvas::Canvas;
-use crate::core::color::Color;
+use crate::core::color::*;
 use crate::core::matrix::Matrix4x4;
 use crate::core::vector::Vec4;
 use crate::ray_tracing::ray::Ray;
+use crate::misc::random::*;
+use crate::world::checkpoint::*;
+use crate::world::integrator::*;
+use crate::world::sampler::*;
 use crate::world::scene::Scene;
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+//Width and height of the tiles used by the parallel renderers
+pub const TILE_SIZE: i32 = 16;
+
+//A rectangular block of pixels which is shaded by a single thread
+#[derive(Debug, PartialEq, Clone)]
+pub struct Tile {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Tile {
+    //Finds the part of the tile inside an image of the given size
+    pub fn within(&self, width: i32, height: i32) -> Tile {
+        let x = self.x.clamp(0, width);
+        let y = self.y.clamp(0, height);
+        Tile {
+            x,
+            y,
+            width: (self.x + self.width).clamp(x, width) - x,
+            height: (self.y + self.height).clamp(y, height) - y,
+        }
+    }
+
+    //Splits the tile into smaller tiles no larger than size, row by row
+    pub fn split(&self, size: i32) -> Vec<Tile> {
+        let mut tiles = vec![];
+        for y in (self.y..(self.y + self.height)).step_by(size as usize) {
+            for x in (self.x..(self.x + self.width)).step_by(size as usize) {
+                tiles.push(Tile {
+                    x,
+                    y,
+                    width: size.min(self.x + self.width - x),
+                    height: size.min(self.y + self.height - y),
+                });
+            }
+        }
+        tiles
+    }
+
+    //Lists the pixels of the tile row by row
+    pub fn pixels(&self) -> Vec<(i32, i32)> {
+        let mut pixels = vec![];
+        for y in self.y..(self.y + self.height) {
+            for x in self.x..(self.x + self.width) {
+                pixels.push((x, y));
+            }
+        }
+        pixels
+    }
+}
+
+//Counts of the work done by a render
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct RenderStats {
+    pub pixels: usize,         //Number of pixels in the image
+    pub refined_pixels: usize, //Number of pixels which needed more than one ray
+    pub rays: usize,           //Number of rays traced from the camera, not counting reflected, refracted or shadow rays
+    pub stopped: bool,         //Whether a progressive render was stopped before tracing every sample
+}
+
+impl RenderStats {
+    //Finds the average number of camera rays traced for each pixel
+    pub fn rays_per_pixel(&self) -> f32 {
+        if self.pixels == 0 {
+            0.0
+        }
+        else {
+            self.rays as f32 / self.pixels as f32
+        }
+    }
+}
+
+//Samples a single pixel for adaptive anti-aliasing on a grid of 2^max_depth + 1 points per side
+//Samples are cached so the corners shared by neighbouring areas are only traced once
+struct AdaptivePixel<'a> {
+    camera: &'a Camera,
+    scene: &'a Scene,
+    settings: &'a AdaptiveSettings,
+    x: i32,
+    y: i32,
+    resolution: u32,
+    samples: HashMap<(u32, u32), Color>,
+}
+
+impl<'a> AdaptivePixel<'a> {
+    //Finds the color at a point of the sample grid, where rays which miss count as black
+    fn sample(&mut self, column: u32, row: u32) -> Color {
+        if let Some(color) = self.samples.get(&(column, row)) {
+            return color.clone();
+        }
+        let offset_x = column as f32 / self.resolution as f32;
+        let offset_y = row as f32 / self.resolution as f32;
+        let ray = Camera::ray_towards_pixel_raw(self.camera, self.x, self.y, offset_x, offset_y);
+        let color = Camera::trace(self.camera, self.scene, ray).unwrap_or(BLACK);
+        self.samples.insert((column, row), color.clone());
+        color
+    }
+
+    //Finds the color of a square area of the pixel, splitting it in four while its corners differ
+    fn area(&mut self, column: u32, row: u32, size: u32) -> Color {
+        let corners = [
+            self.sample(column, row),
+            self.sample(column + size, row),
+            self.sample(column, row + size),
+            self.sample(column + size, row + size),
+        ];
+        let differs = (0..4).any(|i| (i + 1..4).any(|j| self.settings.differs(&corners[i], &corners[j])));
+        if differs && size > 1 {
+            let half = size / 2;
+            let result = self.area(column, row, half)
+                + self.area(column + half, row, half)
+                + self.area(column, row + half, half)
+                + self.area(column + half, row + half, half);
+            result * 0.25
+        }
+        else {
+            let [first, second, third, fourth] = corners;
+            (first + second + third + fourth) * 0.25
+        }
+    }
+}
+
+//Ways of mapping pixels to rays
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Projection {
+    Perspective,     //Rays spread out from a point, covering the field of view across the larger image dimension
+    Orthographic,    //Rays are parallel, covering the area a perspective camera would see at the focal distance
+    Fisheye,         //Equidistant fisheye where the field of view, which may exceed 180 degrees, spans the larger image dimension
+    Equirectangular, //360 degree panorama with longitude across the width and latitude down the height
+}
+
+impl Projection {
+    //Looks up a projection by name
+    pub fn from_name(name: &str) -> Option<Projection> {
+        match name {
+            "perspective" => Some(Projection::Perspective),
+            "orthographic" => Some(Projection::Orthographic),
+            "fisheye" => Some(Projection::Fisheye),
+            "equirectangular" => Some(Projection::Equirectangular),
+            _ => None,
+        }
+    }
+}
 
 //The camera stores all the info relevant to how the scene is viewed
 pub struct Camera {
@@ -13,6 +169,16 @@ pub struct Camera {
     pub half_width: f32,
     pub half_height: f32,
     pub transform: Matrix4x4,
+    pub depth: i32, //Maximum number of reflection and refraction bounces
+    pub fov: f32, //Field of view in degrees
+    pub projection: Projection,
+    pub aperture: f32, //Radius of the lens, where 0 gives a pinhole camera with everything in focus, only used by perspective cameras
+    pub focal_distance: f32, //Distance from the camera to the plane which is in focus
+    pub shutter_open: f32,   //Time when the shutter opens
+    pub shutter_close: f32,  //Time when the shutter closes, where a later time than shutter_open blurs moving objects
+    pub checkpoint: Option<Checkpoint>, //Where the parallel renderers save their progress so they can resume after being stopped
+    pub crop: Option<Tile>, //Region of the image to render, where None renders every pixel
+    pub integrator: Box<dyn Integrator>, //How rays are shaded by the renderers, other than quick_render
 }
 
 impl Camera {
@@ -38,6 +204,73 @@ impl Camera {
             half_height: _half_height,
             pixel_size,
             transform: Matrix4x4::identity(),
+            depth: 5,
+            fov: fov_degrees,
+            projection: Projection::Perspective,
+            aperture: 0.0,
+            focal_distance: 1.0,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
+            checkpoint: None,
+            crop: None,
+            integrator: Box::new(Whitted),
+        }
+    }
+
+    //Changes how pixels are mapped to rays
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+
+    //Sets the times when the shutter opens and closes
+    pub fn set_shutter(&mut self, open: f32, close: f32) {
+        self.shutter_open = open;
+        self.shutter_close = close;
+    }
+
+    //Gives the camera a lens so objects away from the focal distance are blurred
+    pub fn set_lens(&mut self, aperture: f32, focal_distance: f32) {
+        self.aperture = aperture;
+        self.focal_distance = focal_distance;
+    }
+
+    //Saves the progress of the parallel renderers to a checkpoint, resuming from it if it holds progress of the same render
+    pub fn set_checkpoint(&mut self, checkpoint: Checkpoint) {
+        self.checkpoint = Some(checkpoint);
+    }
+
+    //Changes how rays are shaded
+    pub fn set_integrator(&mut self, integrator: Box<dyn Integrator>) {
+        self.integrator = integrator;
+    }
+
+    //Only renders the pixels inside a rectangle, which are traced exactly as they are in a full render
+    //Renderers write into a canvas of the full image size, or into one the size of the rectangle which then holds only the crop
+    pub fn set_crop(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        self.crop = Some(Tile { x, y, width, height });
+    }
+
+    //Finds the region of the image which is rendered, which is the crop window limited to the image or the whole image
+    pub fn region(camera: &Camera) -> Tile {
+        match &camera.crop {
+            Some(crop) => crop.within(camera.hsize, camera.vsize),
+            None => Tile {
+                x: 0,
+                y: 0,
+                width: camera.hsize,
+                height: camera.vsize,
+            },
+        }
+    }
+
+    //Stores the color of an image pixel in a canvas covering the whole image or only the crop window
+    pub fn set_pixel(camera: &Camera, canvas: &mut Canvas, color: Color, x: i32, y: i32) {
+        let region = Camera::region(camera);
+        if camera.crop.is_some() && canvas.width == region.width as usize && canvas.height == region.height as usize {
+            canvas.set(color, x - region.x, y - region.y);
+        }
+        else {
+            canvas.set(color, x, y);
         }
     }
 
@@ -48,26 +281,12 @@ impl Camera {
 
     //Creates a ray with a vector towards a pixel on the canvas
     pub fn ray_towards_pixel(camera: &Camera, pixel_x: i32, pixel_y: i32) -> Ray {
-        //Offset from canvas edge to the center of the pixel
-        let x_offset = (pixel_x as f32 + 0.5) * camera.pixel_size;
-        let y_offset = (pixel_y as f32 + 0.5) * camera.pixel_size;
-
-        //Undoes transform applied to coordinates due to camera facing towards -z
-        let scene_x = camera.half_width - x_offset;
-        let scene_y = camera.half_height - y_offset;
-
-        //Finds the target pixel and origin coordinates by applying the inverse camera transformations
-        let target_pixel =
-            camera.transform.inverse().unwrap() * Vec4::new(scene_x, scene_y, -1.0, 1.0);
-        let origin = camera.transform.inverse().unwrap() * Vec4::new(0.0, 0.0, 0.0, 1.0);
-
-        //Normalizes the vector
-        let direction = (target_pixel - &origin).normalize();
-
-        Ray::new_from_vec(origin, direction)
+        Camera::ray_towards_pixel_raw(camera, pixel_x, pixel_y, 0.5, 0.5)
     }
 
     //Creates a ray with a vector towards a pixel on the canvas
+    //Cameras with an aperture start the ray at a random point on the lens, and cameras with an open shutter cast it at a random time,
+    //both drawn from the current thread's generator
     pub fn ray_towards_pixel_raw(
         camera: &Camera,
         pixel_x: i32,
@@ -75,7 +294,33 @@ impl Camera {
         offset_x: f32,
         offset_y: f32,
     ) -> Ray {
-        //Offset from canvas edge to the center of the pixel
+        let (lens_x, lens_y) = if camera.aperture > 0.0 {
+            sample_disk(random_float(), random_float())
+        }
+        else {
+            (0.0, 0.0)
+        };
+        let ray = Camera::ray_through_lens(camera, pixel_x, pixel_y, offset_x, offset_y, lens_x, lens_y);
+        if camera.shutter_close > camera.shutter_open {
+            ray.with_time(random_range(camera.shutter_open, camera.shutter_close))
+        }
+        else {
+            ray
+        }
+    }
+
+    //Creates a ray towards a pixel which passes through a point on the lens
+    //The lens point is given in units of the aperture radius, so (0, 0) is the center and points on the unit disk cover the lens
+    pub fn ray_through_lens(
+        camera: &Camera,
+        pixel_x: i32,
+        pixel_y: i32,
+        offset_x: f32,
+        offset_y: f32,
+        lens_x: f32,
+        lens_y: f32,
+    ) -> Ray {
+        //Offset from canvas edge to the point in the pixel
         let x_offset = (pixel_x as f32 + offset_x) * camera.pixel_size;
         let y_offset = (pixel_y as f32 + offset_y) * camera.pixel_size;
 
@@ -83,29 +328,176 @@ impl Camera {
         let scene_x = camera.half_width - x_offset;
         let scene_y = camera.half_height - y_offset;
 
-        //Finds the target pixel and origin coordinates by applying the inverse camera transformations
-        let target_pixel =
-            camera.transform.inverse().unwrap() * Vec4::new(scene_x, scene_y, -1.0, 1.0);
-        let origin = camera.transform.inverse().unwrap() * Vec4::new(0.0, 0.0, 0.0, 1.0);
+        //Position on the image from -1 to 1 across each dimension, which fisheye and panorama projections work from
+        //These do not use the perspective view size as it can not describe fields of view of 180 degrees or more
+        let image_x = 1.0 - 2.0 * (pixel_x as f32 + offset_x) / camera.hsize as f32;
+        let image_y = 1.0 - 2.0 * (pixel_y as f32 + offset_y) / camera.vsize as f32;
+
+        let (origin, direction) = match camera.projection {
+            Projection::Perspective => {
+                //Every ray through the same pixel meets on the focal plane, so only objects at the focal distance stay sharp
+                let focus = Vec4::new(
+                    scene_x * camera.focal_distance,
+                    scene_y * camera.focal_distance,
+                    -camera.focal_distance,
+                    1.0,
+                );
+                let lens = Vec4::new(lens_x * camera.aperture, lens_y * camera.aperture, 0.0, 1.0);
+                let direction = &focus - &lens;
+                (lens, direction)
+            }
+            Projection::Orthographic => (
+                Vec4::new(scene_x * camera.focal_distance, scene_y * camera.focal_distance, 0.0, 1.0),
+                Vec4::new(0.0, 0.0, -1.0, 0.0),
+            ),
+            Projection::Fisheye => {
+                //The angle from the view direction grows evenly with the distance from the image center
+                let larger = camera.hsize.max(camera.vsize) as f32;
+                let u = image_x * camera.hsize as f32 / larger;
+                let v = image_y * camera.vsize as f32 / larger;
+                let radius = (u * u + v * v).sqrt();
+                let theta = radius * camera.fov.to_radians() / 2.0;
+                let (sin_theta, cos_theta) = theta.sin_cos();
+                let direction = if radius > 0.0 {
+                    Vec4::new(sin_theta * u / radius, sin_theta * v / radius, -cos_theta, 0.0)
+                }
+                else {
+                    Vec4::new(0.0, 0.0, -1.0, 0.0)
+                };
+                (Vec4::new(0.0, 0.0, 0.0, 1.0), direction)
+            }
+            Projection::Equirectangular => {
+                //The width covers every longitude and the height every latitude
+                let longitude = image_x * PI;
+                let latitude = image_y * PI / 2.0;
+                let direction = Vec4::new(
+                    longitude.sin() * latitude.cos(),
+                    latitude.sin(),
+                    -longitude.cos() * latitude.cos(),
+                    0.0,
+                );
+                (Vec4::new(0.0, 0.0, 0.0, 1.0), direction)
+            }
+        };
+
+        //Finds the origin and direction in the scene by applying the inverse camera transformations
+        let inverse = camera.transform.inverse().unwrap();
+        let origin = &inverse * origin;
 
         //Normalizes the vector
-        let direction = (target_pixel - &origin).normalize();
+        let direction = (&inverse * direction).normalize();
+
+        Ray::new_from_vec(origin, direction).with_time(camera.shutter_open)
+    }
 
-        Ray::new_from_vec(origin, direction)
+    //Finds the color seen along a ray with the camera's integrator, returning None if it misses every object
+    pub fn trace(camera: &Camera, scene: &Scene, ray: Ray) -> Option<Color> {
+        camera.integrator.color(ray, scene, camera.depth)
+    }
+
+    //Finds the color of a pixel using a single ray through its center
+    pub fn pixel_color(camera: &Camera, scene: &Scene, x: i32, y: i32) -> Option<Color> {
+        let ray = Camera::ray_towards_pixel(camera, x, y);
+        Camera::trace(camera, scene, ray)
+    }
+
+    //Finds the color of a pixel by averaging rays through its center and corners
+    pub fn pixel_color_supersampled(camera: &Camera, scene: &Scene, x: i32, y: i32) -> Color {
+        let ray1 = Camera::ray_towards_pixel(camera, x, y);
+        let ray2 = Camera::ray_towards_pixel_raw(camera, x, y, 0.0, 0.0);
+        let ray3 = Camera::ray_towards_pixel_raw(camera, x, y, 0.0, 1.0);
+        let ray4 = Camera::ray_towards_pixel_raw(camera, x, y, 1.0, 0.0);
+        let ray5 = Camera::ray_towards_pixel_raw(camera, x, y, 1.0, 1.0);
+        let mut list = vec![
+            Camera::trace(camera, scene, ray1),
+            Camera::trace(camera, scene, ray2),
+            Camera::trace(camera, scene, ray3),
+            Camera::trace(camera, scene, ray4),
+            Camera::trace(camera, scene, ray5),
+        ];
+        let mut result = Color::new(0.0, 0.0, 0.0);
+        for _ in 0..5 {
+            if list[0] != None {
+                result = result + list.remove(0).unwrap();
+            } else {
+                list.remove(0);
+            }
+        }
+        result * 0.2
+    }
+
+    //Finds the color of a pixel by averaging a level x level grid of rays spread evenly across it
+    //Rays which miss every object count as black
+    pub fn pixel_color_grid(camera: &Camera, scene: &Scene, x: i32, y: i32, level: usize) -> Option<Color> {
+        if level <= 1 {
+            return Camera::pixel_color(camera, scene, x, y);
+        }
+        let mut result = BLACK;
+        for sub_y in 0..level {
+            for sub_x in 0..level {
+                let offset_x = (sub_x as f32 + 0.5) / level as f32;
+                let offset_y = (sub_y as f32 + 0.5) / level as f32;
+                let ray = Camera::ray_towards_pixel_raw(camera, x, y, offset_x, offset_y);
+                if let Some(color) = Camera::trace(camera, scene, ray) {
+                    result = result + color;
+                }
+            }
+        }
+        Some(result * (1.0 / (level * level) as f32))
+    }
+
+    //Finds the color of a pixel from randomly placed samples weighted by a reconstruction filter
+    //Samples cover the whole filter footprint, so wider filters reach into neighbouring pixels, and rays which miss count as black
+    pub fn pixel_color_sampled(camera: &Camera, scene: &Scene, x: i32, y: i32, settings: &SamplerSettings) -> Color {
+        Camera::pixel_samples(camera, scene, x, y, settings).color()
+    }
+
+    //Traces the randomly placed samples of a pixel, returning their totals so they can be combined with more samples later
+    pub fn pixel_samples(camera: &Camera, scene: &Scene, x: i32, y: i32, settings: &SamplerSettings) -> SampleSum {
+        let mut sum = SampleSum::default();
+        for (offset_x, offset_y) in settings.offsets(x, y) {
+            let ray = Camera::ray_towards_pixel_raw(camera, x, y, 0.5 + offset_x, 0.5 + offset_y);
+            let color = Camera::trace(camera, scene, ray).unwrap_or(BLACK);
+            sum.add(color, settings.filter.weight(offset_x, offset_y));
+        }
+        sum
+    }
+
+    //Finds the color of a pixel by tracing rays at its corners and splitting it into quarters wherever they differ
+    //Returns the color along with the number of rays traced
+    pub fn pixel_color_adaptive(
+        camera: &Camera,
+        scene: &Scene,
+        x: i32,
+        y: i32,
+        settings: &AdaptiveSettings,
+    ) -> (Color, usize) {
+        let resolution = 1 << settings.max_depth.min(8);
+        let mut pixel = AdaptivePixel {
+            camera,
+            scene,
+            settings,
+            x,
+            y,
+            resolution,
+            samples: HashMap::new(),
+        };
+        let color = pixel.area(0, 0, resolution);
+        (color, pixel.samples.len())
     }
 
     //Renders a scene
     pub fn render(camera: &Camera, scene: &Scene, canvas: &mut Canvas) {
         let mut counter = 0;
         let mut percent = 0;
-        let pixels = &camera.hsize * &camera.vsize;
+        let region = Camera::region(camera);
+        let pixels = region.width * region.height;
         let percentage_update = pixels as f32 / 10.0;
-        for y in 0..camera.vsize {
-            for x in 0..camera.hsize {
-                let ray = Camera::ray_towards_pixel(camera, x, y);
-                let color = Scene::compute_color(ray, scene, 5);
+        for y in region.y..(region.y + region.height) {
+            for x in region.x..(region.x + region.width) {
+                let color = Camera::pixel_color(camera, scene, x, y);
                 if color != None {
-                    canvas.set(color.unwrap().clone(), x, y);
+                    Camera::set_pixel(camera, canvas, color.unwrap().clone(), x, y);
                 }
                 if counter as f32 > percentage_update {
                     percent += 10;
@@ -122,32 +514,13 @@ impl Camera {
     pub fn render_supersampled(camera: &Camera, scene: &Scene, canvas: &mut Canvas) {
         let mut counter = 0;
         let mut percent = 0;
-        let pixels = &camera.hsize * &camera.vsize;
+        let region = Camera::region(camera);
+        let pixels = region.width * region.height;
         let percentage_update = pixels as f32 / 10.0;
-        for y in 0..camera.vsize {
-            for x in 0..camera.hsize {
-                let ray1 = Camera::ray_towards_pixel(camera, x, y);
-                let ray2 = Camera::ray_towards_pixel_raw(camera, x, y, 0.0, 0.0);
-                let ray3 = Camera::ray_towards_pixel_raw(camera, x, y, 0.0, 1.0);
-                let ray4 = Camera::ray_towards_pixel_raw(camera, x, y, 1.0, 0.0);
-                let ray5 = Camera::ray_towards_pixel_raw(camera, x, y, 1.0, 1.0);
-                let mut list = vec![
-                    Scene::compute_color(ray1, scene, 5),
-                    Scene::compute_color(ray2, scene, 5),
-                    Scene::compute_color(ray3, scene, 5),
-                    Scene::compute_color(ray4, scene, 5),
-                    Scene::compute_color(ray5, scene, 5),
-                ];
-                let mut result = Color::new(0.0, 0.0, 0.0);
-                for _ in 0..5 {
-                    if list[0] != None {
-                        result = result + list.remove(0).unwrap();
-                    } else {
-                        list.remove(0);
-                    }
-                }
-                result = result * 0.2;
-                canvas.set(result, x, y);
+        for y in region.y..(region.y + region.height) {
+            for x in region.x..(region.x + region.width) {
+                let result = Camera::pixel_color_supersampled(camera, scene, x, y);
+                Camera::set_pixel(camera, canvas, result, x, y);
                 if counter as f32 > percentage_update {
                     percent += 10;
                     println!("Render is {}% complete", percent);
@@ -159,18 +532,344 @@ impl Camera {
         }
     }
 
+    //Splits the rendered region of the camera view into tiles no larger than tile_size
+    pub fn tiles(camera: &Camera, tile_size: i32) -> Vec<Tile> {
+        Camera::region(camera).split(tile_size)
+    }
+
+    //Finds the number of threads to use when none is specified
+    pub fn default_threads() -> usize {
+        thread::available_parallelism().map_or(1, |count| count.get())
+    }
+
+    //Renders a scene across multiple threads
+    pub fn render_parallel(camera: &Camera, scene: &Scene, canvas: &mut Canvas, threads: usize) {
+        Camera::render_tiles(camera, canvas, threads, |x, y| Camera::pixel_color(camera, scene, x, y));
+    }
+
+    //Renders a supersampled scene across multiple threads
+    pub fn render_supersampled_parallel(camera: &Camera, scene: &Scene, canvas: &mut Canvas, threads: usize) {
+        Camera::render_tiles(camera, canvas, threads, |x, y| {
+            Some(Camera::pixel_color_supersampled(camera, scene, x, y))
+        });
+    }
+
+    //Renders a scene with a level x level grid of rays per pixel across multiple threads
+    pub fn render_grid_parallel(camera: &Camera, scene: &Scene, canvas: &mut Canvas, level: usize, threads: usize) {
+        Camera::render_tiles(camera, canvas, threads, |x, y| Camera::pixel_color_grid(camera, scene, x, y, level));
+    }
+
+    //Renders a scene with stochastic supersampling across multiple threads
+    pub fn render_sampled_parallel(
+        camera: &Camera,
+        scene: &Scene,
+        canvas: &mut Canvas,
+        settings: &SamplerSettings,
+        threads: usize,
+    ) {
+        Camera::render_tiles(camera, canvas, threads, |x, y| {
+            Some(Camera::pixel_color_sampled(camera, scene, x, y, settings))
+        });
+    }
+
+    //Renders a scene with adaptive anti-aliasing across multiple threads
+    //Every pixel is first shaded with a single ray, then only pixels which differ from a neighbour by more than the threshold are refined
+    //When resuming from a checkpoint the stats only count the rays traced after resuming
+    pub fn render_adaptive_parallel(
+        camera: &Camera,
+        scene: &Scene,
+        canvas: &mut Canvas,
+        settings: &AdaptiveSettings,
+        threads: usize,
+    ) -> RenderStats {
+        //The first pass is cheap to repeat so it is never saved to the checkpoint, which only holds the final colors
+        //It reaches one pixel past the crop window so pixels on its edge are compared with the same neighbours as in a full render
+        let region = Camera::region(camera);
+        let margin = Tile {
+            x: region.x - 1,
+            y: region.y - 1,
+            width: region.width + 2,
+            height: region.height + 2,
+        }
+        .within(camera.hsize, camera.vsize);
+        let tiles = margin.split(TILE_SIZE);
+        let indices: Vec<usize> = (0..tiles.len()).collect();
+        let mut first_pass = Canvas::new(camera.hsize as usize, camera.vsize as usize);
+        //Progress is only reported by the second pass, which traces most of the rays, so it is not printed twice
+        let shaded = Camera::shade_tile_list(&tiles, &indices, threads, 0, false, |x, y| Camera::pixel_color(camera, scene, x, y), |_, _| {});
+        for (index, colors) in shaded {
+            for ((x, y), color) in tiles[index].pixels().into_iter().zip(colors) {
+                first_pass.set(color.unwrap_or(BLACK), x, y);
+            }
+        }
+
+        let rays = AtomicUsize::new((margin.width * margin.height) as usize);
+        let refined_pixels = AtomicUsize::new(0);
+        Camera::render_tiles(camera, canvas, threads, |x, y| {
+            let center = first_pass.get(x, y)?;
+            let neighbours = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)];
+            let differs = neighbours
+                .iter()
+                .any(|(x, y)| first_pass.get(*x, *y).is_some_and(|color| settings.differs(center, color)));
+            if differs {
+                let (color, count) = Camera::pixel_color_adaptive(camera, scene, x, y, settings);
+                rays.fetch_add(count, Ordering::Relaxed);
+                refined_pixels.fetch_add(1, Ordering::Relaxed);
+                Some(color)
+            }
+            else {
+                Some(center.clone())
+            }
+        });
+
+        RenderStats {
+            pixels: (region.width * region.height) as usize,
+            refined_pixels: refined_pixels.into_inner(),
+            rays: rays.into_inner(),
+            stopped: false,
+        }
+    }
+
+    //Renders a scene progressively, tracing passes with more and more samples per pixel until the sample count is reached
+    //The canvas always holds the image so far, which is given to snapshot whenever the snapshot interval has passed
+    //Setting stop, or reaching the time limit, ends the render early while keeping every sample traced
+    //With a checkpoint the samples are saved after passes and when stopping, so a longer render can carry on from them
+    pub fn render_progressive<F>(
+        camera: &Camera,
+        scene: &Scene,
+        canvas: &mut Canvas,
+        settings: &ProgressiveSettings,
+        threads: usize,
+        stop: &AtomicBool,
+        mut snapshot: F,
+    ) -> RenderStats
+    where
+        F: FnMut(&Canvas, usize),
+    {
+        let start = Instant::now();
+        let deadline = settings.time_limit.map(|limit| start + Duration::from_secs_f32(limit));
+        let should_stop = || stop.load(Ordering::Relaxed) || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        let mut sums = vec![SampleSum::default(); (camera.hsize * camera.vsize) as usize];
+        let mut pass = 0;
+        let mut samples = 0;
+        if let Some(Progress::Samples { passes, samples: saved_samples, sums: saved_sums }) = Camera::load_checkpoint(camera) {
+            if saved_sums.len() == sums.len() {
+                println!("Resuming from {} samples per pixel", saved_samples);
+                pass = passes;
+                samples = saved_samples;
+                sums = saved_sums;
+            }
+        }
+        let mut last_snapshot = start;
+        let mut last_checkpoint = start;
+
+        while let Some(size) = settings.pass_size(samples) {
+            let sampler = settings.pass_sampler(pass, size);
+            let shaded = Camera::shade_tiles(camera, threads, sampler.seed, false, |x, y| {
+                if should_stop() {
+                    return None;
+                }
+                Some(Camera::pixel_samples(camera, scene, x, y, &sampler))
+            });
+            //A pass which was stopped partway still adds the pixels it finished
+            for (x, y, sum) in shaded {
+                if let Some(sum) = sum {
+                    sums[(y * camera.hsize + x) as usize].merge(&sum);
+                }
+            }
+            //Even a stopped pass uses up its seed, so resuming never traces the same rays twice
+            let stopped = should_stop();
+            pass += 1;
+            if !stopped {
+                samples += size;
+            }
+            if let Some(checkpoint) = &camera.checkpoint {
+                if stopped || last_checkpoint.elapsed().as_secs_f32() >= checkpoint.interval {
+                    let progress = Progress::Samples {
+                        passes: pass,
+                        samples,
+                        sums: sums.clone(),
+                    };
+                    Camera::save_checkpoint(camera, checkpoint, &progress);
+                    last_checkpoint = Instant::now();
+                }
+            }
+            Camera::resolve_samples(camera, &sums, canvas);
+            if stopped {
+                println!("Render stopped after {} samples per pixel", samples);
+                break;
+            }
+            println!("Pass {} complete, {} samples per pixel", pass, samples);
+            if last_snapshot.elapsed().as_secs_f32() >= settings.snapshot_interval && samples < settings.sampler.samples {
+                snapshot(canvas, samples);
+                last_snapshot = Instant::now();
+            }
+        }
+        //A resumed render may already have every sample, in which case no pass has filled the canvas
+        Camera::resolve_samples(camera, &sums, canvas);
+
+        let region = Camera::region(camera);
+        RenderStats {
+            pixels: (region.width * region.height) as usize,
+            refined_pixels: 0,
+            rays: sums.iter().map(|sum| sum.count).sum(),
+            stopped: samples < settings.sampler.samples,
+        }
+    }
+
+    //Sets every pixel of the canvas to the color of its samples
+    fn resolve_samples(camera: &Camera, sums: &[SampleSum], canvas: &mut Canvas) {
+        for (x, y) in Camera::region(camera).pixels() {
+            Camera::set_pixel(camera, canvas, sums[(y * camera.hsize + x) as usize].color(), x, y);
+        }
+    }
+
+    //Loads the progress saved in the camera's checkpoint, starting over if it can not be read
+    fn load_checkpoint(camera: &Camera) -> Option<Progress> {
+        let checkpoint = camera.checkpoint.as_ref()?;
+        match checkpoint.load(camera.hsize, camera.vsize) {
+            Ok(progress) => progress,
+            Err(error) => {
+                eprintln!("Ignoring checkpoint {}: {}", checkpoint.path, error);
+                None
+            }
+        }
+    }
+
+    //Saves progress to a checkpoint, carrying on with the render if it can not be written
+    fn save_checkpoint(camera: &Camera, checkpoint: &Checkpoint, progress: &Progress) {
+        if let Err(error) = checkpoint.save(camera.hsize, camera.vsize, progress) {
+            eprintln!("Failed to write checkpoint {}: {}", checkpoint.path, error);
+        }
+    }
+
+    //Shades every tile of the canvas, handing out tiles to threads as they finish
+    //A thread count of zero uses one thread per available core
+    //The random generator is reseeded for every pixel so lens and light sampling do not depend on the thread count
+    //With a checkpoint the finished tiles are saved as the render goes, and tiles saved by an earlier run are not shaded again
+    pub fn render_tiles<F>(camera: &Camera, canvas: &mut Canvas, threads: usize, shade: F)
+    where
+        F: Fn(i32, i32) -> Option<Color> + Sync,
+    {
+        let tiles = Camera::tiles(camera, TILE_SIZE);
+        let mut finished = match Camera::load_checkpoint(camera) {
+            Some(Progress::Tiles(saved)) => saved,
+            _ => HashMap::new(),
+        };
+        finished.retain(|index, colors| tiles.get(*index).is_some_and(|tile| tile.pixels().len() == colors.len()));
+        if !finished.is_empty() {
+            println!("Resuming with {} of {} tiles finished", finished.len(), tiles.len());
+        }
+        let remaining: Vec<usize> = (0..tiles.len()).filter(|index| !finished.contains_key(index)).collect();
+
+        let saved = Mutex::new((finished.clone(), Instant::now()));
+        let shaded = Camera::shade_tile_list(&tiles, &remaining, threads, 0, true, shade, |index, colors| {
+            if let Some(checkpoint) = &camera.checkpoint {
+                let mut saved = saved.lock().unwrap();
+                saved.0.insert(index, colors.to_vec());
+                if saved.1.elapsed().as_secs_f32() >= checkpoint.interval {
+                    Camera::save_checkpoint(camera, checkpoint, &Progress::Tiles(saved.0.clone()));
+                    saved.1 = Instant::now();
+                }
+            }
+        });
+
+        for (index, colors) in finished.into_iter().chain(shaded) {
+            for ((x, y), color) in tiles[index].pixels().into_iter().zip(colors) {
+                if let Some(color) = color {
+                    Camera::set_pixel(camera, canvas, color, x, y);
+                }
+            }
+        }
+    }
+
+    //Shades every pixel of the camera view in tiles across multiple threads, returning each pixel with its result
+    //Pixels are reseeded from the seed and their position, and progress is printed if report_progress is set
+    pub fn shade_tiles<T, F>(camera: &Camera, threads: usize, seed: u64, report_progress: bool, shade: F) -> Vec<(i32, i32, T)>
+    where
+        T: Send,
+        F: Fn(i32, i32) -> T + Sync,
+    {
+        let tiles = Camera::tiles(camera, TILE_SIZE);
+        let all: Vec<usize> = (0..tiles.len()).collect();
+        let mut pixels = vec![];
+        for (index, results) in Camera::shade_tile_list(&tiles, &all, threads, seed, report_progress, shade, |_, _| {}) {
+            for ((x, y), result) in tiles[index].pixels().into_iter().zip(results) {
+                pixels.push((x, y, result));
+            }
+        }
+        pixels
+    }
+
+    //Shades the tiles at the given indices, handing them out to threads as they finish
+    //Each tile's results are passed to finished as soon as it is done, and returned with its index at the end
+    fn shade_tile_list<T, F, D>(
+        tiles: &[Tile],
+        indices: &[usize],
+        threads: usize,
+        seed: u64,
+        report_progress: bool,
+        shade: F,
+        finished: D,
+    ) -> Vec<(usize, Vec<T>)>
+    where
+        T: Send,
+        F: Fn(i32, i32) -> T + Sync,
+        D: Fn(usize, &[T]) + Sync,
+    {
+        let threads = if threads == 0 { Camera::default_threads() } else { threads };
+        let next_tile = AtomicUsize::new(0);
+        let finished_tiles = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut shaded = vec![];
+                        loop {
+                            let next = next_tile.fetch_add(1, Ordering::SeqCst);
+                            if next >= indices.len() {
+                                break;
+                            }
+                            let index = indices[next];
+                            let results: Vec<T> = tiles[index]
+                                .pixels()
+                                .iter()
+                                .map(|(x, y)| {
+                                    reseed(pixel_seed(seed, *x, *y));
+                                    shade(*x, *y)
+                                })
+                                .collect();
+                            finished(index, &results);
+                            shaded.push((index, results));
+
+                            let count = finished_tiles.fetch_add(1, Ordering::SeqCst) + 1;
+                            let percent = count * 10 / indices.len();
+                            if report_progress && percent > (count - 1) * 10 / indices.len() {
+                                println!("Render is {}% complete", percent * 10);
+                            }
+                        }
+                        shaded
+                    })
+                })
+                .collect();
+            workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+        })
+    }
+
     //Renders a scene without lighting
     pub fn quick_render(camera: &Camera, scene: &mut Scene, canvas: &mut Canvas) {
         let mut counter = 0;
         let mut percent = 0;
-        let pixels = &camera.hsize * &camera.vsize;
+        let region = Camera::region(camera);
+        let pixels = region.width * region.height;
         let percentage_update = pixels as f32 / 10.0;
-        for y in 0..camera.vsize {
-            for x in 0..camera.hsize {
+        for y in region.y..(region.y + region.height) {
+            for x in region.x..(region.x + region.width) {
                 let ray = Camera::ray_towards_pixel(camera, x, y);
                 let color = Scene::compute_color_quick(ray, scene);
                 if color != None {
-                    canvas.set(color.unwrap().clone(), x, y);
+                    Camera::set_pixel(camera, canvas, color.unwrap().clone(), x, y);
                     if counter as f32 > percentage_update {
                         percent += 10;
                         println!("Render is {}% complete", percent);
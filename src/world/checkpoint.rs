@@ -0,0 +1,207 @@
+use crate::core::color::Color;
+use crate::world::sampler::SampleSum;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+//Marks the start of a checkpoint file and its layout version
+const MAGIC: &[u8; 8] = b"RTCKPT01";
+
+//Where a render saves its progress, so a render which is stopped can carry on from the same point
+#[derive(Debug, PartialEq, Clone)]
+pub struct Checkpoint {
+    pub path: String,
+    pub fingerprint: u64, //Identifies the scene and settings, so progress from a different render is never resumed
+    pub interval: f32,    //Seconds between saves
+}
+
+//Progress stored in a checkpoint file
+#[derive(Debug, PartialEq, Clone)]
+pub enum Progress {
+    //Colors of the finished tiles, by their index in Camera::tiles
+    Tiles(HashMap<usize, Vec<Option<Color>>>),
+    //Sample totals of every pixel of a progressive render after its finished passes
+    Samples {
+        passes: usize,
+        samples: usize,
+        sums: Vec<SampleSum>,
+    },
+}
+
+impl Checkpoint {
+    //Creates a checkpoint which is saved once a minute
+    pub fn new(path: &str, fingerprint: u64) -> Checkpoint {
+        Checkpoint {
+            path: String::from(path),
+            fingerprint,
+            interval: 60.0,
+        }
+    }
+
+    //Saves the progress of a render of the given size
+    //The file is written next to the path and then renamed, so stopping partway keeps the previous checkpoint
+    pub fn save(&self, width: i32, height: i32, progress: &Progress) -> io::Result<()> {
+        let mut output = MAGIC.to_vec();
+        output.extend(&self.fingerprint.to_le_bytes());
+        output.extend(&(width as u32).to_le_bytes());
+        output.extend(&(height as u32).to_le_bytes());
+        match progress {
+            Progress::Tiles(tiles) => {
+                output.push(0);
+                output.extend(&(tiles.len() as u32).to_le_bytes());
+                let mut indices: Vec<&usize> = tiles.keys().collect();
+                indices.sort();
+                for index in indices {
+                    let colors = &tiles[index];
+                    output.extend(&(*index as u32).to_le_bytes());
+                    output.extend(&(colors.len() as u32).to_le_bytes());
+                    for color in colors {
+                        match color {
+                            Some(color) => {
+                                output.push(1);
+                                write_color(&mut output, color);
+                            }
+                            None => output.push(0),
+                        }
+                    }
+                }
+            }
+            Progress::Samples { passes, samples, sums } => {
+                output.push(1);
+                output.extend(&(*passes as u32).to_le_bytes());
+                output.extend(&(*samples as u32).to_le_bytes());
+                output.extend(&(sums.len() as u32).to_le_bytes());
+                for sum in sums {
+                    write_color(&mut output, &sum.weighted);
+                    output.extend(&sum.weight.to_le_bytes());
+                    write_color(&mut output, &sum.unweighted);
+                    output.extend(&(sum.count as u32).to_le_bytes());
+                }
+            }
+        }
+        let partial_path = format!("{}.partial", self.path);
+        fs::write(&partial_path, output)?;
+        fs::rename(&partial_path, &self.path)
+    }
+
+    //Loads the progress of a render of the given size
+    //Returns None if there is no checkpoint, or if it was saved by a render of a different scene, size or settings
+    pub fn load(&self, width: i32, height: i32) -> io::Result<Option<Progress>> {
+        let bytes = match fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        let mut reader = Reader { bytes: &bytes, position: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(invalid("Not a checkpoint file"));
+        }
+        let fingerprint = reader.u64()?;
+        if fingerprint != self.fingerprint || reader.u32()? != width as u32 || reader.u32()? != height as u32 {
+            return Ok(None);
+        }
+        let progress = match reader.take(1)?[0] {
+            0 => {
+                let mut tiles = HashMap::new();
+                for _ in 0..reader.u32()? {
+                    let index = reader.u32()? as usize;
+                    let mut colors = vec![];
+                    for _ in 0..reader.u32()? {
+                        colors.push(match reader.take(1)?[0] {
+                            0 => None,
+                            _ => Some(reader.color()?),
+                        });
+                    }
+                    tiles.insert(index, colors);
+                }
+                Progress::Tiles(tiles)
+            }
+            1 => {
+                let passes = reader.u32()? as usize;
+                let samples = reader.u32()? as usize;
+                let mut sums = vec![];
+                for _ in 0..reader.u32()? {
+                    sums.push(SampleSum {
+                        weighted: reader.color()?,
+                        weight: reader.f32()?,
+                        unweighted: reader.color()?,
+                        count: reader.u32()? as usize,
+                    });
+                }
+                Progress::Samples { passes, samples, sums }
+            }
+            _ => return Err(invalid("Unknown checkpoint contents")),
+        };
+        if reader.position != bytes.len() {
+            return Err(invalid("Unexpected data at the end of the checkpoint"));
+        }
+        Ok(Some(progress))
+    }
+
+    //Deletes the checkpoint file, which is no longer needed once the finished image is saved
+    pub fn remove(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        }
+    }
+}
+
+//Hashes the description of a render with 64 bit FNV-1a, which gives the same result on every platform and version
+pub fn fingerprint(description: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in description.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+//Appends the channels of a color as little endian floats
+fn write_color(output: &mut Vec<u8>, color: &Color) {
+    for channel in &[color.0, color.1, color.2] {
+        output.extend(&channel.to_le_bytes());
+    }
+}
+
+//Creates an error for a checkpoint which can not be read
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+//Reads little endian values from the contents of a checkpoint file
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    //Takes the next count bytes
+    fn take(&mut self, count: usize) -> io::Result<&'a [u8]> {
+        if self.bytes.len() - self.position < count {
+            return Err(invalid("Checkpoint file is truncated"));
+        }
+        self.position += count;
+        Ok(&self.bytes[self.position - count..self.position])
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let mut value = [0; 4];
+        value.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(value))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        let mut value = [0; 8];
+        value.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(value))
+    }
+
+    fn f32(&mut self) -> io::Result<f32> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    fn color(&mut self) -> io::Result<Color> {
+        Ok(Color(self.f32()?, self.f32()?, self.f32()?))
+    }
+}
@@ -0,0 +1,148 @@
+use crate::core::color::*;
+use crate::core::comp::Comp;
+use crate::materials::bsdf::cosine_sample_hemisphere;
+use crate::misc::random::random_float;
+use crate::ray_tracing::intersection::Intersection;
+use crate::ray_tracing::ray::Ray;
+use crate::world::path_tracer::path_color;
+use crate::world::scene::Scene;
+
+//An integrator decides the color seen along a ray, which is how the camera renderers shade a scene
+//Integrators are shared between render threads so they must be Send and Sync
+pub trait Integrator: Send + Sync {
+    //Finds the color seen along a ray, where depth limits the bounces, returning None if it misses every object
+    fn color(&self, ray: Ray, scene: &Scene, depth: i32) -> Option<Color>;
+}
+
+//Phong lighting with perfect reflections and refractions, as in Scene::compute_color
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Whitted;
+
+impl Integrator for Whitted {
+    fn color(&self, ray: Ray, scene: &Scene, depth: i32) -> Option<Color> {
+        Scene::compute_color(ray, scene, depth)
+    }
+}
+
+//Follows random paths of bounces so objects are lit by each other, as in path_color
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PathTracer;
+
+impl Integrator for PathTracer {
+    fn color(&self, ray: Ray, scene: &Scene, depth: i32) -> Option<Color> {
+        path_color(ray, scene, depth)
+    }
+}
+
+//The color of the object which is hit, without any lighting, as in Scene::compute_color_quick
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Flat;
+
+impl Integrator for Flat {
+    fn color(&self, ray: Ray, scene: &Scene, _depth: i32) -> Option<Color> {
+        Scene::compute_color_quick(ray, scene)
+    }
+}
+
+//The surface normal facing the ray, with each axis mapped from -1 to 1 onto a channel from 0 to 1
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Normals;
+
+impl Integrator for Normals {
+    fn color(&self, ray: Ray, scene: &Scene, _depth: i32) -> Option<Color> {
+        let comps = first_hit(&ray, scene)?;
+        let normal = &comps.n_vec;
+        Some(Color::new(normal.0 + 1.0, normal.1 + 1.0, normal.2 + 1.0) * 0.5)
+    }
+}
+
+//The distance along the ray to the first hit in every channel
+//Distances are not scaled, so they are best written to a .pfm or .hdr image or brought into range with the exposure
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Depth;
+
+impl Integrator for Depth {
+    fn color(&self, ray: Ray, scene: &Scene, _depth: i32) -> Option<Color> {
+        let intersections = Ray::intersect_scene(scene, ray);
+        let hit = Intersection::hit(&intersections)?;
+        Some(Color::new(hit.t, hit.t, hit.t))
+    }
+}
+
+//How open the surface is to its surroundings, from black where every nearby direction is blocked to white
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct AmbientOcclusion {
+    pub samples: usize, //Number of rays traced from each hit
+    pub distance: f32,  //Objects further away than this do not block the surface
+}
+
+impl Default for AmbientOcclusion {
+    fn default() -> AmbientOcclusion {
+        AmbientOcclusion {
+            samples: 16,
+            distance: 1.0,
+        }
+    }
+}
+
+impl Integrator for AmbientOcclusion {
+    fn color(&self, ray: Ray, scene: &Scene, _depth: i32) -> Option<Color> {
+        let comps = first_hit(&ray, scene)?;
+        let mut open = 0;
+        for _ in 0..self.samples {
+            let direction = cosine_sample_hemisphere(&comps.n_vec, random_float(), random_float());
+            let occlusion_ray = Ray::new_from_vec(comps.over_point.clone(), direction).with_time(comps.time);
+            let intersections = Ray::intersect_scene(scene, occlusion_ray);
+            match Intersection::hit(&intersections) {
+                Some(hit) if hit.t < self.distance => {}
+                _ => open += 1,
+            }
+        }
+        Some(WHITE * (open as f32 / self.samples.max(1) as f32))
+    }
+}
+
+//Integrators which can be chosen by name
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum IntegratorKind {
+    Whitted,
+    PathTracer,
+    Flat,
+    Normals,
+    Depth,
+    AmbientOcclusion,
+}
+
+impl IntegratorKind {
+    //Looks up an integrator by name
+    pub fn from_name(name: &str) -> Option<IntegratorKind> {
+        match name {
+            "whitted" => Some(IntegratorKind::Whitted),
+            "path" => Some(IntegratorKind::PathTracer),
+            "flat" => Some(IntegratorKind::Flat),
+            "normals" => Some(IntegratorKind::Normals),
+            "depth" => Some(IntegratorKind::Depth),
+            "ao" => Some(IntegratorKind::AmbientOcclusion),
+            _ => None,
+        }
+    }
+
+    //Creates the integrator with its default settings
+    pub fn integrator(&self) -> Box<dyn Integrator> {
+        match self {
+            IntegratorKind::Whitted => Box::new(Whitted),
+            IntegratorKind::PathTracer => Box::new(PathTracer),
+            IntegratorKind::Flat => Box::new(Flat),
+            IntegratorKind::Normals => Box::new(Normals),
+            IntegratorKind::Depth => Box::new(Depth),
+            IntegratorKind::AmbientOcclusion => Box::new(AmbientOcclusion::default()),
+        }
+    }
+}
+
+//Finds the shading variables of the first object a ray hits
+fn first_hit(ray: &Ray, scene: &Scene) -> Option<Comp> {
+    let intersections = Ray::intersect_scene(scene, ray.clone());
+    let hit = Intersection::hit(&intersections)?;
+    Some(Comp::compute_vars(hit, ray, &intersections))
+}
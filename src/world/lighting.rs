@@ -3,21 +3,42 @@ use crate::core::comp::Comp;
 use crate::core::matrix::Matrix4x4;
 use crate::core::vector::Vec4;
 use crate::ray_tracing::intersection::Intersection;
+use crate::materials::bsdf::{tangents, Bsdf};
 use crate::materials::material::*;
+use crate::objects::object::Object;
 use crate::ray_tracing::ray::Ray;
 use crate::world::scene::Scene;
 use crate::misc::utils::*;
-use rand::Rng;
+use crate::misc::random::{random_float, random_range};
+use std::f32::consts::PI;
 
-//A Light is either a PointLight or an AreaLight
-pub trait Light {
+//A Light is a PointLight, an AreaLight, a SpotLight, a DirectionalLight or the light given off by an emissive object
+//Lights are shared between render threads so they must be Send and Sync
+pub trait Light: Send + Sync {
     fn get_intensity(&self) -> &Color;
 
     fn get_position(&self) -> &Vec4;
 
     fn get_positions(&self) -> Vec<Vec4>;
 
-    fn light_intensity(&self, point: &Vec4, scene: &Scene) -> f32;
+    //Finds how much of the light reaches a point at a time, from 0 when fully shadowed to 1
+    fn light_intensity(&self, point: &Vec4, scene: &Scene, time: f32) -> f32;
+
+    //Finds the share of the light's intensity which is sent towards a point, for lights which do not shine every way
+    //This scales the diffuse and specular lighting after shadows, so it is not limited like light_intensity
+    fn attenuation(&self, _point: &Vec4) -> f32 {
+        1.0
+    }
+
+    //Picks a random point on the light to trace a shadow ray towards from a point being lit
+    fn sample_point(&self, _target: &Vec4) -> Vec4 {
+        self.get_position().clone()
+    }
+
+    //Finds the emissive object which gives off the light, for lights which are objects in the scene
+    fn emitter(&self) -> Option<&dyn Object> {
+        None
+    }
 }
 
 //An area light is an array of lights which produce soft shadows
@@ -51,18 +72,25 @@ impl Light for AreaLight {
         vec
     }
 
-    fn light_intensity(&self, point: &Vec4, scene: &Scene) -> f32 {
+    fn light_intensity(&self, point: &Vec4, scene: &Scene, time: f32) -> f32 {
         let mut total = 0.0;
         for v in 0..self.vsteps {
             for u in 0..self.usteps {
                 let light_position = self.point_on_light(u, v, true);
-                if !in_shadow(&light_position, point, scene) {
+                if !in_shadow_at(&light_position, point, scene, time) {
                     total += 1.0;
                 }
             }
         }
         total / (self.samples as f32)
     }
+
+    //Picks a point anywhere on the light rather than near one of its cells
+    fn sample_point(&self, _target: &Vec4) -> Vec4 {
+        &self.corner
+        + &self.uvec * random_range(0.0, self.usteps as f32)
+        + &self.vvec * random_range(0.0, self.vsteps as f32)
+    }
 }
 
 impl AreaLight {
@@ -87,11 +115,10 @@ impl AreaLight {
     }
 
     pub fn point_on_light(&self, u: usize, v: usize, jitter: bool) -> Vec4 {
-        let mut rng = rand::thread_rng();
         if jitter {
             &self.corner
-            + &self.uvec * ((u as f32) + rng.gen_range(-0.5, 0.5))
-            + &self.vvec * ((v as f32) + rng.gen_range(-0.5, 0.5))
+            + &self.uvec * ((u as f32) + random_range(-0.5, 0.5))
+            + &self.vvec * ((v as f32) + random_range(-0.5, 0.5))
         }
         else {
             &self.corner
@@ -122,8 +149,8 @@ impl Light for PointLight {
     }
 
     //Finds the intensity of a PointLight at a given point
-    fn light_intensity(&self, point: &Vec4, scene: &Scene) -> f32 {
-        if in_shadow(&self.position, point, scene) == true {
+    fn light_intensity(&self, point: &Vec4, scene: &Scene, time: f32) -> f32 {
+        if in_shadow_at(&self.position, point, scene, time) {
             1.0
         } else {
             1.0
@@ -141,55 +168,290 @@ impl PointLight {
     }
 }
 
-//Creates a ray reflected off of a surface
-pub fn reflected_color(
-    scene: &Scene,
-    comps: &Comp,
-    remaining: i32,
-) -> Color {
-    if comps.material.reflectivity == 0.0 || remaining <= 0 {
-        Color::new(0.0, 0.0, 0.0)
-    } else {
-        let reflected_ray = Ray::new_from_vec(
-            Vec4::new(
-                comps.over_point.0,
-                comps.over_point.1,
-                comps.over_point.2,
-                1.0,
-            ),
-            Vec4::new(comps.r_vec.0, comps.r_vec.1, comps.r_vec.2, 0.0),
-        );
-        let color = Scene::compute_color(reflected_ray, scene, remaining - 1);
-        if color != None {
-            color.unwrap() * comps.material.reflectivity
-        } else {
-            Color::new(0.0, 0.0, 0.0)
+//Light from a point which only shines inside a cone
+#[derive(Debug, PartialEq)]
+pub struct SpotLight {
+    pub intensity: Color,
+    pub position: Vec4,
+    pub direction: Vec4, //Direction the cone points in
+    pub angle: f32,      //Angle between the direction and the edge of the cone in degrees
+    pub falloff: f32,    //Angle inside the edge of the cone over which the light fades out in degrees
+}
+
+impl Light for SpotLight {
+    fn get_intensity(&self) -> &Color {
+        &self.intensity
+    }
+
+    fn get_position(&self) -> &Vec4 {
+        &self.position
+    }
+
+    fn get_positions(&self) -> Vec<Vec4> {
+        vec![self.position.clone()]
+    }
+
+    fn light_intensity(&self, point: &Vec4, scene: &Scene, time: f32) -> f32 {
+        if in_shadow_at(&self.position, point, scene, time) {
+            0.0
+        }
+        else {
+            1.0
+        }
+    }
+
+    //Lights points inside the cone fully, fading smoothly to nothing across the falloff at its edge
+    fn attenuation(&self, point: &Vec4) -> f32 {
+        let to_point = (point - &self.position).normalize();
+        let angle = clamp_float(Vec4::dot(&to_point, &self.direction), -1.0, 1.0).acos().to_degrees();
+        if angle >= self.angle {
+            0.0
+        }
+        else if angle <= self.angle - self.falloff {
+            1.0
+        }
+        else {
+            let t = (self.angle - angle) / self.falloff;
+            t * t * (3.0 - 2.0 * t)
         }
     }
 }
 
-//Finds the refracted color at a certain point
-pub fn refracted_color(scene: &Scene, comps: &Comp, remaining: i32) -> Color {
-    if remaining <= 0 || comps.material.transparency == 0.0 {
-        return BLACK;
+impl SpotLight {
+    //Creates a new SpotLight, where the falloff can be no wider than the cone
+    pub fn new(intensity: Color, position: Vec4, direction: Vec4, angle: f32, falloff: f32) -> SpotLight {
+        SpotLight {
+            intensity,
+            position,
+            direction: direction.normalize(),
+            angle,
+            falloff: clamp_float(falloff, 0.0, angle),
+        }
+    }
+}
+
+//Distance to the point a DirectionalLight is placed at, far enough away that its rays are parallel across a scene
+pub const SUN_DISTANCE: f32 = 1.0e6;
+
+//Light from very far away such as the sun, which arrives from the same direction everywhere
+//A light with an angular diameter covers a disk of the sky, giving soft shadows
+#[derive(Debug, PartialEq)]
+pub struct DirectionalLight {
+    pub intensity: Color,
+    pub direction: Vec4,        //Direction the light travels in
+    pub angular_diameter: f32,  //Angle across the disk of the light in the sky in degrees, which is 0 for sharp shadows
+    pub samples: usize,         //Number of shadow rays traced for soft shadows
+    pub position: Vec4,         //Point far away towards the light which shades surfaces with the Phong model
+}
+
+impl Light for DirectionalLight {
+    fn get_intensity(&self) -> &Color {
+        &self.intensity
+    }
+
+    fn get_position(&self) -> &Vec4 {
+        &self.position
+    }
+
+    fn get_positions(&self) -> Vec<Vec4> {
+        vec![self.position.clone()]
+    }
+
+    //Finds the share of shadow rays towards the disk of the light which are not blocked
+    //Shadow rays from every point are parallel, rather than meeting at a position
+    fn light_intensity(&self, point: &Vec4, scene: &Scene, time: f32) -> f32 {
+        let samples = if self.angular_diameter > 0.0 {
+            self.samples
+        }
+        else {
+            1
+        };
+        let mut total = 0.0;
+        for _ in 0..samples {
+            if !in_shadow_at(&self.sample_point(point), point, scene, time) {
+                total += 1.0;
+            }
+        }
+        total / (samples as f32)
+    }
+
+    //Picks a point far away from the target in a random direction across the disk of the light
+    fn sample_point(&self, target: &Vec4) -> Vec4 {
+        let towards_light = self.direction.negate();
+        if self.angular_diameter <= 0.0 {
+            return target + &towards_light * SUN_DISTANCE;
+        }
+        let cos_max = (self.angular_diameter / 2.0).to_radians().cos();
+        let cos_theta = 1.0 - random_float() * (1.0 - cos_max);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = random_range(0.0, 2.0 * PI);
+        let (tangent, bitangent) = tangents(&towards_light);
+        let direction = &towards_light * cos_theta + &tangent * (sin_theta * phi.cos()) + &bitangent * (sin_theta * phi.sin());
+        target + &direction * SUN_DISTANCE
+    }
+}
+
+impl DirectionalLight {
+    //Creates a new DirectionalLight shining in a direction
+    pub fn new(intensity: Color, direction: Vec4, angular_diameter: f32, samples: usize) -> DirectionalLight {
+        let direction = direction.normalize();
+        DirectionalLight {
+            intensity,
+            position: Vec4::new(0.0, 0.0, 0.0, 1.0) - &direction * SUN_DISTANCE,
+            direction,
+            angular_diameter,
+            samples: samples.max(1),
+        }
     }
-    //Ratio between refraction indices
-    let n_ratio = comps.n1 / comps.n2;
-    let cos_i = Vec4::dot(&comps.e_vec, &comps.n_vec);
-    //sin2_t is used to detect internal refraction
-    let sin2_t = (n_ratio.powi(2)) * (1.0 - (cos_i.powi(2)));
-    if sin2_t > 1.0 {
+}
+
+//Number of points an emissive object is sampled at when it lights a point
+pub const OBJECT_LIGHT_SAMPLES: usize = 16;
+
+//Number of random rays fired at an emissive object to find a point on its surface before giving up
+const SURFACE_POINT_ATTEMPTS: usize = 32;
+
+//The light given off by an emissive object, which gives soft shadows like an AreaLight
+//Its intensity is the emission of the object's material, and it is sampled at random points on the object's surface
+//Like every other light it lights a surface facing it with its full intensity, whatever its size and distance,
+//so it is not the same as the light its emission would give if it were gathered over the solid angle of the object
+#[derive(Debug)]
+pub struct ObjectLight {
+    pub object: Box<dyn Object>,
+    pub center: Vec4,         //Center of the object's bounds
+    pub radius: f32,          //Distance from the center to the corners of the bounds
+    pub positions: Vec<Vec4>, //Points on the surface which shade with the Phong model
+    pub samples: usize,       //Number of shadow rays traced to find how much of the object a point sees
+    pub intensity: Color,
+}
+
+impl Light for ObjectLight {
+    fn get_intensity(&self) -> &Color {
+        &self.intensity
+    }
+
+    fn get_position(&self) -> &Vec4 {
+        &self.center
+    }
+
+    fn get_positions(&self) -> Vec<Vec4> {
+        self.positions.clone()
+    }
+
+    //Finds the share of random points on the side of the surface facing a point which are not shadowed from it
+    fn light_intensity(&self, point: &Vec4, scene: &Scene, time: f32) -> f32 {
+        let mut total = 0.0;
+        for _ in 0..self.samples {
+            let light_position = self.sample_point(point);
+            if !in_shadow_at(&light_position, point, scene, time) {
+                total += 1.0;
+            }
+        }
+        total / (self.samples as f32)
+    }
+
+    //Picks a random point on the surface facing the target, since the rest of the surface is hidden behind it
+    //The center is picked if no such point is found, which leaves the target in the shadow of the object
+    fn sample_point(&self, target: &Vec4) -> Vec4 {
+        for _ in 0..SURFACE_POINT_ATTEMPTS {
+            if let Some((point, normal)) = self.surface_point() {
+                if Vec4::dot(&normal, &(target - &point)) > 0.0 {
+                    return point;
+                }
+            }
+        }
+        self.center.clone()
+    }
+
+    fn emitter(&self) -> Option<&dyn Object> {
+        Some(&*self.object)
+    }
+}
+
+impl ObjectLight {
+    //Creates the light given off by an object, sampled at the given number of points
+    //Returns None if the object is not emissive, has no finite bounds, or no point on its surface can be found
+    pub fn new(object: Box<dyn Object>, samples: usize) -> Option<ObjectLight> {
+        let intensity = object.get_material().emission.clone();
+        let bounds = object.parent_space_bounds();
+        if intensity == BLACK || bounds.is_empty() || !bounds.is_finite() {
+            return None;
+        }
+        let mut light = ObjectLight {
+            object,
+            center: bounds.center(),
+            radius: Vec4::magnitude(&(&bounds.max - &bounds.min)) / 2.0,
+            positions: vec![],
+            samples: samples.max(1),
+            intensity,
+        };
+        light.positions = (0..light.samples).filter_map(|_| light.surface_point()).map(|(point, _)| point).collect();
+        if light.positions.is_empty() {
+            None
+        }
+        else {
+            Some(light)
+        }
+    }
+
+    //Finds a random point on the surface of the object and its normal by firing a ray at it from a random direction
+    //Rays come evenly from every direction, so the surface of a convex object is sampled evenly over its area
+    //The normal faces the ray, and the point is lifted slightly off the surface along it so shadow rays aimed at the point
+    //are not blocked by the surface itself
+    pub fn surface_point(&self) -> Option<(Vec4, Vec4)> {
+        for _ in 0..SURFACE_POINT_ATTEMPTS {
+            let z = random_range(-1.0, 1.0);
+            let angle = random_range(0.0, 2.0 * PI);
+            let ring = (1.0 - z * z).sqrt();
+            let direction = Vec4::new(ring * angle.cos(), ring * angle.sin(), z, 0.0);
+
+            //The ray starts outside the bounds and passes through a random point of the disk facing it
+            let (tangent, bitangent) = tangents(&direction);
+            let offset = self.radius * random_float().sqrt();
+            let offset_angle = random_range(0.0, 2.0 * PI);
+            let origin = &self.center - &direction * (2.0 * self.radius)
+                + &tangent * (offset * offset_angle.cos())
+                + &bitangent * (offset * offset_angle.sin());
+            let ray = Ray::new_from_vec(origin, direction);
+            let intersections = match self.object.intersect(&ray) {
+                Some(intersections) => intersections,
+                None => continue,
+            };
+            if let Some(hit) = Intersection::hit(&intersections) {
+                let normal = if Vec4::dot(&hit.normal, &ray.direction) > 0.0 {
+                    hit.normal.negate()
+                }
+                else {
+                    hit.normal.clone()
+                };
+                return Some((Ray::position(&ray, hit.t) + &normal * EPSILON_BUMP, normal));
+            }
+        }
+        None
+    }
+}
+
+//Finds the light reflected and refracted towards the eye by the parts of a surface's BSDF which act like mirrors and glass
+//Rough surfaces trace glossy_samples rays through random microfacets, which blurs what they reflect and refract
+pub fn specular_color(scene: &Scene, comps: &Comp, bsdf: &dyn Bsdf, remaining: i32) -> Color {
+    if remaining <= 0 {
         return BLACK;
     }
-    let cos_t = (1.0 - sin2_t).sqrt();
-    let direction = (&comps.n_vec * (n_ratio * cos_i - cos_t)) - (&comps.e_vec * n_ratio);
-    let refract_ray = Ray::new_from_vec(comps.under_point.clone(), direction);
-    let color = Scene::compute_color(refract_ray, scene, remaining - 1);
-    if color != None {
-        color.unwrap() * comps.material.transparency
-    } else {
-        Color::new(0.0, 0.0, 0.0)
+    let mut total = BLACK;
+    for sample in bsdf.specular(&comps.n_vec, &comps.e_vec, comps.material.glossy_samples) {
+        //Directions into the surface start below it so they are not blocked by the surface itself
+        let origin = if Vec4::dot(&sample.direction, &comps.n_vec) > 0.0 {
+            comps.over_point.clone()
+        }
+        else {
+            comps.under_point.clone()
+        };
+        let ray = Ray::new_from_vec(origin, sample.direction).with_time(comps.time);
+        if let Some(color) = Scene::compute_color(ray, scene, remaining - 1) {
+            total = total + color * sample.weight;
+        }
     }
+    total
 }
 
 //Finds the refracted color at a certain point
@@ -197,7 +459,7 @@ pub fn environment_color(scene: &Scene, comps: &Comp, remaining: i32) -> Color {
     if remaining < 5 || comps.material.environment_lighting == 0.0 {
         return BLACK;
     }
-    let new_ray = Ray::new_from_vec(comps.over_point.clone(), comps.n_vec.clone());
+    let new_ray = Ray::new_from_vec(comps.over_point.clone(), comps.n_vec.clone()).with_time(comps.time);
     let intersections = Ray::intersect_scene(&scene, new_ray.clone());
     let hit = Intersection::hit(&intersections);
     let factor;
@@ -221,27 +483,6 @@ pub fn environment_color(scene: &Scene, comps: &Comp, remaining: i32) -> Color {
     } 
 }
 
-//Approximates reflectance
-pub fn schlick(comps: &Comp) -> f32 {
-   let mut cos = Vec4::dot(&comps.e_vec, &comps.n_vec);
-
-    if comps.n1 > comps.n2 {
-        //Ratio between refraction indices
-        let n_ratio = comps.n1 / comps.n2;
-        let cos_i = Vec4::dot(&comps.e_vec, &comps.n_vec);
-        //sin2_t is used to detect internal refraction
-        let sin2_t = (n_ratio * n_ratio) * (1.0 - (cos_i * cos_i));
-        if sin2_t > 1.0 {
-            return 1.0;
-        }
-        let cos_t = (1.0 - sin2_t).sqrt();
-        cos = cos_t;
-    }
-
-    let r0 = ((comps.n1 - comps.n2) / (comps.n1 + comps.n2)).powi(2);
-    r0 + (1.0 - r0) * ((1.0 - cos).powi(5))
-}
-
 //Computes a color given all the variables of the environment
 pub fn lighting(
     material: &Material,
@@ -251,7 +492,8 @@ pub fn lighting(
     e_vec: &Vec4,
     n_vec: &Vec4,
     light_intensity: f32,
-    list: &Vec<Matrix4x4>
+    list: &Vec<Matrix4x4>,
+    uv: Option<(f32, f32)>,
 ) -> Color {
     let mut color = material.color.clone();
     let material_pattern = &material.pattern;
@@ -259,18 +501,24 @@ pub fn lighting(
         color = material_pattern
             .as_ref()
             .unwrap()
-            .color_at_object(list, object_inverse, point);
+            .color_at_uv(list, object_inverse, point, uv);
     }
 
     //Combines surface and light color
-    let effective_color = color * light.get_intensity();
+    let effective_color = &color * light.get_intensity();
 
     //Computes the ambient value
     let ambient = &effective_color * material.ambient;
 
+    if material.model == ReflectionModel::Microfacet {
+        return ambient + microfacet_lighting(material, &color, &**light, point, e_vec, n_vec, light_intensity);
+    }
+
     let mut diffuse_sum = BLACK;
     let mut specular_sum = BLACK;
 
+    let light_intensity = clamp_float(light_intensity, 0.4, 1.0) * light.attenuation(point);
+
     //Iterate through lights
     for light_position in light.get_positions() {
         //Finds the direction to the light source
@@ -281,7 +529,7 @@ pub fn lighting(
 
         //A negative light_dot_normal means the light is obstructed
         if light_dot_normal >= 0.0 {
-            diffuse_sum = diffuse_sum + (&effective_color * material.diffuse * light_dot_normal * clamp_float(light_intensity, 0.4, 1.0));
+            diffuse_sum = diffuse_sum + (&effective_color * material.diffuse * light_dot_normal * light_intensity);
 
             //reflect_dot_eye represents the cosine of the angle between the reflection and eye vectors
             let reflect_vec = Vec4::reflect(&light_vec.negate(), &n_vec);
@@ -290,7 +538,7 @@ pub fn lighting(
             if reflect_dot_eye > 0.0 {
                 let factor = f32::powf(reflect_dot_eye as f32, material.shininess);
                 specular_sum = specular_sum
-                    + light.get_intensity() * &material.specular * factor * clamp_float(light_intensity, 0.4, 1.0);
+                    + light.get_intensity() * &material.specular * factor * light_intensity;
             }
         }
     }
@@ -299,12 +547,94 @@ pub fn lighting(
     ambient + (diffuse_sum * light_count) + (specular_sum * light_count)
 }
 
+//Computes the light reflected towards the eye by a microfacet material with the given base color
+//Lights are as bright as with the Phong model, so a white rough dielectric facing a light is lit about as much as a Phong surface with a diffuse of 1
+pub fn microfacet_lighting(
+    material: &Material,
+    base_color: &Color,
+    light: &dyn Light,
+    point: &Vec4,
+    e_vec: &Vec4,
+    n_vec: &Vec4,
+    light_intensity: f32,
+) -> Color {
+    let mut sum = BLACK;
+    let light_intensity = clamp_float(light_intensity, 0.4, 1.0) * light.attenuation(point);
+    let positions = light.get_positions();
+    for light_position in &positions {
+        let light_vec = (light_position - point).normalize();
+        let light_dot_normal = Vec4::dot(&light_vec, n_vec);
+        if light_dot_normal > 0.0 {
+            let brdf = microfacet_brdf(base_color, material.metallic, material.roughness, n_vec, e_vec, &light_vec);
+            sum = sum + brdf * light.get_intensity() * (PI * light_dot_normal * light_intensity);
+        }
+    }
+    sum * (1.0 / positions.len() as f32)
+}
+
+//Finds how much light arriving along light_vec is reflected along e_vec, per unit of solid angle
+//Combines a Lambertian diffuse lobe for dielectrics with a Cook-Torrance specular lobe using the GGX distribution
+pub fn microfacet_brdf(base_color: &Color, metallic: f32, roughness: f32, n_vec: &Vec4, e_vec: &Vec4, light_vec: &Vec4) -> Color {
+    let normal_dot_eye = Vec4::dot(n_vec, e_vec);
+    let normal_dot_light = Vec4::dot(n_vec, light_vec);
+    if normal_dot_eye <= 0.0 || normal_dot_light <= 0.0 {
+        return BLACK;
+    }
+    let half_vec = (e_vec + light_vec).normalize();
+    let alpha = roughness_alpha(roughness);
+    //Dielectrics reflect about 4% of light head on, while metals reflect their base color
+    let f0 = Color::new(0.04, 0.04, 0.04) * (1.0 - metallic) + base_color * metallic;
+    let fresnel = fresnel_schlick(&f0, Vec4::dot(e_vec, &half_vec));
+    let distribution = ggx_distribution(alpha, Vec4::dot(n_vec, &half_vec));
+    let masking = smith_masking(alpha, normal_dot_eye) * smith_masking(alpha, normal_dot_light);
+    let specular = &fresnel * (distribution * masking / (4.0 * normal_dot_eye * normal_dot_light));
+    //Light which is not reflected at the surface enters it and is scattered back out by dielectrics, but absorbed by metals
+    let diffuse = (WHITE - fresnel) * base_color * ((1.0 - metallic) / PI);
+    diffuse + specular
+}
+
+//Maps the roughness of a material onto the width of the GGX distribution
+//Squaring the roughness makes it change evenly to the eye, and a small minimum keeps perfectly smooth surfaces from dividing by zero
+pub fn roughness_alpha(roughness: f32) -> f32 {
+    (roughness * roughness).max(0.002)
+}
+
+//Finds the density of microfacets facing along a direction whose cosine with the normal is cos_theta
+pub fn ggx_distribution(alpha: f32, cos_theta: f32) -> f32 {
+    if cos_theta <= 0.0 {
+        return 0.0;
+    }
+    let alpha2 = alpha * alpha;
+    let denominator = cos_theta * cos_theta * (alpha2 - 1.0) + 1.0;
+    alpha2 / (PI * denominator * denominator)
+}
+
+//Finds the fraction of microfacets seen from a direction whose cosine with the normal is cos_theta, rather than hidden behind others
+pub fn smith_masking(alpha: f32, cos_theta: f32) -> f32 {
+    if cos_theta <= 0.0 {
+        return 0.0;
+    }
+    let alpha2 = alpha * alpha;
+    2.0 * cos_theta / (cos_theta + (alpha2 + (1.0 - alpha2) * cos_theta * cos_theta).sqrt())
+}
+
+//Approximates how much light is reflected at an angle whose cosine is cos_theta, given the reflectance head on
+pub fn fresnel_schlick(f0: &Color, cos_theta: f32) -> Color {
+    let factor = (1.0 - clamp_float(cos_theta, 0.0, 1.0)).powi(5);
+    f0 + (WHITE - f0) * factor
+}
+
 //Creates a vector from a point to a given light and tests for intersections within that distance
 pub fn in_shadow(light_position: &Vec4, point: &Vec4, scene: &Scene) -> bool {
+    in_shadow_at(light_position, point, scene, 0.0)
+}
+
+//Tests for intersections between a point and a light with moving objects placed where they are at a time
+pub fn in_shadow_at(light_position: &Vec4, point: &Vec4, scene: &Scene, time: f32) -> bool {
     let vector = light_position - point;
     let distance = Vec4::magnitude(&vector);
     let direction = (&vector).normalize();
-    let shadow_ray = Ray::new_from_vec(Vec4::new(point.0, point.1, point.2, 1.0), direction);
+    let shadow_ray = Ray::new_from_vec(Vec4::new(point.0, point.1, point.2, 1.0), direction).with_time(time);
     let intersections = Ray::intersect_scene(scene, shadow_ray);
     let ray_hit = Intersection::hit(&intersections);
     let mut result = false;
@@ -1,3 +1,9 @@
+pub mod animation;
 pub mod camera;
+pub mod checkpoint;
+pub mod integrator;
 pub mod lighting;
-pub mod scene;
\ No newline at end of file
+pub mod path_tracer;
+pub mod sampler;
+pub mod scene;
+pub mod scene_file;
\ No newline at end of file
@@ -0,0 +1,102 @@
+use crate::core::color::*;
+use crate::core::comp::Comp;
+use crate::core::vector::Vec4;
+use crate::materials::bsdf::Bsdf;
+use crate::objects::object::Object;
+use crate::misc::random::random_float;
+use crate::ray_tracing::intersection::Intersection;
+use crate::ray_tracing::ray::Ray;
+use crate::world::lighting::*;
+use crate::world::scene::Scene;
+use std::f32::consts::PI;
+
+//Number of bounces every path makes before Russian roulette may end it
+pub const ROULETTE_DEPTH: i32 = 3;
+
+//Finds the color seen along a ray by following one random path of bounces through the scene
+//Every bounce is picked by the BSDF of the surface, so objects are lit by light bounced off other objects as well as by lights
+//Ambient lighting is left out, since light from the rest of the scene takes its place
+//Emissive objects which are lights in the scene are already found with shadow rays, so their emission is only added
+//when they are seen directly or through perfect reflections and refractions, which shadow rays can not follow
+//Other emissive objects, such as planes which have no ObjectLight, light the scene through the bounces which hit them
+//Returns None if the ray misses every object
+pub fn path_color(ray: Ray, scene: &Scene, max_bounces: i32) -> Option<Color> {
+    let mut ray = ray;
+    let mut throughput = WHITE;
+    let mut result = BLACK;
+    let mut bounce = 0;
+    let mut specular = true;
+    loop {
+        let intersections = Ray::intersect_scene(scene, ray.clone());
+        let hit = match Intersection::hit(&intersections) {
+            Some(hit) => hit,
+            None if bounce == 0 => return None,
+            None => break,
+        };
+        let object = hit.object;
+        let comps = Comp::compute_vars(hit, &ray, &intersections);
+        let bsdf = comps.bsdf();
+        if comps.material.emission != BLACK && (specular || !is_light(scene, object)) {
+            result = result + &throughput * &comps.material.emission;
+        }
+        result = result + &throughput * direct_light(scene, &comps, &*bsdf);
+        if bounce >= max_bounces {
+            break;
+        }
+
+        let sample = match bsdf.sample(&comps.n_vec, &comps.e_vec) {
+            Some(sample) => sample,
+            None => break,
+        };
+        specular = sample.specular;
+        throughput = throughput * sample.weight;
+        if throughput == BLACK {
+            break;
+        }
+        //Directions into the surface start below it so they are not blocked by the surface itself
+        let origin = if Vec4::dot(&sample.direction, &comps.n_vec) > 0.0 {
+            comps.over_point.clone()
+        }
+        else {
+            comps.under_point.clone()
+        };
+        ray = Ray::new_from_vec(origin, sample.direction).with_time(comps.time);
+        bounce += 1;
+
+        //Paths carrying little light are ended at random, and the ones which go on are brightened to make up for them
+        if bounce >= ROULETTE_DEPTH {
+            let survival = throughput.0.max(throughput.1).max(throughput.2).min(0.95);
+            if random_float() >= survival {
+                break;
+            }
+            throughput = throughput * (1.0 / survival);
+        }
+    }
+    Some(result)
+}
+
+//Finds the light arriving straight from every light source and scattered towards the eye, tracing one shadow ray to a random point on each
+//Lights are as bright as with the Phong model, where a light shining straight at a white matte surface lights it with its full intensity
+//This includes emissive objects, whose light is not scaled by how large they look, so it only matches their emission
+//seen along bounces for an object covering the whole sky
+pub fn direct_light(scene: &Scene, comps: &Comp, bsdf: &dyn Bsdf) -> Color {
+    let mut total = BLACK;
+    for light in &scene.light_sources {
+        let position = light.sample_point(&comps.over_point);
+        let light_vec = (&position - &comps.over_point).normalize();
+        let light_dot_normal = Vec4::dot(&light_vec, &comps.n_vec);
+        if light_dot_normal > 0.0 && !in_shadow_at(&position, &comps.over_point, scene, comps.time) {
+            let scattered = bsdf.eval(&comps.n_vec, &comps.e_vec, &light_vec);
+            total = total + scattered * light.get_intensity() * (PI * light_dot_normal * light.attenuation(&comps.over_point));
+        }
+    }
+    total
+}
+
+//Tests whether an object is part of an emissive object which is a light in the scene
+fn is_light(scene: &Scene, object: &dyn Object) -> bool {
+    scene
+        .light_sources
+        .iter()
+        .any(|light| light.emitter().is_some_and(|emitter| emitter.includes(object)))
+}
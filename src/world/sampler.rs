@@ -0,0 +1,297 @@
+use crate::core::color::*;
+use crate::misc::random::*;
+
+//How sample positions are spread across a pixel's filter footprint
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SamplePattern {
+    Random,     //Every sample is placed independently
+    Stratified, //The footprint is split into a grid of cells and each sample is jittered inside its own cell
+}
+
+impl SamplePattern {
+    //Looks up a pattern by name
+    pub fn from_name(name: &str) -> Option<SamplePattern> {
+        match name {
+            "random" => Some(SamplePattern::Random),
+            "stratified" => Some(SamplePattern::Stratified),
+            _ => None,
+        }
+    }
+}
+
+//Reconstruction filters which weight samples by their distance from the pixel center
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Filter {
+    Box,      //Equal weight inside the pixel
+    Tent,     //Weight falls off linearly, reaching zero one pixel from the center
+    Gaussian, //Smooth falloff which slightly blurs the image
+    Mitchell, //Mitchell-Netravali cubic with B = C = 1/3, which keeps edges sharp
+}
+
+impl Filter {
+    //Looks up a filter by name
+    pub fn from_name(name: &str) -> Option<Filter> {
+        match name {
+            "box" => Some(Filter::Box),
+            "tent" => Some(Filter::Tent),
+            "gaussian" => Some(Filter::Gaussian),
+            "mitchell" => Some(Filter::Mitchell),
+            _ => None,
+        }
+    }
+
+    //Distance from the pixel center, in pixels, beyond which the filter has no weight
+    pub fn radius(&self) -> f32 {
+        match self {
+            Filter::Box => 0.5,
+            Filter::Tent => 1.0,
+            Filter::Gaussian => 1.5,
+            Filter::Mitchell => 2.0,
+        }
+    }
+
+    //Weight of the filter along one axis at a distance from the center
+    fn weight_1d(&self, distance: f32) -> f32 {
+        let distance = distance.abs();
+        if distance > self.radius() {
+            return 0.0;
+        }
+        match self {
+            Filter::Box => 1.0,
+            Filter::Tent => 1.0 - distance,
+            Filter::Gaussian => {
+                //Shifted down so the weight reaches zero at the radius instead of cutting off
+                const ALPHA: f32 = 2.0;
+                (-ALPHA * distance * distance).exp() - (-ALPHA * 1.5 * 1.5f32).exp()
+            }
+            Filter::Mitchell => {
+                const B: f32 = 1.0 / 3.0;
+                const C: f32 = 1.0 / 3.0;
+                let x = distance;
+                if x < 1.0 {
+                    ((12.0 - 9.0 * B - 6.0 * C) * x * x * x + (-18.0 + 12.0 * B + 6.0 * C) * x * x + (6.0 - 2.0 * B))
+                        / 6.0
+                }
+                else {
+                    ((-B - 6.0 * C) * x * x * x + (6.0 * B + 30.0 * C) * x * x + (-12.0 * B - 48.0 * C) * x
+                        + (8.0 * B + 24.0 * C))
+                        / 6.0
+                }
+            }
+        }
+    }
+
+    //Weight of a sample offset from the pixel center by (x, y) pixels
+    pub fn weight(&self, x: f32, y: f32) -> f32 {
+        self.weight_1d(x) * self.weight_1d(y)
+    }
+}
+
+//Maps a point on the unit square to the unit disk, keeping evenly spread points evenly spread
+//Uses the concentric mapping by Shirley and Chiu, which distorts areas less than using the square root of the radius
+pub fn sample_disk(u: f32, v: f32) -> (f32, f32) {
+    let x = 2.0 * u - 1.0;
+    let y = 2.0 * v - 1.0;
+    if x == 0.0 && y == 0.0 {
+        return (0.0, 0.0);
+    }
+    let (radius, theta) = if x.abs() > y.abs() {
+        (x, std::f32::consts::FRAC_PI_4 * (y / x))
+    }
+    else {
+        (y, std::f32::consts::FRAC_PI_2 - std::f32::consts::FRAC_PI_4 * (x / y))
+    };
+    (radius * theta.cos(), radius * theta.sin())
+}
+
+//Settings for stochastic supersampling
+#[derive(Debug, PartialEq, Clone)]
+pub struct SamplerSettings {
+    pub samples: usize, //Number of rays traced per pixel
+    pub pattern: SamplePattern,
+    pub filter: Filter,
+    pub seed: u64, //Renders with the same seed and settings produce the same image
+}
+
+impl Default for SamplerSettings {
+    fn default() -> SamplerSettings {
+        SamplerSettings {
+            samples: 16,
+            pattern: SamplePattern::Stratified,
+            filter: Filter::Box,
+            seed: 0,
+        }
+    }
+}
+
+impl SamplerSettings {
+    //Creates settings with a sample count and the default pattern, filter and seed
+    pub fn new(samples: usize) -> SamplerSettings {
+        SamplerSettings {
+            samples,
+            ..SamplerSettings::default()
+        }
+    }
+
+    //Generates the sample positions for a pixel as offsets in pixels from its center
+    //The current thread's generator is reseeded from the pixel so the offsets only depend on the seed and the pixel
+    pub fn offsets(&self, x: i32, y: i32) -> Vec<(f32, f32)> {
+        reseed(pixel_seed(self.seed, x, y));
+        let radius = self.filter.radius();
+        let samples = self.samples.max(1);
+        let mut offsets = Vec::with_capacity(samples);
+        match self.pattern {
+            SamplePattern::Random => {
+                for _ in 0..samples {
+                    offsets.push((random_range(-radius, radius), random_range(-radius, radius)));
+                }
+            }
+            SamplePattern::Stratified => {
+                //Counts which are not square leave the last row of cells partly empty
+                let columns = (samples as f32).sqrt().ceil() as usize;
+                let rows = samples.div_ceil(columns);
+                let cell_width = 2.0 * radius / columns as f32;
+                let cell_height = 2.0 * radius / rows as f32;
+                for index in 0..samples {
+                    let column = index % columns;
+                    let row = index / columns;
+                    offsets.push((
+                        -radius + (column as f32 + random_float()) * cell_width,
+                        -radius + (row as f32 + random_float()) * cell_height,
+                    ));
+                }
+            }
+        }
+        offsets
+    }
+}
+
+//Running totals of the samples traced for a pixel, weighted by a reconstruction filter
+#[derive(Debug, PartialEq, Clone)]
+pub struct SampleSum {
+    pub weighted: Color,
+    pub weight: f32,
+    pub unweighted: Color,
+    pub count: usize,
+}
+
+impl Default for SampleSum {
+    fn default() -> SampleSum {
+        SampleSum {
+            weighted: BLACK,
+            weight: 0.0,
+            unweighted: BLACK,
+            count: 0,
+        }
+    }
+}
+
+impl SampleSum {
+    //Adds a sample with its filter weight
+    pub fn add(&mut self, color: Color, weight: f32) {
+        self.weighted = &self.weighted + &color * weight;
+        self.weight += weight;
+        self.unweighted = &self.unweighted + color;
+        self.count += 1;
+    }
+
+    //Adds the samples of another sum
+    pub fn merge(&mut self, other: &SampleSum) {
+        self.weighted = &self.weighted + &other.weighted;
+        self.weight += other.weight;
+        self.unweighted = &self.unweighted + &other.unweighted;
+        self.count += other.count;
+    }
+
+    //Finds the color of the pixel from its samples
+    //Filters with negative lobes can leave almost no total weight, in which case a plain average is safer
+    pub fn color(&self) -> Color {
+        if self.weight > 1e-4 {
+            &self.weighted * (1.0 / self.weight)
+        }
+        else if self.count > 0 {
+            &self.unweighted * (1.0 / self.count as f32)
+        }
+        else {
+            BLACK
+        }
+    }
+}
+
+//Settings for progressive rendering, which refines the whole image pass after pass
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProgressiveSettings {
+    pub sampler: SamplerSettings,  //Pattern, filter and seed of the samples, with the number of samples per pixel to stop at
+    pub snapshot_interval: f32,    //Seconds between writing the image so far
+    pub time_limit: Option<f32>,   //Seconds after which the render stops with the samples it has
+}
+
+impl Default for ProgressiveSettings {
+    fn default() -> ProgressiveSettings {
+        ProgressiveSettings {
+            sampler: SamplerSettings::new(256),
+            snapshot_interval: 10.0,
+            time_limit: None,
+        }
+    }
+}
+
+impl ProgressiveSettings {
+    //Finds the number of samples per pixel traced by the pass after the given number of samples, if any are left
+    //The first pass traces a single sample so a preview is ready quickly, then every pass doubles the total
+    pub fn pass_size(&self, samples: usize) -> Option<usize> {
+        if samples >= self.sampler.samples {
+            return None;
+        }
+        Some(samples.max(1).min(self.sampler.samples - samples))
+    }
+
+    //Finds the number of samples per pixel traced by each pass
+    pub fn pass_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![];
+        let mut total = 0;
+        while let Some(size) = self.pass_size(total) {
+            sizes.push(size);
+            total += size;
+        }
+        sizes
+    }
+
+    //Gets the sampler used for a pass, with a seed of its own so passes trace different rays
+    pub fn pass_sampler(&self, pass: usize, samples: usize) -> SamplerSettings {
+        SamplerSettings {
+            samples,
+            seed: pixel_seed(self.sampler.seed, pass as i32, -1),
+            ..self.sampler.clone()
+        }
+    }
+}
+
+//Settings for adaptive anti-aliasing, which only spends extra rays on pixels that differ from their neighbours
+#[derive(Debug, PartialEq, Clone)]
+pub struct AdaptiveSettings {
+    pub threshold: f32, //Largest channel difference allowed before a pixel or sub-pixel area is split
+    pub max_depth: u32, //Number of times a pixel may be split in four, so up to (2^max_depth + 1)^2 rays are traced for it
+}
+
+impl Default for AdaptiveSettings {
+    fn default() -> AdaptiveSettings {
+        AdaptiveSettings {
+            threshold: 0.1,
+            max_depth: 2,
+        }
+    }
+}
+
+impl AdaptiveSettings {
+    //Finds the largest channel difference between two colors, ignoring brightness above 1 which displays the same
+    pub fn contrast(first: &Color, second: &Color) -> f32 {
+        let channel = |a: f32, b: f32| (a.clamp(0.0, 1.0) - b.clamp(0.0, 1.0)).abs();
+        channel(first.0, second.0).max(channel(first.1, second.1)).max(channel(first.2, second.2))
+    }
+
+    //Checks if two colors differ by more than the threshold
+    pub fn differs(&self, first: &Color, second: &Color) -> bool {
+        AdaptiveSettings::contrast(first, second) > self.threshold
+    }
+}
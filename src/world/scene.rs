@@ -8,10 +8,12 @@ use crate::ray_tracing::intersection::Intersection;
 use crate::world::lighting::*;
 use crate::materials::material::Material;
 use crate::ray_tracing::ray::Ray;
+use crate::ray_tracing::bvh::Bvh;
 
 pub struct Scene {
     pub light_sources: Vec<Box<dyn Light>>,
-    pub objects: Vec<Box<dyn Object>>,
+    pub objects: Vec<Box<dyn Object>>, //build_bvh must be called again after replacing or transforming any of these
+    pub bvh: Option<Bvh>,
 }
 
 impl Scene {
@@ -20,6 +22,31 @@ impl Scene {
         Scene {
             light_sources: vec![],
             objects: vec![],
+            bvh: None,
+        }
+    }
+
+    //Builds a bounding volume hierarchy over the objects currently in the scene
+    //Objects added afterwards are still intersected, but the hierarchy is ignored until it is rebuilt
+    pub fn build_bvh(&mut self) {
+        self.bvh = Some(Bvh::build(&self.objects));
+    }
+
+    //Adds an ObjectLight for every emissive object in the scene, so they light the objects around them
+    //Objects without finite bounds, such as planes, still glow but do not light anything
+    pub fn add_object_lights(&mut self) {
+        for object in &self.objects {
+            if let Some(light) = ObjectLight::new(object.clone(), OBJECT_LIGHT_SAMPLES) {
+                self.light_sources.push(Box::new(light));
+            }
+        }
+    }
+
+    //Finds the indices of the objects which a ray could intersect
+    pub fn candidates(&self, ray: &Ray) -> Vec<usize> {
+        match &self.bvh {
+            Some(bvh) if bvh.is_current(&self.objects) => bvh.candidates(ray),
+            _ => (0..self.objects.len()).collect(),
         }
     }
 
@@ -31,7 +58,7 @@ impl Scene {
     ) -> Color {
         let mut color = Color::new(0.0, 0.0, 0.0);
         for light in &scene.light_sources {
-            let light_intensity = light.light_intensity(&comps.over_point, &scene);
+            let light_intensity = light.light_intensity(&comps.over_point, &scene, comps.time);
             color = color
                 + lighting(
                     &comps.material,
@@ -42,20 +69,12 @@ impl Scene {
                     &comps.n_vec,
                     light_intensity,
                     &comps.parent_inverses,
+                    comps.uv,
                 );
         }
         let environment = environment_color(scene, &comps, remaining);
-        let mut reflected = reflected_color(&scene, comps, remaining);
-        let mut refracted = refracted_color(&scene, comps, remaining);
-
-        let reflectance = schlick(comps);
-
-        if comps.material.reflectivity > 0.0 && comps.material.transparency > 0.0 {
-            reflected = reflected * reflectance;
-            refracted = refracted * (1.0 - reflectance);
-        }
-        
-        color + reflected + refracted + environment
+        let specular = specular_color(scene, comps, &*comps.bsdf(), remaining);
+        color + specular + environment + &comps.material.emission
     }
 
     //Computes the color at a given point
@@ -105,6 +124,7 @@ impl Scene {
                     Material::default(),
                 )),
             ],
+            bvh: None,
         };
         scene
     }
@@ -0,0 +1,998 @@
+use crate::core::canvas::Canvas;
+use crate::core::color::Color;
+use crate::core::matrix::Matrix4x4;
+use crate::core::vector::Vec4;
+use crate::materials::material::*;
+use crate::materials::patterns::*;
+use crate::materials::texture::UvMapping;
+use crate::misc::axis::Axis;
+use crate::objects::cone::Cone;
+use crate::objects::csg::*;
+use crate::objects::cube::Cube;
+use crate::objects::cylinder::Cylinder;
+use crate::objects::group::Group;
+use crate::objects::moving::*;
+use crate::objects::object::Object;
+use crate::objects::parser::Parser;
+use crate::objects::plane::Plane;
+use crate::objects::sphere::Sphere;
+use crate::objects::triangle::Triangle;
+use crate::world::animation::*;
+use crate::world::camera::*;
+use crate::world::lighting::*;
+use crate::world::scene::Scene;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+//A scene file describes a scene as a list of blocks closed by "end", with one statement per line
+//The keywords of each block are listed in docs/scene_format.md
+
+//Object keywords which may start an object block
+const OBJECT_KINDS: [&str; 9] = ["sphere", "plane", "cube", "cylinder", "cone", "triangle", "group", "csg", "obj"];
+
+//Describes an error found while loading a scene file
+#[derive(Debug, PartialEq, Clone)]
+pub struct SceneError {
+    pub line: usize, //Line of the error, or 0 if it is not tied to a line
+    pub message: String,
+}
+
+impl SceneError {
+    //Creates a new SceneError
+    pub fn new(line: usize, message: &str) -> SceneError {
+        SceneError {
+            line,
+            message: String::from(message),
+        }
+    }
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        }
+        else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+//Camera properties read from a scene file
+//These are kept separate from Camera so the resolution and field of view can be overridden before it is built
+#[derive(Debug, PartialEq, Clone)]
+pub struct CameraSettings {
+    pub width: usize,
+    pub height: usize,
+    pub fov: f32,
+    pub from: Vec4,
+    pub to: Vec4,
+    pub up: Vec4,
+    pub projection: Projection,
+    pub aperture: f32,
+    pub focal_distance: Option<f32>, //Defaults to the distance from "from" to "to"
+    pub shutter: (f32, f32),
+}
+
+impl Default for CameraSettings {
+    //Creates the camera settings used when a scene file has no camera block
+    fn default() -> CameraSettings {
+        CameraSettings {
+            width: 100,
+            height: 100,
+            fov: 45.0,
+            from: Vec4(0.0, 0.0, -5.0, 1.0),
+            to: Vec4(0.0, 0.0, 0.0, 1.0),
+            up: Vec4(0.0, 1.0, 0.0, 0.0),
+            projection: Projection::Perspective,
+            aperture: 0.0,
+            focal_distance: None,
+            shutter: (0.0, 0.0),
+        }
+    }
+}
+
+impl CameraSettings {
+    //Checks if the projection can show the field of view, as only fisheye cameras can reach 180 degrees or more
+    pub fn fov_is_valid(&self) -> bool {
+        self.fov < 180.0 || self.projection == Projection::Fisheye
+    }
+
+    //Creates a Camera from the settings
+    pub fn build(&self) -> Camera {
+        let mut camera = Camera::new(self.width, self.height, self.fov);
+        camera.transform(Matrix4x4::view_transform(self.from.clone(), self.to.clone(), self.up.clone()));
+        let focal_distance = self
+            .focal_distance
+            .unwrap_or_else(|| Vec4::magnitude(&(self.to.clone() - &self.from)));
+        camera.set_lens(self.aperture, focal_distance);
+        camera.set_projection(self.projection);
+        camera.set_shutter(self.shutter.0, self.shutter.1);
+        camera
+    }
+}
+
+//The scene and camera described by a scene file
+pub struct SceneFile {
+    pub scene: Scene,
+    pub camera: CameraSettings,
+}
+
+impl SceneFile {
+    //Loads a scene file from disk
+    pub fn load(path: &str) -> Result<SceneFile, SceneError> {
+        SceneFile::load_at(path, 0.0)
+    }
+
+    //Loads a scene file from disk as it is at a time, which is the frame number of an animation
+    pub fn load_at(path: &str, time: f32) -> Result<SceneFile, SceneError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) => return Err(SceneError::new(0, &format!("Failed to read {}: {}", path, error))),
+        };
+        let base_dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+        SceneFile::parse_at(&text, base_dir, time)
+    }
+
+    //Parses the text of a scene file, resolving obj paths against base_dir
+    pub fn parse(text: &str, base_dir: &Path) -> Result<SceneFile, SceneError> {
+        SceneFile::parse_at(text, base_dir, 0.0)
+    }
+
+    //Parses the text of a scene file as it is at a time
+    pub fn parse_at(text: &str, base_dir: &Path, time: f32) -> Result<SceneFile, SceneError> {
+        let mut parser = SceneParser::new(text, base_dir, time);
+        parser.parse()
+    }
+
+    //Creates the camera described by the file
+    pub fn camera(&self) -> Camera {
+        self.camera.build()
+    }
+}
+
+//A line of a scene file split into words
+#[derive(Debug, Clone)]
+struct Statement {
+    line: usize,
+    tokens: Vec<String>,
+}
+
+impl Statement {
+    //Gets the keyword at the start of the statement
+    fn keyword(&self) -> &str {
+        &self.tokens[0]
+    }
+
+    //Checks that the statement has the expected number of values after the keyword
+    fn expect(&self, count: usize) -> Result<(), SceneError> {
+        if self.tokens.len() - 1 != count {
+            return Err(SceneError::new(
+                self.line,
+                &format!("'{}' expects {} value(s) but got {}", self.keyword(), count, self.tokens.len() - 1),
+            ));
+        }
+        Ok(())
+    }
+
+    //Parses the value at a given position as a float
+    fn float(&self, index: usize) -> Result<f32, SceneError> {
+        self.tokens[index]
+            .parse::<f32>()
+            .map_err(|_| SceneError::new(self.line, &format!("'{}' is not a number", self.tokens[index])))
+    }
+
+    //Parses a statement with a single float value
+    fn single_float(&self) -> Result<f32, SceneError> {
+        self.expect(1)?;
+        self.float(1)
+    }
+
+    //Parses a statement with a single whole number value
+    fn integer(&self) -> Result<usize, SceneError> {
+        self.expect(1)?;
+        self.tokens[1]
+            .parse::<usize>()
+            .map_err(|_| SceneError::new(self.line, &format!("'{}' is not a whole number", self.tokens[1])))
+    }
+
+    //Parses a statement with a single true or false value
+    fn boolean(&self) -> Result<bool, SceneError> {
+        self.expect(1)?;
+        match self.tokens[1].as_str() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            other => Err(SceneError::new(self.line, &format!("'{}' is not true or false", other))),
+        }
+    }
+
+    //Parses a statement with three float values
+    fn triple(&self) -> Result<(f32, f32, f32), SceneError> {
+        self.expect(3)?;
+        Ok((self.float(1)?, self.float(2)?, self.float(3)?))
+    }
+
+    //Parses a statement containing a point
+    fn point(&self) -> Result<Vec4, SceneError> {
+        let (x, y, z) = self.triple()?;
+        Ok(Vec4(x, y, z, 1.0))
+    }
+
+    //Parses a statement containing a vector
+    fn vector(&self) -> Result<Vec4, SceneError> {
+        let (x, y, z) = self.triple()?;
+        Ok(Vec4(x, y, z, 0.0))
+    }
+
+    //Parses a statement containing a color
+    fn color(&self) -> Result<Color, SceneError> {
+        let (r, g, b) = self.triple()?;
+        Ok(Color(r, g, b))
+    }
+
+    //Parses a transformation, returning None if the statement is not a transformation
+    fn transform(&self) -> Result<Option<Matrix4x4>, SceneError> {
+        match self.keyword() {
+            "translate" => {
+                let (x, y, z) = self.triple()?;
+                Ok(Some(Matrix4x4::translation(x, y, z)))
+            }
+            "scale" => {
+                let (x, y, z) = self.triple()?;
+                Ok(Some(Matrix4x4::scaling(x, y, z)))
+            }
+            "rotate" => {
+                self.expect(2)?;
+                let axis = match self.tokens[1].as_str() {
+                    "x" => Axis::X,
+                    "y" => Axis::Y,
+                    "z" => Axis::Z,
+                    other => return Err(SceneError::new(self.line, &format!("'{}' is not an axis", other))),
+                };
+                Ok(Some(Matrix4x4::rotation(axis, self.float(2)?)))
+            }
+            "shear" => {
+                self.expect(6)?;
+                Ok(Some(Matrix4x4::shearing(
+                    self.float(1)?,
+                    self.float(2)?,
+                    self.float(3)?,
+                    self.float(4)?,
+                    self.float(5)?,
+                    self.float(6)?,
+                )))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    //Parses the header of a keyframe block, which gives its time and optionally the curve leading into it
+    fn keyframe(&self) -> Result<(f32, Curve), SceneError> {
+        let curve = match self.tokens.len() {
+            2 => Curve::Linear,
+            3 => Curve::from_name(&self.tokens[2])
+                .ok_or_else(|| SceneError::new(self.line, &format!("unknown curve '{}'", self.tokens[2])))?,
+            _ => return Err(SceneError::new(self.line, "expected 'keyframe TIME' or 'keyframe TIME CURVE'")),
+        };
+        let time = self.float(1)?;
+        if !time.is_finite() {
+            return Err(SceneError::new(self.line, "keyframe time must be a finite number"));
+        }
+        Ok((time, curve))
+    }
+
+    //Creates an error for a keyword which is not valid in the current block
+    fn unknown(&self, block: &str) -> SceneError {
+        SceneError::new(self.line, &format!("unknown property '{}' in {}", self.keyword(), block))
+    }
+}
+
+//Sets a property of the camera from a statement in a camera block
+fn set_camera_property(camera: &mut CameraSettings, statement: &Statement) -> Result<(), SceneError> {
+    match statement.keyword() {
+        "width" => camera.width = statement.integer()?,
+        "height" => camera.height = statement.integer()?,
+        "fov" => camera.fov = statement.single_float()?,
+        "from" => camera.from = statement.point()?,
+        "to" => camera.to = statement.point()?,
+        "up" => camera.up = statement.vector()?,
+        "projection" => {
+            statement.expect(1)?;
+            camera.projection = Projection::from_name(&statement.tokens[1]).ok_or_else(|| {
+                SceneError::new(statement.line, &format!("unknown projection '{}'", statement.tokens[1]))
+            })?;
+        }
+        "aperture" => camera.aperture = statement.single_float()?,
+        "focal_distance" => camera.focal_distance = Some(statement.single_float()?),
+        "shutter" => {
+            statement.expect(2)?;
+            camera.shutter = (statement.float(1)?, statement.float(2)?);
+        }
+        _ => return Err(statement.unknown("camera")),
+    }
+    Ok(())
+}
+
+//Properties of a point, area, spot or directional light block
+struct LightProperties {
+    intensity: Color,
+    position: Vec4,
+    uvec: Vec4,
+    vvec: Vec4,
+    usteps: usize,
+    vsteps: usize,
+    direction: Vec4,
+    angle: f32,
+    falloff: f32,
+    angular_diameter: f32,
+    samples: usize,
+}
+
+impl Default for LightProperties {
+    fn default() -> LightProperties {
+        LightProperties {
+            intensity: Color(1.0, 1.0, 1.0),
+            position: Vec4(0.0, 0.0, 0.0, 1.0),
+            uvec: Vec4(1.0, 0.0, 0.0, 0.0),
+            vvec: Vec4(0.0, 1.0, 0.0, 0.0),
+            usteps: 1,
+            vsteps: 1,
+            direction: Vec4(0.0, -1.0, 0.0, 0.0),
+            angle: 30.0,
+            falloff: 5.0,
+            angular_diameter: 0.0,
+            samples: 16,
+        }
+    }
+}
+
+impl LightProperties {
+    //Sets a property from a statement in a light block of the given kind
+    fn set(&mut self, kind: &str, statement: &Statement) -> Result<(), SceneError> {
+        match (kind, statement.keyword()) {
+            (_, "intensity") => self.intensity = statement.color()?,
+            ("point", "position") | ("area", "corner") | ("spot", "position") => self.position = statement.point()?,
+            ("area", "uvec") => self.uvec = statement.vector()?,
+            ("area", "vvec") => self.vvec = statement.vector()?,
+            ("area", "usteps") => self.usteps = statement.integer()?,
+            ("area", "vsteps") => self.vsteps = statement.integer()?,
+            ("spot", "direction") | ("directional", "direction") => self.direction = statement.vector()?,
+            ("spot", "angle") => self.angle = statement.single_float()?,
+            ("spot", "falloff") => self.falloff = statement.single_float()?,
+            ("directional", "angular_diameter") => self.angular_diameter = statement.single_float()?,
+            ("directional", "samples") => self.samples = statement.integer()?,
+            _ => return Err(statement.unknown(&format!("{} light", kind))),
+        }
+        Ok(())
+    }
+}
+
+//Properties of each block which may be given in keyframe blocks
+const CAMERA_KEYFRAMES: [&str; 6] = ["fov", "from", "to", "up", "aperture", "focal_distance"];
+const LIGHT_KEYFRAMES: [&str; 9] = [
+    "intensity",
+    "position",
+    "corner",
+    "uvec",
+    "vvec",
+    "direction",
+    "angle",
+    "falloff",
+    "angular_diameter",
+];
+const MATERIAL_KEYFRAMES: [&str; 12] = [
+    "color",
+    "ambient",
+    "diffuse",
+    "specular",
+    "shininess",
+    "reflectivity",
+    "transparency",
+    "refractive_index",
+    "environment_lighting",
+    "emission",
+    "metallic",
+    "roughness",
+];
+
+//Keys of a property, holding the values which follow its keyword
+type PropertyKeys = Vec<Key<Vec<f32>>>;
+
+//Keys of the transforms of a moving object
+type TransformKeys = Vec<Key<Matrix4x4>>;
+
+//The keyframed properties of a camera, light or material block
+//Each property is a track of its values, which starts at time 0 from the value given outside of any keyframe
+struct PropertyTracks {
+    keyframed: &'static [&'static str],
+    given: HashMap<String, Vec<f32>>,
+    tracks: Vec<(Statement, PropertyKeys)>, //The first keyframed statement of each property and its keys
+}
+
+impl PropertyTracks {
+    //Creates an empty set of tracks for the properties of a block
+    fn new(keyframed: &'static [&'static str]) -> PropertyTracks {
+        PropertyTracks {
+            keyframed,
+            given: HashMap::new(),
+            tracks: vec![],
+        }
+    }
+
+    //Parses the values of a statement as floats
+    fn values(statement: &Statement) -> Result<Vec<f32>, SceneError> {
+        (1..statement.tokens.len()).map(|index| statement.float(index)).collect()
+    }
+
+    //Remembers a property given outside of a keyframe, which is where its track starts
+    fn given(&mut self, statement: &Statement) {
+        if self.keyframed.contains(&statement.keyword()) {
+            if let Ok(values) = PropertyTracks::values(statement) {
+                self.given.insert(statement.keyword().to_string(), values);
+            }
+        }
+    }
+
+    //Adds a property from a keyframe block to its track
+    fn add(&mut self, statement: &Statement, block: &str, time: f32, curve: Curve) -> Result<(), SceneError> {
+        if !self.keyframed.contains(&statement.keyword()) {
+            return Err(SceneError::new(
+                statement.line,
+                &format!("'{}' can not be keyframed in {}", statement.keyword(), block),
+            ));
+        }
+        let key = Key {
+            time,
+            value: PropertyTracks::values(statement)?,
+            curve,
+        };
+        match self.tracks.iter_mut().find(|(first, _)| first.keyword() == statement.keyword()) {
+            Some((first, keys)) => {
+                if keys[0].value.len() != key.value.len() {
+                    return Err(SceneError::new(
+                        statement.line,
+                        &format!("'{}' has {} value(s) in an earlier keyframe", statement.keyword(), keys[0].value.len()),
+                    ));
+                }
+                if keys.iter().any(|other| other.time == time) {
+                    return Err(SceneError::new(
+                        statement.line,
+                        &format!("'{}' is keyframed twice at line {} and time {}", statement.keyword(), first.line, time),
+                    ));
+                }
+                keys.push(key);
+            }
+            None => self.tracks.push((statement.clone(), vec![key])),
+        }
+        Ok(())
+    }
+
+    //Creates a statement for each keyframed property with its value at a time
+    fn at(&self, time: f32) -> Vec<Statement> {
+        let mut statements = vec![];
+        for (first, keys) in &self.tracks {
+            let mut keys = keys.clone();
+            if let Some(values) = self.given.get(first.keyword()) {
+                if !keys.iter().any(|key| key.time == 0.0) {
+                    keys.push(Key::new(0.0, values.clone()));
+                }
+            }
+            let values = Track::new(keys).at(time).unwrap();
+            let mut tokens = vec![first.keyword().to_string()];
+            tokens.extend(values.iter().map(|value| value.to_string()));
+            statements.push(Statement {
+                line: first.line,
+                tokens,
+            });
+        }
+        statements
+    }
+}
+
+//Reads statements from a scene file and builds the objects they describe
+struct SceneParser {
+    statements: Vec<Statement>,
+    position: usize,
+    base_dir: PathBuf,
+    materials: HashMap<String, Material>,
+    images: HashMap<PathBuf, Arc<Canvas>>,
+    time: f32, //Time at which keyframed properties are read
+}
+
+impl SceneParser {
+    //Splits the text into statements, dropping comments and blank lines
+    fn new(text: &str, base_dir: &Path, time: f32) -> SceneParser {
+        let mut statements = vec![];
+        for (index, line) in text.lines().enumerate() {
+            let content = line.split('#').next().unwrap_or("");
+            let tokens: Vec<String> = content.split_whitespace().map(String::from).collect();
+            if !tokens.is_empty() {
+                statements.push(Statement { line: index + 1, tokens });
+            }
+        }
+        SceneParser {
+            statements,
+            position: 0,
+            base_dir: base_dir.to_path_buf(),
+            materials: HashMap::new(),
+            images: HashMap::new(),
+            time,
+        }
+    }
+
+    //Parses a keyframe block of a camera, light or material, adding each property to its track
+    fn parse_property_keyframe(
+        &mut self,
+        header: &Statement,
+        block: &str,
+        tracks: &mut PropertyTracks,
+    ) -> Result<(), SceneError> {
+        let (time, curve) = header.keyframe()?;
+        loop {
+            let statement = self.next_in_block(header)?;
+            match statement.keyword() {
+                "end" => break,
+                _ => tracks.add(&statement, block, time, curve)?,
+            }
+        }
+        Ok(())
+    }
+
+    //Gets the next statement inside a block
+    fn next_in_block(&mut self, header: &Statement) -> Result<Statement, SceneError> {
+        if self.position >= self.statements.len() {
+            return Err(SceneError::new(
+                header.line,
+                &format!("'{}' block is missing 'end'", header.keyword()),
+            ));
+        }
+        self.position += 1;
+        Ok(self.statements[self.position - 1].clone())
+    }
+
+    //Parses every top level block in the file
+    fn parse(&mut self) -> Result<SceneFile, SceneError> {
+        let mut scene = Scene::new();
+        let mut camera = CameraSettings::default();
+        while self.position < self.statements.len() {
+            let statement = self.statements[self.position].clone();
+            self.position += 1;
+            match statement.keyword() {
+                "camera" => {
+                    statement.expect(0)?;
+                    camera = self.parse_camera(&statement)?;
+                }
+                "light" => scene.light_sources.push(self.parse_light(&statement)?),
+                "material" => self.parse_named_material(&statement)?,
+                keyword if OBJECT_KINDS.contains(&keyword) => scene.objects.push(self.parse_object(&statement)?),
+                "end" => return Err(SceneError::new(statement.line, "'end' without an open block")),
+                other => return Err(SceneError::new(statement.line, &format!("unknown statement '{}'", other))),
+            }
+        }
+        scene.add_object_lights();
+        scene.build_bvh();
+        Ok(SceneFile { scene, camera })
+    }
+
+    //Parses a camera block
+    fn parse_camera(&mut self, header: &Statement) -> Result<CameraSettings, SceneError> {
+        let mut camera = CameraSettings::default();
+        let mut tracks = PropertyTracks::new(&CAMERA_KEYFRAMES);
+        loop {
+            let statement = self.next_in_block(header)?;
+            match statement.keyword() {
+                "end" => break,
+                "keyframe" => self.parse_property_keyframe(&statement, "camera", &mut tracks)?,
+                _ => {
+                    set_camera_property(&mut camera, &statement)?;
+                    tracks.given(&statement);
+                }
+            }
+        }
+        for statement in tracks.at(self.time) {
+            set_camera_property(&mut camera, &statement)?;
+        }
+        if camera.width == 0 || camera.height == 0 {
+            return Err(SceneError::new(header.line, "camera width and height must be greater than 0"));
+        }
+        if camera.fov <= 0.0 || camera.fov >= 360.0 {
+            return Err(SceneError::new(header.line, "camera fov must be between 0 and 360 degrees"));
+        }
+        if !camera.fov_is_valid() {
+            return Err(SceneError::new(header.line, "camera fov of 180 degrees or more needs the fisheye projection"));
+        }
+        //The view transform divides by the length of both of these, so they must not be zero
+        let forward = &camera.to - &camera.from;
+        if Vec4::magnitude(&forward) == 0.0 {
+            return Err(SceneError::new(header.line, "camera from and to must be different points"));
+        }
+        let left = forward.normalize() * camera.up.normalize();
+        if Vec4::magnitude(&camera.up) == 0.0 || Vec4::magnitude(&left) < f32::EPSILON {
+            return Err(SceneError::new(header.line, "camera up must not be zero or parallel to the direction from from to to"));
+        }
+        if camera.shutter.1 < camera.shutter.0 {
+            return Err(SceneError::new(header.line, "camera shutter must close after it opens"));
+        }
+        if camera.aperture < 0.0 {
+            return Err(SceneError::new(header.line, "camera aperture can not be negative"));
+        }
+        if camera.focal_distance.is_some_and(|distance| distance <= 0.0) {
+            return Err(SceneError::new(header.line, "camera focal_distance must be greater than 0"));
+        }
+        //The shutter is given relative to the frame being rendered
+        camera.shutter = (camera.shutter.0 + self.time, camera.shutter.1 + self.time);
+        Ok(camera)
+    }
+
+    //Parses a point, area, spot or directional light block
+    fn parse_light(&mut self, header: &Statement) -> Result<Box<dyn Light>, SceneError> {
+        header.expect(1)?;
+        let kind = header.tokens[1].clone();
+        if !["point", "area", "spot", "directional"].contains(&kind.as_str()) {
+            return Err(SceneError::new(header.line, &format!("unknown light type '{}'", kind)));
+        }
+        let mut light = LightProperties::default();
+        let mut tracks = PropertyTracks::new(&LIGHT_KEYFRAMES);
+        let block = format!("{} light", kind);
+        loop {
+            let statement = self.next_in_block(header)?;
+            match statement.keyword() {
+                "end" => break,
+                "keyframe" => self.parse_property_keyframe(&statement, &block, &mut tracks)?,
+                _ => {
+                    light.set(&kind, &statement)?;
+                    tracks.given(&statement);
+                }
+            }
+        }
+        for statement in tracks.at(self.time) {
+            light.set(&kind, &statement)?;
+        }
+        let LightProperties {
+            intensity,
+            position,
+            uvec,
+            vvec,
+            usteps,
+            vsteps,
+            direction,
+            angle,
+            falloff,
+            angular_diameter,
+            samples,
+        } = light;
+        if (kind == "spot" || kind == "directional") && Vec4::magnitude(&direction) == 0.0 {
+            return Err(SceneError::new(header.line, &format!("{} light direction can not be 0 0 0", kind)));
+        }
+        match kind.as_str() {
+            "point" => Ok(Box::new(PointLight::new(intensity, position))),
+            "area" => {
+                if usteps == 0 || vsteps == 0 {
+                    return Err(SceneError::new(header.line, "area light steps must be greater than 0"));
+                }
+                Ok(Box::new(AreaLight::new(position, uvec, usteps as i32, vvec, vsteps as i32, intensity)))
+            }
+            "spot" => {
+                if angle <= 0.0 || angle > 180.0 || falloff < 0.0 {
+                    return Err(SceneError::new(
+                        header.line,
+                        "spot light angle must be between 0 and 180 degrees and falloff can not be negative",
+                    ));
+                }
+                Ok(Box::new(SpotLight::new(intensity, position, direction, angle, falloff)))
+            }
+            _ => {
+                if !(0.0..180.0).contains(&angular_diameter) || samples == 0 {
+                    return Err(SceneError::new(
+                        header.line,
+                        "directional light angular_diameter must be between 0 and 180 degrees and samples greater than 0",
+                    ));
+                }
+                Ok(Box::new(DirectionalLight::new(intensity, direction, angular_diameter, samples)))
+            }
+        }
+    }
+
+    //Parses a top level material block which can be referenced by name
+    fn parse_named_material(&mut self, header: &Statement) -> Result<(), SceneError> {
+        let base = match header.tokens.len() {
+            2 => Material::default(),
+            4 if header.tokens[2] == "extends" => self.find_material(header, &header.tokens[3])?,
+            _ => return Err(SceneError::new(header.line, "expected 'material NAME' or 'material NAME extends BASE'")),
+        };
+        let material = self.parse_material(header, base)?;
+        self.materials.insert(header.tokens[1].clone(), material);
+        Ok(())
+    }
+
+    //Looks up a previously defined material
+    fn find_material(&self, statement: &Statement, name: &str) -> Result<Material, SceneError> {
+        match self.materials.get(name) {
+            Some(material) => Ok(material.clone()),
+            None => Err(SceneError::new(statement.line, &format!("unknown material '{}'", name))),
+        }
+    }
+
+    //Parses the properties of a material block starting from a base material
+    fn parse_material(&mut self, header: &Statement, base: Material) -> Result<Material, SceneError> {
+        let mut material = base;
+        let mut tracks = PropertyTracks::new(&MATERIAL_KEYFRAMES);
+        loop {
+            let statement = self.next_in_block(header)?;
+            match statement.keyword() {
+                "end" => break,
+                "keyframe" => self.parse_property_keyframe(&statement, "material", &mut tracks)?,
+                _ => {
+                    self.set_material_property(&mut material, &statement)?;
+                    tracks.given(&statement);
+                }
+            }
+        }
+        for statement in tracks.at(self.time) {
+            self.set_material_property(&mut material, &statement)?;
+        }
+        Ok(material)
+    }
+
+    //Sets a property of a material from a statement in a material block
+    fn set_material_property(&mut self, material: &mut Material, statement: &Statement) -> Result<(), SceneError> {
+        match statement.keyword() {
+            "color" => material.color = statement.color()?,
+            "ambient" => material.ambient = statement.single_float()?,
+            "diffuse" => material.diffuse = statement.single_float()?,
+            "specular" => material.specular = statement.single_float()?,
+            "shininess" => material.shininess = statement.single_float()?,
+            "reflectivity" => material.reflectivity = statement.single_float()?,
+            "transparency" => material.transparency = statement.single_float()?,
+            "refractive_index" => material.refractive_index = statement.single_float()?,
+            "environment_lighting" => material.environment_lighting = statement.single_float()?,
+            "emission" => material.emission = statement.color()?,
+            "model" => {
+                statement.expect(1)?;
+                material.model = ReflectionModel::from_name(&statement.tokens[1]).ok_or_else(|| {
+                    SceneError::new(statement.line, &format!("unknown reflection model '{}'", statement.tokens[1]))
+                })?;
+            }
+            "metallic" => material.metallic = statement.single_float()?,
+            "roughness" => material.roughness = statement.single_float()?,
+            "glossy_samples" => material.glossy_samples = statement.integer()?,
+            "casts_shadows" => material.casts_shadows = statement.boolean()?,
+            "pattern" => material.pattern = Some(self.parse_pattern(statement)?),
+            _ => return Err(statement.unknown("material")),
+        }
+        Ok(())
+    }
+
+    //Loads an image as a texture, sharing it between every pattern which uses the same file
+    fn load_image(&mut self, statement: &Statement, name: &str) -> Result<Arc<Canvas>, SceneError> {
+        let path = self.base_dir.join(name);
+        if let Some(image) = self.images.get(&path) {
+            return Ok(image.clone());
+        }
+        let image = match Canvas::load_texture(&path.to_string_lossy()) {
+            Ok(image) => Arc::new(image),
+            Err(error) => {
+                return Err(SceneError::new(statement.line, &format!("Failed to load {}: {}", path.display(), error)))
+            }
+        };
+        self.images.insert(path, image.clone());
+        Ok(image)
+    }
+
+    //Parses an image pattern block, whose header names the image file
+    fn parse_image_pattern(&mut self, header: &Statement) -> Result<Box<dyn Pattern>, SceneError> {
+        header.expect(2)?;
+        let image = self.load_image(header, &header.tokens[2].clone())?;
+        let mut mapping = UvMapping::Planar;
+        let mut transform = Matrix4x4::identity();
+        loop {
+            let statement = self.next_in_block(header)?;
+            if let Some(matrix) = statement.transform()? {
+                transform = matrix * transform;
+                continue;
+            }
+            match statement.keyword() {
+                "end" => break,
+                "mapping" => {
+                    statement.expect(1)?;
+                    mapping = match statement.tokens[1].as_str() {
+                        "spherical" => UvMapping::Spherical,
+                        "planar" => UvMapping::Planar,
+                        "cylindrical" => UvMapping::Cylindrical,
+                        "cube" => UvMapping::Cube,
+                        "uv" => UvMapping::Surface,
+                        other => return Err(SceneError::new(statement.line, &format!("unknown mapping '{}'", other))),
+                    };
+                }
+                _ => return Err(statement.unknown("pattern")),
+            }
+        }
+        Ok(Box::new(ImagePattern::new(image, mapping, transform)))
+    }
+
+    //Parses a pattern block
+    fn parse_pattern(&mut self, header: &Statement) -> Result<Box<dyn Pattern>, SceneError> {
+        if header.tokens.get(1).map(String::as_str) == Some("image") {
+            return self.parse_image_pattern(header);
+        }
+        header.expect(1)?;
+        let kind = header.tokens[1].clone();
+        let mut colors = vec![];
+        let mut transform = Matrix4x4::identity();
+        loop {
+            let statement = self.next_in_block(header)?;
+            if let Some(matrix) = statement.transform()? {
+                transform = matrix * transform;
+                continue;
+            }
+            match statement.keyword() {
+                "end" => break,
+                "color" => colors.push(statement.color()?),
+                _ => return Err(statement.unknown("pattern")),
+            }
+        }
+        if kind == "test" {
+            return Ok(Box::new(TestPattern::new(transform)));
+        }
+        if colors.len() != 2 {
+            return Err(SceneError::new(header.line, &format!("{} pattern needs exactly 2 colors", kind)));
+        }
+        let second = colors.pop().unwrap();
+        let first = colors.pop().unwrap();
+        match kind.as_str() {
+            "stripes" => Ok(Box::new(StripePattern::new(first, second, transform))),
+            "gradient" => Ok(Box::new(GradientPattern::new(first, second, transform))),
+            "rings" => Ok(Box::new(RingPattern::new(first, second, transform))),
+            "checkers" => Ok(Box::new(CheckerboardPattern::new(first, second, transform))),
+            other => Err(SceneError::new(header.line, &format!("unknown pattern '{}'", other))),
+        }
+    }
+
+    //Parses an object block, including any objects nested inside it
+    //Objects with keyframes are wrapped so they move over time
+    fn parse_object(&mut self, header: &Statement) -> Result<Box<dyn Object>, SceneError> {
+        let (object, mut keyframes) = self.parse_static_object(header)?;
+        if keyframes.is_empty() {
+            return Ok(object);
+        }
+        if !keyframes.iter().any(|key| key.time == 0.0) {
+            keyframes.push(Key::new(0.0, Matrix4x4::identity()));
+        }
+        Ok(Box::new(Moving::new(object, MotionPath::from_track(Track::new(keyframes)))))
+    }
+
+    //Parses a keyframe block, which lists the transforms applied to an object at a time
+    fn parse_keyframe(&mut self, header: &Statement) -> Result<Key<Matrix4x4>, SceneError> {
+        let (time, curve) = header.keyframe()?;
+        let mut transform = Matrix4x4::identity();
+        loop {
+            let statement = self.next_in_block(header)?;
+            if let Some(matrix) = statement.transform()? {
+                transform = matrix * transform;
+                continue;
+            }
+            match statement.keyword() {
+                "end" => break,
+                _ => return Err(statement.unknown("keyframe")),
+            }
+        }
+        Ok(Key {
+            time,
+            value: transform,
+            curve,
+        })
+    }
+
+    //Parses an object block without its motion, returning the object and its keyframes
+    fn parse_static_object(
+        &mut self,
+        header: &Statement,
+    ) -> Result<(Box<dyn Object>, TransformKeys), SceneError> {
+        let kind = header.keyword().to_string();
+        match kind.as_str() {
+            "csg" | "obj" => header.expect(1)?,
+            _ => header.expect(0)?,
+        }
+        let mut transform = Matrix4x4::identity();
+        let mut material = Material::default();
+        let mut minimum = -f32::INFINITY;
+        let mut maximum = f32::INFINITY;
+        let mut capped = false;
+        let mut points = vec![None, None, None];
+        let mut children: Vec<Box<dyn Object>> = vec![];
+        let mut keyframes = vec![];
+        loop {
+            let statement = self.next_in_block(header)?;
+            if let Some(matrix) = statement.transform()? {
+                transform = matrix * transform;
+                continue;
+            }
+            match (kind.as_str(), statement.keyword()) {
+                (_, "end") => break,
+                (_, "keyframe") => keyframes.push(self.parse_keyframe(&statement)?),
+                (_, "material") => {
+                    material = match statement.tokens.len() {
+                        1 => self.parse_material(&statement, Material::default())?,
+                        2 => self.find_material(&statement, &statement.tokens[1])?,
+                        _ => return Err(SceneError::new(statement.line, "expected 'material' or 'material NAME'")),
+                    }
+                }
+                ("cylinder", "minimum") | ("cone", "minimum") => minimum = statement.single_float()?,
+                ("cylinder", "maximum") | ("cone", "maximum") => maximum = statement.single_float()?,
+                ("cylinder", "capped") | ("cone", "capped") => capped = statement.boolean()?,
+                ("triangle", "p1") => points[0] = Some(statement.point()?),
+                ("triangle", "p2") => points[1] = Some(statement.point()?),
+                ("triangle", "p3") => points[2] = Some(statement.point()?),
+                ("group", keyword) | ("csg", keyword) if OBJECT_KINDS.contains(&keyword) => {
+                    children.push(self.parse_object(&statement)?);
+                }
+                _ => return Err(statement.unknown(&kind)),
+            }
+        }
+
+        let object: Box<dyn Object> = match kind.as_str() {
+            "sphere" => Box::new(Sphere::new(transform, material)),
+            "plane" => Box::new(Plane::new(transform, material)),
+            "cube" => Box::new(Cube::new(transform, material)),
+            "cylinder" => Box::new(Cylinder::new(transform, material, minimum, maximum, capped)),
+            "cone" => Box::new(Cone::new(transform, material, minimum, maximum, capped)),
+            "triangle" => {
+                //Triangles have no transform of their own so it is applied to their points
+                let mut corners = vec![];
+                for point in points {
+                    match point {
+                        Some(point) => corners.push(&transform * point),
+                        None => return Err(SceneError::new(header.line, "triangle needs p1, p2 and p3")),
+                    }
+                }
+                let p3 = corners.pop().unwrap();
+                let p2 = corners.pop().unwrap();
+                let p1 = corners.pop().unwrap();
+                Box::new(Triangle::new(p1, p2, p3, material))
+            }
+            "group" => {
+                let mut group = Group::new(transform, material);
+                for child in children {
+                    group.add_object(child);
+                }
+                group.build_bvh();
+                Box::new(group)
+            }
+            "csg" => {
+                let operation = match header.tokens[1].as_str() {
+                    "union" => Operation::Union,
+                    "intersect" => Operation::Intersect,
+                    "difference" => Operation::Difference,
+                    other => return Err(SceneError::new(header.line, &format!("unknown csg operation '{}'", other))),
+                };
+                if children.len() != 2 {
+                    return Err(SceneError::new(header.line, &format!("csg needs exactly 2 objects but got {}", children.len())));
+                }
+                let right = children.pop().unwrap();
+                let left = children.pop().unwrap();
+                Box::new(CSG::new(transform, material, left, right, operation))
+            }
+            _ => {
+                let path = self.base_dir.join(&header.tokens[1]);
+                let file = match File::open(&path) {
+                    Ok(file) => file,
+                    Err(error) => {
+                        return Err(SceneError::new(header.line, &format!("Failed to open {}: {}", path.display(), error)))
+                    }
+                };
+                let parser = Parser::parse_obj(file)
+                    .map_err(|error| SceneError::new(header.line, &format!("Failed to read {}: {}", path.display(), error)))?;
+                let mut group = Group::new(transform, material);
+                parser.convert_to_group(&mut group);
+                Box::new(group)
+            }
+        };
+        Ok((object, keyframes))
+    }
+}
@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod tests {
+    use rust_ray_tracer::world::animation::*;
+
+    #[test]
+    //Tests the shape of each curve
+    fn curves() {
+        assert_eq!(Curve::Linear.apply(0.25), 0.25);
+        assert_eq!(Curve::Step.apply(0.99), 0.0);
+        assert_eq!(Curve::Step.apply(1.0), 1.0);
+        assert_eq!(Curve::EaseIn.apply(0.5), 0.25);
+        assert_eq!(Curve::EaseOut.apply(0.5), 0.75);
+        assert_eq!(Curve::EaseInOut.apply(0.5), 0.5);
+        assert_eq!(Curve::EaseInOut.apply(0.25), 0.15625);
+        assert_eq!(Curve::from_name("ease_in_out"), Some(Curve::EaseInOut));
+        assert_eq!(Curve::from_name("bounce"), None);
+    }
+
+    #[test]
+    //Tests reading a track between and outside of its keys
+    fn track_values() {
+        let track = Track::new(vec![
+            Key {
+                time: 4.0,
+                value: vec![2.0, 8.0],
+                curve: Curve::EaseIn,
+            },
+            Key::new(0.0, vec![0.0, 0.0]),
+        ]);
+        assert_eq!(track.keys[0].time, 0.0);
+        assert_eq!(track.at(-1.0), Some(vec![0.0, 0.0]));
+        assert_eq!(track.at(2.0), Some(vec![0.5, 2.0]));
+        assert_eq!(track.at(5.0), Some(vec![2.0, 8.0]));
+        assert_eq!(Track::<f32>::new(vec![]).at(1.0), None);
+    }
+
+    #[test]
+    //Tests numbering the output files of an animation
+    fn frame_paths() {
+        assert_eq!(frame_path("render.png", 7), "render_0007.png");
+        assert_eq!(frame_path("out/frame_###.ppm", 12), "out/frame_012.ppm");
+        assert_eq!(frame_path("out.dir/render", 3), "out.dir/render_0003");
+        assert_eq!(frame_path("#.png", 123), "123.png");
+    }
+}
@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod tests {
+    use rust_ray_tracer::core::color::*;
+    use rust_ray_tracer::core::comp::Comp;
+    use rust_ray_tracer::core::matrix::Matrix4x4;
+    use rust_ray_tracer::core::vector::Vec4;
+    use rust_ray_tracer::materials::bsdf::*;
+    use rust_ray_tracer::materials::material::*;
+    use rust_ray_tracer::misc::random::reseed;
+    use rust_ray_tracer::objects::object::*;
+    use rust_ray_tracer::objects::sphere::Sphere;
+    use rust_ray_tracer::ray_tracing::ray::Ray;
+    use std::f32::consts::PI;
+
+    //Averages the weights of many samples, which is the fraction of light the BSDF scatters towards the eye
+    fn average_weight(bsdf: &dyn Bsdf, n_vec: &Vec4, e_vec: &Vec4, samples: usize) -> Color {
+        let mut total = BLACK;
+        for _ in 0..samples {
+            if let Some(sample) = bsdf.sample(n_vec, e_vec) {
+                total = total + sample.weight;
+            }
+        }
+        total * (1.0 / samples as f32)
+    }
+
+    #[test]
+    //Tests that hemisphere samples face along the normal and favour directions close to it
+    fn cosine_weighted_hemisphere() {
+        let normal = Vec4::new(0.0, 0.6, 0.8, 0.0);
+        let mut total_cosine = 0.0;
+        let steps = 40;
+        for i in 0..steps {
+            for j in 0..steps {
+                let u = (i as f32 + 0.5) / steps as f32;
+                let v = (j as f32 + 0.5) / steps as f32;
+                let direction = cosine_sample_hemisphere(&normal, u, v);
+                assert!((Vec4::magnitude(&direction) - 1.0).abs() < 0.001);
+                let cosine = Vec4::dot(&direction, &normal);
+                assert!(cosine >= 0.0);
+                total_cosine += cosine;
+            }
+        }
+        //The average cosine of a cosine weighted hemisphere is 2/3
+        assert!((total_cosine / (steps * steps) as f32 - 2.0 / 3.0).abs() < 0.01);
+    }
+
+    #[test]
+    //Tests evaluating and sampling a matte surface
+    fn lambertian() {
+        reseed(1);
+        let bsdf = Lambertian { albedo: Color(0.5, 0.25, 1.0) };
+        let n_vec = Vec4::new(0.0, 1.0, 0.0, 0.0);
+        let e_vec = Vec4::new(0.0, 0.6, 0.8, 0.0);
+        let light_vec = Vec4::new(0.8, 0.6, 0.0, 0.0);
+        assert_eq!(bsdf.eval(&n_vec, &e_vec, &light_vec), Color(0.5, 0.25, 1.0) * (1.0 / PI));
+        assert_eq!(bsdf.eval(&n_vec, &e_vec, &light_vec.negate()), BLACK);
+        assert_eq!(bsdf.pdf(&n_vec, &e_vec, &light_vec), 0.6 / PI);
+        let sample = bsdf.sample(&n_vec, &e_vec).unwrap();
+        assert_eq!(sample.weight, Color(0.5, 0.25, 1.0));
+        assert!(!sample.specular);
+        assert!((sample.pdf - bsdf.pdf(&n_vec, &e_vec, &sample.direction)).abs() < 0.0001);
+    }
+
+    #[test]
+    //Tests that mirrors and glass scatter light along single directions
+    fn specular_bsdfs() {
+        reseed(2);
+        let n_vec = Vec4::new(0.0, 1.0, 0.0, 0.0);
+        let e_vec = Vec4::new(0.0, 0.6, 0.8, 0.0);
+        let mirror = Mirror { color: WHITE };
+        let sample = mirror.sample(&n_vec, &e_vec).unwrap();
+        assert_eq!(sample.direction, Vec4::new(0.0, 0.6, -0.8, 0.0));
+        assert!(sample.specular);
+        assert_eq!(mirror.eval(&n_vec, &e_vec, &sample.direction), BLACK);
+
+        //Light leaving glass at a steep angle is totally internally reflected
+        let glass = Dielectric { n1: 1.5, n2: 1.0, roughness: 0.0, reflection: 1.0, transmission: 1.0 };
+        let steep = Vec4::new(0.0, 0.3, (1.0f32 - 0.09).sqrt(), 0.0);
+        for _ in 0..10 {
+            let sample = glass.sample(&n_vec, &steep).unwrap();
+            assert!(Vec4::dot(&sample.direction, &n_vec) > 0.0);
+        }
+        //Light entering glass head on is mostly refracted
+        let glass = Dielectric { n1: 1.0, n2: 1.5, roughness: 0.0, reflection: 1.0, transmission: 1.0 };
+        let refracted = (0..1000).filter(|_| Vec4::dot(&glass.sample(&n_vec, &n_vec).unwrap().direction, &n_vec) < 0.0).count();
+        assert!(refracted > 930 && refracted < 985);
+
+        //Whitted shading traces both directions of smooth glass, splitting the light by the reflectance
+        let split = glass.specular(&n_vec, &n_vec, 8);
+        assert_eq!(split.len(), 2);
+        assert!((split[0].weight.0 - 0.04).abs() < 0.0001 && (split[1].weight.0 - 0.96).abs() < 0.0001);
+        assert_eq!(split[1].direction.round(), n_vec.negate());
+        //Glass which does not reflect refracts all of its light, as with the Phong model
+        let clear = Dielectric { reflection: 0.0, ..glass };
+        assert_eq!(clear.specular(&n_vec, &n_vec, 8), vec![BsdfSample {
+            direction: split[1].direction.clone(),
+            weight: WHITE,
+            pdf: 0.0,
+            specular: true,
+        }]);
+        assert_eq!(mirror.specular(&n_vec, &e_vec, 8), vec![sample]);
+        assert!(Lambertian { albedo: WHITE }.specular(&n_vec, &e_vec, 8).is_empty());
+    }
+
+    #[test]
+    //Tests the reflectance of a boundary between refraction indices
+    fn dielectric_reflectance() {
+        assert!((reflectance(1.0, 1.0, 1.5) - 0.04).abs() < 0.0001);
+        assert_eq!(reflectance(0.3, 1.5, 1.0), 1.0);
+        assert!(reflectance(0.1, 1.0, 1.5) > 0.5);
+        let refracted = refract(&Vec4::new(0.0, 1.0, 0.0, 0.0), &Vec4::new(0.0, 1.0, 0.0, 0.0), 1.0 / 1.5).unwrap();
+        assert_eq!(refracted.round(), Vec4::new(0.0, -1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    //Tests that rough metal and plastic scatter close to all of the light of a white surface, and agree with their pdfs
+    fn microfacet_bsdfs() {
+        reseed(3);
+        let n_vec = Vec4::new(0.0, 0.0, 1.0, 0.0);
+        let e_vec = Vec4::new(0.0, 0.6, 0.8, 0.0);
+        let metal = RoughConductor { color: WHITE, roughness: 0.3 };
+        let plastic = Plastic { color: WHITE, roughness: 0.3 };
+        let bsdfs: [&dyn Bsdf; 2] = [&metal, &plastic];
+        for bsdf in bsdfs {
+            let albedo = average_weight(bsdf, &n_vec, &e_vec, 20000);
+            assert!(albedo.0 > 0.85 && albedo.0 < 1.05);
+            let sample = bsdf.sample(&n_vec, &e_vec).unwrap();
+            let expected = bsdf.eval(&n_vec, &e_vec, &sample.direction)
+                * (Vec4::dot(&sample.direction, &n_vec) / bsdf.pdf(&n_vec, &e_vec, &sample.direction));
+            assert_eq!(sample.weight.round(), expected.round());
+        }
+        //Whitted shading averages the given number of random reflections of rough surfaces
+        let reflections = metal.specular(&n_vec, &e_vec, 4000);
+        assert_eq!(reflections.len(), 4000);
+        let albedo = reflections.iter().fold(BLACK, |total, sample| total + sample.weight.clone());
+        assert!(albedo.0 > 0.85 && albedo.0 < 1.05);
+        let coat = plastic.specular(&n_vec, &e_vec, 4000).iter().fold(BLACK, |total, sample| total + sample.weight.clone());
+        assert!(coat.0 > 0.02 && coat.0 < 0.2);
+        let smooth = RoughConductor { color: WHITE, roughness: 0.0 }.specular(&n_vec, &e_vec, 4000);
+        assert_eq!(smooth.len(), 1);
+        assert!(smooth[0].specular);
+
+        //Metal tints its reflections while the coat of plastic does not
+        let gold = RoughConductor { color: Color(1.0, 0.5, 0.0), roughness: 0.3 };
+        let highlight = gold.eval(&n_vec, &n_vec, &n_vec);
+        assert_eq!(highlight.2, 0.0);
+        let red = Plastic { color: Color(1.0, 0.0, 0.0), roughness: 0.3 };
+        assert!(red.eval(&n_vec, &n_vec, &n_vec).2 > 0.0);
+    }
+
+    #[test]
+    //Tests the BSDFs created for Phong and microfacet materials
+    fn comp_bsdfs() {
+        reseed(4);
+        let mut material = Material::default();
+        material.reflectivity = 0.5;
+        let sphere = Sphere::new(Matrix4x4::identity(), material);
+        let ray = Ray::new((0.0, 0.0, -5.0), (0.0, 0.0, 1.0));
+        let intersections = sphere.intersect(&ray).unwrap();
+        let comps = Comp::compute_vars(intersections[0].clone(), &ray, &intersections);
+        let bsdf = comps.bsdf();
+        //The matte part is the Phong diffuse, and the mirror reflects light straight back
+        let light_vec = Vec4::new(0.0, 0.0, -1.0, 0.0);
+        assert_eq!(bsdf.eval(&comps.n_vec, &comps.e_vec, &light_vec), Color(0.9, 0.9, 0.9) * (1.0 / PI));
+        let mirrored = (0..300).filter_map(|_| bsdf.sample(&comps.n_vec, &comps.e_vec)).filter(|sample| sample.specular);
+        for sample in mirrored {
+            assert_eq!(sample.direction, light_vec);
+            assert_eq!(sample.weight.round(), Color(1.5, 1.5, 1.5));
+        }
+
+        let mut material = Material::default();
+        material.model = ReflectionModel::Microfacet;
+        material.metallic = 1.0;
+        material.roughness = 0.5;
+        let sphere = Sphere::new(Matrix4x4::identity(), material);
+        let intersections = sphere.intersect(&ray).unwrap();
+        let comps = Comp::compute_vars(intersections[0].clone(), &ray, &intersections);
+        let metal = RoughConductor { color: WHITE, roughness: 0.5 };
+        assert_eq!(
+            comps.bsdf().eval(&comps.n_vec, &comps.e_vec, &light_vec),
+            metal.eval(&comps.n_vec, &comps.e_vec, &light_vec)
+        );
+    }
+}
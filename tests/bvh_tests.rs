@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+    use rust_ray_tracer::core::bounds::BoundingBox;
+    use rust_ray_tracer::core::canvas::Canvas;
+    use rust_ray_tracer::core::matrix::Matrix4x4;
+    use rust_ray_tracer::core::vector::Vec4;
+    use rust_ray_tracer::materials::material::Material;
+    use rust_ray_tracer::objects::cone::Cone;
+    use rust_ray_tracer::objects::cube::Cube;
+    use rust_ray_tracer::objects::group::Group;
+    use rust_ray_tracer::objects::object::*;
+    use rust_ray_tracer::objects::plane::Plane;
+    use rust_ray_tracer::objects::sphere::Sphere;
+    use rust_ray_tracer::objects::triangle::Triangle;
+    use rust_ray_tracer::ray_tracing::ray::Ray;
+    use rust_ray_tracer::world::camera::Camera;
+    use rust_ray_tracer::world::scene::Scene;
+
+    #[test]
+    //Tests the bounds of a transformed sphere in its parent space
+    fn transformed_sphere_bounds() {
+        let sphere = Sphere::new(Matrix4x4::translation(1.0, -3.0, 5.0) * Matrix4x4::scaling(0.5, 2.0, 4.0), Material::default());
+        let bounds = sphere.parent_space_bounds();
+        assert_eq!(bounds.min, Vec4(0.5, -5.0, 1.0, 1.0));
+        assert_eq!(bounds.max, Vec4(1.5, -1.0, 9.0, 1.0));
+    }
+
+    #[test]
+    //Tests the bounds of a triangle and a cone
+    fn primitive_bounds() {
+        let triangle = Triangle::new(Vec4(-3.0, 7.0, 2.0, 1.0), Vec4(6.0, 2.0, -4.0, 1.0), Vec4(2.0, -1.0, -1.0, 1.0), Material::default());
+        assert_eq!(triangle.bounds(), BoundingBox::new(Vec4(-3.0, -1.0, -4.0, 1.0), Vec4(6.0, 7.0, 2.0, 1.0)));
+        let cone = Cone::new(Matrix4x4::identity(), Material::default(), -5.0, 3.0, true);
+        assert_eq!(cone.bounds(), BoundingBox::new(Vec4(-5.0, -5.0, -5.0, 1.0), Vec4(5.0, 3.0, 5.0, 1.0)));
+    }
+
+    #[test]
+    //Tests that a plane has infinite bounds
+    fn plane_bounds() {
+        let plane = Plane::new(Matrix4x4::translation(0.0, 2.0, 0.0), Material::default());
+        assert!(!plane.parent_space_bounds().is_finite());
+    }
+
+    #[test]
+    //Tests the bounds of a group containing transformed children
+    fn group_bounds() {
+        let mut group = Group::new(Matrix4x4::scaling(2.0, 2.0, 2.0), Material::default());
+        Sphere::new(Matrix4x4::translation(2.0, 5.0, -3.0), Material::default()).add_to_group(&mut group);
+        Cube::new(Matrix4x4::translation(-4.0, -1.0, 4.0), Material::default()).add_to_group(&mut group);
+        assert_eq!(group.bounds(), BoundingBox::new(Vec4(-5.0, -2.0, -4.0, 1.0), Vec4(3.0, 6.0, 5.0, 1.0)));
+        assert_eq!(group.parent_space_bounds(), BoundingBox::new(Vec4(-10.0, -4.0, -8.0, 1.0), Vec4(6.0, 12.0, 10.0, 1.0)));
+    }
+
+    #[test]
+    //Tests intersecting rays with a bounding box
+    fn ray_box_intersection() {
+        let bounds = BoundingBox::new(Vec4(-1.0, -1.0, -1.0, 1.0), Vec4(1.0, 1.0, 1.0, 1.0));
+        assert!(bounds.intersects(&Ray::new((5.0, 0.5, 0.0), (-1.0, 0.0, 0.0))));
+        assert!(bounds.intersects(&Ray::new((0.0, 0.0, 0.0), (0.0, 0.0, 1.0))));
+        assert!(bounds.intersects(&Ray::new((0.0, 0.0, 5.0), (0.0, 0.0, 1.0))));
+        assert!(!bounds.intersects(&Ray::new((-2.0, 0.0, 0.0), (2.0, 4.0, 6.0))));
+        assert!(!bounds.intersects(&Ray::new((2.0, 2.0, 0.0), (0.0, 0.0, 1.0))));
+        assert!(!BoundingBox::empty().intersects(&Ray::new((0.0, 0.0, 0.0), (0.0, 0.0, 1.0))));
+    }
+
+    #[test]
+    //Tests that a group with a hierarchy finds the same intersections as one without
+    fn group_bvh_intersections() {
+        let mut group = Group::default();
+        for x in 0..6 {
+            for y in 0..6 {
+                let transform = Matrix4x4::translation(x as f32 * 3.0, y as f32 * 3.0, 0.0);
+                Sphere::new(transform, Material::default()).add_to_group(&mut group);
+            }
+        }
+        let linear = group.clone();
+        group.build_bvh();
+        for ray in &[
+            Ray::new((6.0, 6.0, -5.0), (0.0, 0.0, 1.0)),
+            Ray::new((-5.0, 0.2, 0.0), (1.0, 0.0, 0.0)),
+            Ray::new((1.5, 1.5, -5.0), (0.0, 0.0, 1.0)),
+        ] {
+            assert_eq!(group.intersect(ray), linear.intersect(ray));
+        }
+        assert_eq!(group, linear);
+    }
+
+    #[test]
+    //Tests that rendering a scene with a hierarchy matches rendering it without one
+    fn scene_bvh_render() {
+        let mut scene = Scene::default();
+        scene.objects.push(Box::new(Plane::new(Matrix4x4::translation(0.0, -1.0, 0.0), Material::default())));
+        scene.objects.push(Box::new(Cube::new(Matrix4x4::translation(2.0, 0.0, 1.0), Material::default())));
+        let mut camera = Camera::new(20, 20, 90.0);
+        camera.transform(Matrix4x4::view_transform(Vec4::new(0.0, 1.0, -5.0, 1.0), Vec4::new(0.0, 0.0, 0.0, 1.0), Vec4::new(0.0, 1.0, 0.0, 0.0)));
+
+        let mut linear_canvas = Canvas::new(20, 20);
+        Camera::render(&camera, &scene, &mut linear_canvas);
+        scene.build_bvh();
+        let mut bvh_canvas = Canvas::new(20, 20);
+        Camera::render(&camera, &scene, &mut bvh_canvas);
+        assert_eq!(linear_canvas.contents, bvh_canvas.contents);
+    }
+}
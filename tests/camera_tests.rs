@@ -1,13 +1,20 @@
 #[cfg(test)]
 
 mod tests {
-    use rust_ray_tracer::world::camera::Camera;
+    use rust_ray_tracer::world::camera::*;
+    use rust_ray_tracer::world::integrator::Flat;
+    use rust_ray_tracer::world::sampler::*;
+    use rust_ray_tracer::world::scene_file::SceneFile;
     use rust_ray_tracer::core::vector::Vec4;
     use rust_ray_tracer::core::matrix::Matrix4x4;
     use rust_ray_tracer::misc::axis::Axis;
     use rust_ray_tracer::world::scene::Scene;
     use rust_ray_tracer::core::canvas::Canvas;
     use rust_ray_tracer::core::color::Color;
+    use rust_ray_tracer::objects::moving::*;
+    use rust_ray_tracer::objects::sphere::Sphere;
+    use std::f32::consts::FRAC_1_SQRT_2;
+    use std::sync::atomic::AtomicBool;
 
     //Tests the pixel size of a new camera
     #[test]
@@ -57,4 +64,409 @@ mod tests {
         Camera::render(&camera, &scene, &mut canvas);
         assert_eq!(canvas.get(5, 5).unwrap().round(), Color::new(0.38072, 0.47583, 0.2855).round());
     }
-}
\ No newline at end of file
+
+    //Creates the camera used by the parallel rendering tests
+    fn parallel_test_camera() -> Camera {
+        let mut camera = Camera::new(37, 21, 90.0);
+        let start_pos = Vec4::new(0.0, 0.0, -5.0, 1.0);
+        let end_pos = Vec4::new(0.0, 0.0, 0.0, 1.0);
+        let up_vec = Vec4::new(0.0, 1.0, 0.0, 0.0);
+        camera.transform(Matrix4x4::view_transform(start_pos, end_pos, up_vec));
+        camera
+    }
+
+    //Tests that tiles cover every pixel exactly once
+    #[test]
+    fn tiles_cover_canvas() {
+        let camera = parallel_test_camera();
+        let tiles = Camera::tiles(&camera, 16);
+        assert_eq!(tiles.len(), 6);
+        assert_eq!(tiles[5], Tile { x: 32, y: 16, width: 5, height: 5 });
+        let mut pixels: Vec<(i32, i32)> = tiles.iter().flat_map(|tile| tile.pixels()).collect();
+        pixels.sort();
+        pixels.dedup();
+        assert_eq!(pixels.len(), 37 * 21);
+    }
+
+    //Tests that a scene can be shared between threads
+    #[test]
+    fn scene_is_thread_safe() {
+        fn assert_thread_safe<T: Send + Sync>() {}
+        assert_thread_safe::<Scene>();
+        assert_thread_safe::<Camera>();
+    }
+
+    //Tests that rendering in parallel matches rendering on a single thread
+    #[test]
+    fn parallel_render_matches_serial() {
+        let scene = Scene::default();
+        let camera = parallel_test_camera();
+        let mut serial = Canvas::new(37, 21);
+        Camera::render(&camera, &scene, &mut serial);
+        let mut parallel = Canvas::new(37, 21);
+        Camera::render_parallel(&camera, &scene, &mut parallel, 4);
+        assert_eq!(serial.contents, parallel.contents);
+    }
+
+    //Tests that supersampled rendering in parallel matches rendering on a single thread
+    #[test]
+    fn parallel_supersampled_render_matches_serial() {
+        let scene = Scene::default();
+        let camera = parallel_test_camera();
+        let mut serial = Canvas::new(37, 21);
+        Camera::render_supersampled(&camera, &scene, &mut serial);
+        let mut parallel = Canvas::new(37, 21);
+        Camera::render_supersampled_parallel(&camera, &scene, &mut parallel, 3);
+        assert_eq!(serial.contents, parallel.contents);
+    }
+
+    //Tests that a one ray grid matches a single ray through the pixel center
+    #[test]
+    fn grid_supersampling() {
+        let scene = Scene::default();
+        let camera = parallel_test_camera();
+        assert_eq!(Camera::pixel_color_grid(&camera, &scene, 18, 10, 1), Camera::pixel_color(&camera, &scene, 18, 10));
+        assert_eq!(Camera::pixel_color_grid(&camera, &scene, 0, 0, 3), Some(Color::new(0.0, 0.0, 0.0)));
+    }
+
+    //Tests that stochastic supersampling is reproducible for a seed, even with area lights and different thread counts
+    #[test]
+    fn seeded_sampling_is_reproducible() {
+        let mut file = SceneFile::load("scenes/showcase.scene").unwrap();
+        file.camera.width = 24;
+        file.camera.height = 24;
+        let camera = file.camera();
+        let mut settings = SamplerSettings::new(4);
+        settings.filter = Filter::Tent;
+        settings.seed = 42;
+
+        let mut first = Canvas::new(24, 24);
+        Camera::render_sampled_parallel(&camera, &file.scene, &mut first, &settings, 1);
+        let mut second = Canvas::new(24, 24);
+        Camera::render_sampled_parallel(&camera, &file.scene, &mut second, &settings, 4);
+        assert!(first == second);
+
+        settings.seed = 43;
+        let mut third = Canvas::new(24, 24);
+        Camera::render_sampled_parallel(&camera, &file.scene, &mut third, &settings, 4);
+        assert!(first != third);
+    }
+
+    //Tests that a pixel fully covered by one color keeps that color with every filter
+    #[test]
+    fn sampled_pixel_color() {
+        let scene = Scene::default();
+        let camera = parallel_test_camera();
+        let center = Camera::pixel_color(&camera, &scene, 18, 10).unwrap();
+        for filter in [Filter::Box, Filter::Tent, Filter::Gaussian, Filter::Mitchell].iter() {
+            let mut settings = SamplerSettings::new(9);
+            settings.filter = *filter;
+            let color = Camera::pixel_color_sampled(&camera, &scene, 18, 10, &settings);
+            assert!((color.0 - center.0).abs() < 0.05 && (color.1 - center.1).abs() < 0.05, "{:?}", filter);
+        }
+        assert_eq!(Camera::pixel_color_sampled(&camera, &scene, 0, 0, &SamplerSettings::new(4)), Color::new(0.0, 0.0, 0.0));
+    }
+
+    //Tests that adaptive rendering only refines pixels on edges and counts the rays it traced
+    #[test]
+    fn adaptive_render() {
+        let scene = Scene::default();
+        let camera = parallel_test_camera();
+        let settings = AdaptiveSettings::default();
+        let mut canvas = Canvas::new(camera.hsize as usize, camera.vsize as usize);
+        let stats = Camera::render_adaptive_parallel(&camera, &scene, &mut canvas, &settings, 4);
+        let pixels = (camera.hsize * camera.vsize) as usize;
+        assert_eq!(stats.pixels, pixels);
+        assert!(stats.refined_pixels > 0 && stats.refined_pixels < pixels / 2);
+        assert!(stats.rays > pixels && stats.rays <= pixels + stats.refined_pixels * 25);
+        assert!(stats.rays_per_pixel() < 5.0);
+
+        //Pixels which are not refined keep the color of their single sample
+        assert_eq!(canvas.get(0, 0), Some(&Color::new(0.0, 0.0, 0.0)));
+        let mut reference = Canvas::new(camera.hsize as usize, camera.vsize as usize);
+        Camera::render_parallel(&camera, &scene, &mut reference, 1);
+        let unchanged = canvas.contents.iter().zip(&reference.contents).filter(|(a, b)| a == b).count();
+        assert!(unchanged >= pixels - stats.refined_pixels);
+    }
+
+    //Tests that a pixel is only split where its samples differ
+    #[test]
+    fn adaptive_pixel_color() {
+        let scene = Scene::default();
+        let camera = parallel_test_camera();
+        let settings = AdaptiveSettings::default();
+        let (color, rays) = Camera::pixel_color_adaptive(&camera, &scene, 0, 0, &settings);
+        assert_eq!(color, Color::new(0.0, 0.0, 0.0));
+        assert_eq!(rays, 4);
+
+        //A pixel on the edge of the sphere is split until the maximum depth
+        let edge = (0..camera.hsize)
+            .map(|x| Camera::pixel_color_adaptive(&camera, &scene, x, camera.vsize / 2, &settings))
+            .max_by_key(|(_, rays)| *rays)
+            .unwrap();
+        assert!(edge.1 > 9 && edge.1 <= 25);
+    }
+
+    //Tests that rays through different points of the lens meet on the focal plane
+    #[test]
+    fn thin_lens_rays() {
+        let mut camera = parallel_test_camera();
+        camera.set_lens(0.5, 5.0);
+        let center = Camera::ray_through_lens(&camera, 18, 10, 0.5, 0.5, 0.0, 0.0);
+        let pinhole = Camera::ray_towards_pixel(&parallel_test_camera(), 18, 10);
+        assert_eq!(center.origin.round(), pinhole.origin.round());
+        assert_eq!(center.direction.round(), pinhole.direction.round());
+
+        let focus = |ray: &rust_ray_tracer::ray_tracing::ray::Ray| {
+            let distance = (0.0 - ray.origin.2) / ray.direction.2;
+            (ray.origin.clone() + ray.direction.clone() * distance).round()
+        };
+        for (lens_x, lens_y) in [(1.0, 0.0), (0.0, -1.0), (0.6, 0.6)].iter() {
+            let ray = Camera::ray_through_lens(&camera, 7, 3, 0.25, 0.75, *lens_x, *lens_y);
+            assert_eq!(ray.origin.round(), Vec4::new(-lens_x * 0.5, lens_y * 0.5, -5.0, 1.0).round());
+            assert_eq!(focus(&ray), focus(&Camera::ray_through_lens(&camera, 7, 3, 0.25, 0.75, 0.0, 0.0)));
+        }
+    }
+
+    //Tests that a lens blurs objects away from the focal plane
+    #[test]
+    fn depth_of_field() {
+        let scene = Scene::default();
+        let mut camera = parallel_test_camera();
+        let settings = SamplerSettings::new(16);
+        let edge = |camera: &Camera| {
+            (0..camera.hsize)
+                .map(|x| Camera::pixel_color_sampled(camera, &scene, x, 10, &settings).0)
+                .filter(|red| *red > 0.05 && *red < 0.5)
+                .count()
+        };
+        let sharp = edge(&camera);
+        camera.set_lens(2.0, 20.0);
+        let blurred = edge(&camera);
+        assert!(blurred > sharp + 2);
+
+        //Lens samples are drawn per pixel, so renders do not depend on the thread count
+        let mut first = Canvas::new(37, 21);
+        Camera::render_grid_parallel(&camera, &scene, &mut first, 2, 1);
+        let mut second = Canvas::new(37, 21);
+        Camera::render_grid_parallel(&camera, &scene, &mut second, 2, 3);
+        assert!(first == second);
+    }
+
+    //Tests that orthographic rays are parallel and cover the view at the focal distance
+    #[test]
+    fn orthographic_rays() {
+        let mut camera = Camera::new(201, 101, 90.0);
+        camera.set_projection(Projection::Orthographic);
+        camera.set_lens(0.0, 2.0);
+        let center = Camera::ray_towards_pixel(&camera, 100, 50);
+        assert_eq!(center.origin.round(), Vec4::new(0.0, 0.0, 0.0, 1.0));
+        assert_eq!(center.direction.round(), Vec4::new(0.0, 0.0, -1.0, 0.0));
+        let corner = Camera::ray_towards_pixel_raw(&camera, 0, 0, 0.0, 0.0);
+        assert_eq!(corner.origin.round(), Vec4::new(2.0, 0.99502, 0.0, 1.0).round());
+        assert_eq!(corner.direction.round(), Vec4::new(0.0, 0.0, -1.0, 0.0));
+    }
+
+    //Tests that fisheye rays turn evenly away from the view direction
+    #[test]
+    fn fisheye_rays() {
+        let mut camera = Camera::new(101, 51, 180.0);
+        camera.set_projection(Projection::Fisheye);
+        let center = Camera::ray_towards_pixel(&camera, 50, 25);
+        assert_eq!(center.direction.round(), Vec4::new(0.0, 0.0, -1.0, 0.0));
+        let edge = Camera::ray_towards_pixel_raw(&camera, 0, 25, 0.0, 0.5);
+        assert_eq!(edge.direction.round(), Vec4::new(1.0, 0.0, 0.0, 0.0));
+        let halfway = Camera::ray_towards_pixel_raw(&camera, 25, 25, 0.25, 0.5);
+        assert_eq!(halfway.direction.round(), Vec4::new(FRAC_1_SQRT_2, 0.0, -FRAC_1_SQRT_2, 0.0).round());
+    }
+
+    //Tests that equirectangular rays cover every direction around the camera
+    #[test]
+    fn equirectangular_rays() {
+        let mut camera = Camera::new(200, 100, 60.0);
+        camera.set_projection(Projection::Equirectangular);
+        camera.transform(Matrix4x4::translation(0.0, 1.0, 0.0));
+        let center = Camera::ray_towards_pixel_raw(&camera, 100, 50, 0.0, 0.0);
+        assert_eq!(center.origin.round(), Vec4::new(0.0, -1.0, 0.0, 1.0));
+        assert_eq!(center.direction.round(), Vec4::new(0.0, 0.0, -1.0, 0.0));
+        let behind = Camera::ray_towards_pixel_raw(&camera, 0, 50, 0.0, 0.0);
+        assert_eq!(behind.direction.round(), Vec4::new(0.0, 0.0, 1.0, 0.0));
+        let side = Camera::ray_towards_pixel_raw(&camera, 50, 50, 0.0, 0.0);
+        assert_eq!(side.direction.round(), Vec4::new(1.0, 0.0, 0.0, 0.0));
+        let up = Camera::ray_towards_pixel_raw(&camera, 100, 0, 0.0, 0.0);
+        assert_eq!(up.direction.round(), Vec4::new(0.0, 1.0, 0.0, 0.0));
+    }
+
+    //Tests that quick and parallel renders both use the projection
+    #[test]
+    fn projection_renders() {
+        let mut scene = Scene::default();
+        let mut camera = parallel_test_camera();
+        camera.set_projection(Projection::Orthographic);
+        camera.set_lens(0.0, 1.0);
+        let mut quick = Canvas::new(37, 21);
+        Camera::quick_render(&camera, &mut scene, &mut quick);
+        let mut full = Canvas::new(37, 21);
+        Camera::render_parallel(&camera, &scene, &mut full, 2);
+        //The view is 2 units wide, so the unit sphere covers the whole middle row
+        let black = Color::new(0.0, 0.0, 0.0);
+        assert!((0..37).all(|x| quick.get(x, 10) != Some(&black) && full.get(x, 10) != Some(&black)));
+        assert_eq!(quick.get(0, 0), Some(&black));
+
+        camera.set_projection(Projection::Equirectangular);
+        let mut quick = Canvas::new(37, 21);
+        Camera::quick_render(&camera, &mut scene, &mut quick);
+        assert_eq!(quick.get(0, 10), Some(&black));
+        assert_eq!(quick.get(18, 10), Some(&Color::new(0.8, 1.0, 0.6)));
+    }
+
+    //Tests that the parallel renderers shade with the camera's integrator
+    #[test]
+    fn camera_integrator() {
+        let mut scene = Scene::default();
+        let mut camera = parallel_test_camera();
+        let mut quick = Canvas::new(37, 21);
+        Camera::quick_render(&camera, &mut scene, &mut quick);
+        camera.set_integrator(Box::new(Flat));
+        let mut flat = Canvas::new(37, 21);
+        Camera::render_parallel(&camera, &scene, &mut flat, 2);
+        assert_eq!(flat, quick);
+    }
+
+    //Tests that an open shutter blurs a moving object
+    #[test]
+    fn motion_blur() {
+        let mut scene = Scene::new();
+        scene.light_sources = Scene::default().light_sources;
+        scene.objects.push(Box::new(Moving::new(
+            Box::new(Sphere::default()),
+            MotionPath::linear(Matrix4x4::translation(-2.0, 0.0, 0.0), Matrix4x4::translation(2.0, 0.0, 0.0)),
+        )));
+        let mut camera = parallel_test_camera();
+        let settings = SamplerSettings::new(16);
+        let partly_covered = |camera: &Camera| {
+            (0..camera.hsize)
+                .map(|x| Camera::pixel_color_sampled(camera, &scene, x, 10, &settings).0)
+                .filter(|red| *red > 0.05 && *red < 0.3)
+                .count()
+        };
+        let still = partly_covered(&camera);
+        camera.set_shutter(0.0, 1.0);
+        let blurred = partly_covered(&camera);
+        assert!(blurred > still + 4);
+
+        for _ in 0..10 {
+            let time = Camera::ray_towards_pixel(&camera, 18, 10).time;
+            assert!((0.0..1.0).contains(&time));
+        }
+        camera.set_shutter(0.5, 0.5);
+        assert_eq!(Camera::ray_towards_pixel(&camera, 18, 10).time, 0.5);
+    }
+
+    //Tests that a progressive render reaches its sample count, writes snapshots between passes and is reproducible
+    #[test]
+    fn progressive_render() {
+        let scene = Scene::default();
+        let camera = parallel_test_camera();
+        let settings = ProgressiveSettings {
+            sampler: SamplerSettings::new(8),
+            snapshot_interval: 0.0,
+            time_limit: None,
+        };
+        let stop = AtomicBool::new(false);
+        let mut snapshots = vec![];
+        let mut canvas = Canvas::new(37, 21);
+        let stats = Camera::render_progressive(&camera, &scene, &mut canvas, &settings, 4, &stop, |_, samples| {
+            snapshots.push(samples)
+        });
+        assert_eq!(snapshots, vec![1, 2, 4]);
+        assert_eq!(stats.rays, 37 * 21 * 8);
+        assert_eq!(stats.rays_per_pixel(), 8.0);
+        assert_eq!(canvas.get(0, 0), Some(&Color::new(0.0, 0.0, 0.0)));
+        let center = Camera::pixel_color(&camera, &scene, 18, 10).unwrap();
+        assert!((canvas.get(18, 10).unwrap().0 - center.0).abs() < 0.05);
+
+        let mut again = Canvas::new(37, 21);
+        Camera::render_progressive(&camera, &scene, &mut again, &settings, 1, &stop, |_, _| {});
+        assert!(canvas == again);
+    }
+
+    //Tests that a stopped progressive render keeps the image it had
+    #[test]
+    fn stopped_progressive_render() {
+        let scene = Scene::default();
+        let camera = parallel_test_camera();
+        let stop = AtomicBool::new(true);
+        let mut canvas = Canvas::new(37, 21);
+        let stats = Camera::render_progressive(&camera, &scene, &mut canvas, &ProgressiveSettings::default(), 2, &stop, |_, _| {
+            panic!("no snapshot is written after stopping")
+        });
+        assert_eq!(stats.rays, 0);
+        assert_eq!(canvas.get(18, 10), Some(&Color::new(0.0, 0.0, 0.0)));
+    }
+
+    //Tests limiting the crop window to the image and splitting it into tiles
+    #[test]
+    fn crop_region() {
+        let mut camera = parallel_test_camera();
+        assert_eq!(Camera::region(&camera), Tile { x: 0, y: 0, width: 37, height: 21 });
+        camera.set_crop(30, 5, 20, 10);
+        assert_eq!(Camera::region(&camera), Tile { x: 30, y: 5, width: 7, height: 10 });
+        camera.set_crop(10, 3, 20, 15);
+        let tiles = Camera::tiles(&camera, 16);
+        assert_eq!(tiles.len(), 2);
+        assert_eq!(tiles[1], Tile { x: 26, y: 3, width: 4, height: 15 });
+        camera.set_crop(-5, 40, 10, 10);
+        assert_eq!(Camera::region(&camera).width * Camera::region(&camera).height, 0);
+        assert!(Camera::tiles(&camera, 16).is_empty());
+    }
+
+    //Tests that every renderer traces the pixels of a crop exactly as in the full image
+    #[test]
+    fn cropped_renders_match_full_render() {
+        let scene = Scene::default();
+        let mut camera = parallel_test_camera();
+        let sampler = SamplerSettings::new(4);
+        let adaptive = AdaptiveSettings::default();
+        let progressive = ProgressiveSettings {
+            sampler: SamplerSettings::new(2),
+            ..ProgressiveSettings::default()
+        };
+        let stop = AtomicBool::new(false);
+        let render = |camera: &Camera, canvas: &mut Canvas, renderer: usize| match renderer {
+            0 => Camera::render(camera, &scene, canvas),
+            1 => Camera::render_parallel(camera, &scene, canvas, 3),
+            2 => Camera::render_grid_parallel(camera, &scene, canvas, 2, 3),
+            3 => Camera::render_sampled_parallel(camera, &scene, canvas, &sampler, 3),
+            4 => {
+                Camera::render_adaptive_parallel(camera, &scene, canvas, &adaptive, 3);
+            }
+            _ => {
+                Camera::render_progressive(camera, &scene, canvas, &progressive, 3, &stop, |_, _| {});
+            }
+        };
+        for renderer in 0..6 {
+            camera.crop = None;
+            let mut full = Canvas::new(37, 21);
+            render(&camera, &mut full, renderer);
+
+            camera.set_crop(12, 4, 15, 9);
+            let mut cropped = Canvas::new(15, 9);
+            render(&camera, &mut cropped, renderer);
+            let mut full_size = Canvas::new(37, 21);
+            render(&camera, &mut full_size, renderer);
+            for y in 0..21 {
+                for x in 0..37 {
+                    let inside = (12..27).contains(&x) && (4..13).contains(&y);
+                    if inside {
+                        assert_eq!(cropped.get(x - 12, y - 4), full.get(x, y), "renderer {}", renderer);
+                        assert_eq!(full_size.get(x, y), full.get(x, y), "renderer {}", renderer);
+                    }
+                    else {
+                        assert_eq!(full_size.get(x, y), Some(&Color::new(0.0, 0.0, 0.0)), "renderer {}", renderer);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod tests {
+    use rust_ray_tracer::core::canvas::Canvas;
+    use rust_ray_tracer::core::color::Color;
+    use rust_ray_tracer::core::matrix::Matrix4x4;
+    use rust_ray_tracer::core::vector::Vec4;
+    use rust_ray_tracer::world::camera::*;
+    use rust_ray_tracer::world::checkpoint::*;
+    use rust_ray_tracer::world::sampler::*;
+    use rust_ray_tracer::world::scene::Scene;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::sync::atomic::AtomicBool;
+
+    //Creates a checkpoint in the temporary directory which is saved after every tile or pass
+    fn temp_checkpoint(name: &str, fingerprint: u64) -> Checkpoint {
+        let path = std::env::temp_dir().join(name);
+        let mut checkpoint = Checkpoint::new(&path.to_string_lossy(), fingerprint);
+        checkpoint.interval = 0.0;
+        checkpoint
+    }
+
+    //Creates a small camera looking at the default scene
+    fn test_camera() -> Camera {
+        let mut camera = Camera::new(37, 21, 90.0);
+        camera.transform(Matrix4x4::view_transform(
+            Vec4::new(0.0, 0.0, -5.0, 1.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+            Vec4::new(0.0, 1.0, 0.0, 0.0),
+        ));
+        camera
+    }
+
+    #[test]
+    //Tests saving and loading finished tiles and sample totals
+    fn save_and_load() {
+        let checkpoint = temp_checkpoint("rust_ray_tracer_checkpoint_save.bin", 7);
+        assert_eq!(checkpoint.load(4, 2).unwrap(), None);
+
+        let mut tiles = HashMap::new();
+        tiles.insert(3, vec![Some(Color::new(0.25, -1.0, 8.5)), None]);
+        tiles.insert(0, vec![]);
+        let progress = Progress::Tiles(tiles);
+        checkpoint.save(4, 2, &progress).unwrap();
+        assert_eq!(checkpoint.load(4, 2).unwrap(), Some(progress));
+
+        let mut sum = SampleSum::default();
+        sum.add(Color::new(0.5, 0.25, 1.0), 0.75);
+        let progress = Progress::Samples {
+            passes: 3,
+            samples: 4,
+            sums: vec![sum, SampleSum::default()],
+        };
+        checkpoint.save(4, 2, &progress).unwrap();
+        assert_eq!(checkpoint.load(4, 2).unwrap(), Some(progress));
+
+        //Progress from a different render or size is not resumed
+        assert_eq!(checkpoint.load(2, 4).unwrap(), None);
+        assert_eq!(temp_checkpoint("rust_ray_tracer_checkpoint_save.bin", 8).load(4, 2).unwrap(), None);
+
+        checkpoint.remove().unwrap();
+        assert!(!std::path::Path::new(&checkpoint.path).exists());
+        checkpoint.remove().unwrap();
+    }
+
+    #[test]
+    //Tests that damaged checkpoint files are reported
+    fn damaged_checkpoint() {
+        let checkpoint = temp_checkpoint("rust_ray_tracer_checkpoint_damaged.bin", 1);
+        checkpoint.save(1, 1, &Progress::Tiles(HashMap::new())).unwrap();
+        let bytes = fs::read(&checkpoint.path).unwrap();
+        fs::write(&checkpoint.path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(checkpoint.load(1, 1).is_err());
+        fs::write(&checkpoint.path, b"not a checkpoint").unwrap();
+        assert!(checkpoint.load(1, 1).is_err());
+        checkpoint.remove().unwrap();
+    }
+
+    #[test]
+    //Tests that fingerprints tell descriptions apart
+    fn fingerprints() {
+        assert_eq!(fingerprint(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fingerprint("scene"), fingerprint("scene"));
+        assert_ne!(fingerprint("scene 1"), fingerprint("scene 2"));
+    }
+
+    #[test]
+    //Tests that a resumed render keeps the saved tiles and shades the rest as an uninterrupted render would
+    fn resume_tiles() {
+        let scene = Scene::default();
+        let mut camera = test_camera();
+        let mut reference = Canvas::new(37, 21);
+        Camera::render_parallel(&camera, &scene, &mut reference, 2);
+
+        //Saves every other tile with a color the scene can not produce, so tiles shaded again would not match
+        let marker = Color::new(0.0, 5.0, 0.0);
+        let checkpoint = temp_checkpoint("rust_ray_tracer_checkpoint_tiles.bin", 2);
+        let tiles = Camera::tiles(&camera, TILE_SIZE);
+        let mut saved = HashMap::new();
+        for (index, tile) in tiles.iter().enumerate().step_by(2) {
+            saved.insert(index, vec![Some(marker.clone()); tile.pixels().len()]);
+        }
+        checkpoint.save(37, 21, &Progress::Tiles(saved)).unwrap();
+
+        camera.set_checkpoint(checkpoint.clone());
+        let mut resumed = Canvas::new(37, 21);
+        Camera::render_parallel(&camera, &scene, &mut resumed, 2);
+        for (index, tile) in tiles.iter().enumerate() {
+            for (x, y) in tile.pixels() {
+                let expected = if index % 2 == 0 { Some(&marker) } else { reference.get(x, y) };
+                assert_eq!(resumed.get(x, y), expected);
+            }
+        }
+        match checkpoint.load(37, 21).unwrap() {
+            Some(Progress::Tiles(saved)) => assert_eq!(saved.len(), tiles.len()),
+            other => panic!("unexpected checkpoint {:?}", other),
+        }
+        checkpoint.remove().unwrap();
+    }
+
+    #[test]
+    //Tests that a stopped progressive render saves its samples and carries on from them
+    fn resume_progressive() {
+        let scene = Scene::default();
+        let mut camera = test_camera();
+        let checkpoint = temp_checkpoint("rust_ray_tracer_checkpoint_progressive.bin", 3);
+        camera.set_checkpoint(checkpoint.clone());
+        let mut settings = ProgressiveSettings::default();
+        settings.sampler.samples = 2;
+        let mut canvas = Canvas::new(37, 21);
+        let stats = Camera::render_progressive(&camera, &scene, &mut canvas, &settings, 2, &AtomicBool::new(false), |_, _| {});
+        assert!(!stats.stopped);
+        match checkpoint.load(37, 21).unwrap() {
+            Some(Progress::Samples { passes, samples, .. }) => assert_eq!((passes, samples), (2, 2)),
+            other => panic!("unexpected checkpoint {:?}", other),
+        }
+
+        //Asking for more samples continues from the checkpoint
+        settings.sampler.samples = 4;
+        let stats = Camera::render_progressive(&camera, &scene, &mut canvas, &settings, 2, &AtomicBool::new(false), |_, _| {});
+        assert_eq!(stats.rays, 37 * 21 * 4);
+
+        //A stopped render traces nothing more but still fills the canvas from the checkpoint
+        let mut stopped = Canvas::new(37, 21);
+        settings.sampler.samples = 8;
+        let stats = Camera::render_progressive(&camera, &scene, &mut stopped, &settings, 2, &AtomicBool::new(true), |_, _| {});
+        assert!(stats.stopped);
+        assert_eq!(stats.rays, 37 * 21 * 4);
+        assert!(stopped == canvas);
+        checkpoint.remove().unwrap();
+    }
+}
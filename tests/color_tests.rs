@@ -44,5 +44,12 @@ mod tests {
         let clr = Color::new(1.0, 0.5, 0.0);
         assert_eq!(clr.ppm_string(), String::from("255 128 0"));
     }
-}
 
+    //Tests scaling colors to whole numbers
+    #[test]
+    fn quantize() {
+        let clr = Color::new(1.5, 0.5, -0.2);
+        assert_eq!(clr.quantize(255), [255, 128, 0]);
+        assert_eq!(clr.quantize(65535), [65535, 32768, 0]);
+    }
+}
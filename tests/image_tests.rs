@@ -0,0 +1,329 @@
+#[cfg(test)]
+mod tests {
+    use rust_ray_tracer::core::canvas::Canvas;
+    use rust_ray_tracer::core::color::Color;
+    use rust_ray_tracer::image::format::*;
+    use rust_ray_tracer::image::hdr::*;
+    use rust_ray_tracer::image::png::*;
+    use rust_ray_tracer::image::tone::*;
+    use rust_ray_tracer::image::zlib::*;
+    use rust_ray_tracer::world::camera::Camera;
+    use rust_ray_tracer::world::scene_file::SceneFile;
+    use std::fs;
+    use std::io;
+
+    //Reads a big endian u32 from a byte slice
+    fn read_u32(bytes: &[u8], start: usize) -> u32 {
+        u32::from_be_bytes([bytes[start], bytes[start + 1], bytes[start + 2], bytes[start + 3]])
+    }
+
+    #[test]
+    //Tests the checksums against known values
+    fn checksums() {
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E60398);
+        assert_eq!(adler32(&[]), 1);
+    }
+
+    #[test]
+    //Tests the paeth predictor
+    fn paeth_predictor() {
+        assert_eq!(paeth(10, 20, 10), 20);
+        assert_eq!(paeth(20, 10, 10), 20);
+        assert_eq!(paeth(10, 10, 20), 10);
+        assert_eq!(paeth(0, 0, 0), 0);
+    }
+
+    #[test]
+    //Tests the zlib header and that repeated data is compressed
+    fn zlib_stream() {
+        let data = vec![7; 10000];
+        let compressed = compress(&data);
+        assert_eq!(&compressed[0..2], &[0x78, 0x01]);
+        assert_eq!(read_u32(&compressed, compressed.len() - 4), adler32(&data));
+        assert!(compressed.len() < 200);
+    }
+
+    #[test]
+    //Tests the png signature, header and chunk checksums
+    fn png_structure() {
+        let mut canvas = Canvas::new(5, 3);
+        canvas.set(Color::new(1.0, 0.5, 0.0), 2, 1);
+        for bit_depth in &[8, 16] {
+            let bytes = encode_png(&canvas, *bit_depth);
+            assert_eq!(&bytes[0..8], &PNG_SIGNATURE);
+            assert_eq!(read_u32(&bytes, 8), 13);
+            assert_eq!(&bytes[12..16], b"IHDR");
+            assert_eq!(read_u32(&bytes, 16), 5);
+            assert_eq!(read_u32(&bytes, 20), 3);
+            assert_eq!(bytes[24], *bit_depth);
+            assert_eq!(bytes[25], 2);
+            assert_eq!(read_u32(&bytes, 29), crc32(&bytes[12..29]));
+            assert_eq!(&bytes[bytes.len() - 8..bytes.len() - 4], b"IEND");
+        }
+    }
+
+    #[test]
+    //Tests picking the format from a file extension
+    fn format_from_path() {
+        assert_eq!(ImageFormat::from_path("image.ppm").unwrap(), ImageFormat::Ppm);
+        assert_eq!(ImageFormat::from_path("out/IMAGE.PNG").unwrap(), ImageFormat::Png);
+        assert_eq!(ImageFormat::from_path("image.pfm").unwrap(), ImageFormat::Pfm);
+        assert_eq!(ImageFormat::from_path("image.hdr").unwrap(), ImageFormat::Hdr);
+        assert!(ImageFormat::from_path("image.jpg").is_err());
+        assert!(ImageFormat::from_path("image").is_err());
+    }
+
+    #[test]
+    //Tests saving a canvas as a png file
+    fn save_png() {
+        let path = std::env::temp_dir().join("rust_ray_tracer_save_png.png");
+        let path = path.to_str().unwrap();
+        let canvas = Canvas::new(4, 4);
+        canvas.save(path).unwrap();
+        let bytes = fs::read(path).unwrap();
+        assert_eq!(bytes, encode_png(&canvas, 8));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    //Tests that pfm files keep unclamped values with the bottom row first
+    fn pfm_layout() {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.set(Color::new(1.5, -0.25, 40.0), 0, 1);
+        let bytes = encode_pfm(&canvas);
+        let header = b"PF\n2 2\n-1.0\n";
+        assert_eq!(&bytes[0..header.len()], header);
+        assert_eq!(bytes.len(), header.len() + 2 * 2 * 12);
+        let channel = |index: usize| {
+            let start = header.len() + index * 4;
+            f32::from_le_bytes([bytes[start], bytes[start + 1], bytes[start + 2], bytes[start + 3]])
+        };
+        assert_eq!(channel(0), 1.5);
+        assert_eq!(channel(1), -0.25);
+        assert_eq!(channel(2), 40.0);
+    }
+
+    #[test]
+    //Tests converting colors to and from the shared exponent form
+    fn rgbe_round_trip() {
+        assert_eq!(to_rgbe(&Color::new(0.0, 0.0, 0.0)), [0, 0, 0, 0]);
+        assert_eq!(to_rgbe(&Color::new(1.0, 0.5, 0.0)), [128, 64, 0, 129]);
+        for color in &[Color::new(12.5, 0.3, 3.0), Color::new(0.01, 0.002, 0.005), Color::new(1.0, 1.0, 1.0)] {
+            let result = from_rgbe(&to_rgbe(color));
+            let largest = color.0.max(color.1).max(color.2);
+            assert!((result.0 - color.0).abs() <= largest / 128.0);
+            assert!((result.1 - color.1).abs() <= largest / 128.0);
+            assert!((result.2 - color.2).abs() <= largest / 128.0);
+        }
+    }
+
+    #[test]
+    //Tests the radiance header and that flat scanlines are run length encoded
+    fn hdr_layout() {
+        let mut canvas = Canvas::new(100, 2);
+        for x in 0..100 {
+            canvas.set(Color::new(4.0, 4.0, 4.0), x, 0);
+        }
+        let bytes = encode_hdr(&canvas);
+        let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 2 +X 100\n";
+        assert_eq!(&bytes[0..header.len()], header);
+        //Each scanline is a marker and one run per channel
+        assert_eq!(bytes.len(), header.len() + 2 * (4 + 4 * 2));
+        assert_eq!(&bytes[header.len()..header.len() + 4], &[2, 2, 0, 100]);
+        assert_eq!(&bytes[header.len() + 4..header.len() + 6], &[228, 128]);
+
+        //Narrow images are written without run length encoding
+        let narrow = encode_hdr(&Canvas::new(3, 1));
+        assert_eq!(narrow.len(), b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 3\n".len() + 12);
+    }
+
+    //Creates a canvas with a different color in every pixel
+    fn gradient_canvas(width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let color = Color::new(x as f32 / width as f32, y as f32 / height as f32, ((x + y) % 3) as f32 * 0.5);
+                canvas.set(color, x as i32, y as i32);
+            }
+        }
+        canvas
+    }
+
+    #[test]
+    //Tests decompressing streams with stored and compressed blocks
+    fn zlib_round_trip() {
+        let data: Vec<u8> = (0..5000).map(|index| (index * index % 251) as u8).collect();
+        assert_eq!(decompress(&compress(&data)).unwrap(), data);
+        assert_eq!(decompress(&compress(&[])).unwrap(), Vec::<u8>::new());
+
+        //A stored block holding "abc" with its length and complement
+        let stored = [0x78, 0x01, 0x01, 0x03, 0x00, 0xfc, 0xff, b'a', b'b', b'c', 0x02, 0x4d, 0x01, 0x27];
+        assert_eq!(decompress(&stored).unwrap(), b"abc");
+
+        let mut corrupted = compress(&data);
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 1;
+        assert!(decompress(&corrupted).is_err());
+    }
+
+    #[test]
+    //Tests that every format reads back what was written
+    fn format_round_trips() {
+        let canvas = gradient_canvas(17, 9);
+        let cases: [(Vec<u8>, f32); 5] = [
+            (canvas.to_ppm().into_bytes(), 0.5 / 255.0),
+            (encode_png(&canvas, 8), 0.5 / 255.0),
+            (encode_png(&canvas, 16), 0.5 / 65535.0),
+            (encode_pfm(&canvas), 0.0),
+            (encode_hdr(&canvas), 1.0 / 128.0),
+        ];
+        for (bytes, tolerance) in cases.iter() {
+            let result = Canvas::decode(bytes).unwrap();
+            assert!(canvas.max_difference(&result).unwrap() <= *tolerance + 1e-6);
+        }
+    }
+
+    #[test]
+    //Tests reading binary and ascii ppm files with comments and 16 bit samples
+    fn read_ppm() {
+        let ascii = Canvas::decode(b"P3\n# comment\n2 1\n255\n255 0 0\n0 51 255\n").unwrap();
+        assert_eq!(ascii.get(0, 0), Some(&Color::new(1.0, 0.0, 0.0)));
+        assert_eq!(ascii.get(1, 0), Some(&Color::new(0.0, 0.2, 1.0)));
+
+        let mut binary = b"P6 1 1 65535\n".to_vec();
+        binary.extend(&[0xff, 0xff, 0x00, 0x00, 0x80, 0x00]);
+        let binary = Canvas::decode(&binary).unwrap();
+        assert_eq!(binary.get(0, 0), Some(&Color::new(1.0, 0.0, 32768.0 / 65535.0)));
+
+        assert!(Canvas::decode(b"P3\n2 1\n255\n255 0 0\n").is_err());
+        assert!(Canvas::decode(b"P6\n2 2\n255\n\x01\x02").is_err());
+        assert!(Canvas::decode(b"JPEG").is_err());
+        let huge = format!("P6\n{} 2\n255\n\x01\x02", usize::MAX / 2);
+        assert_eq!(Canvas::decode(huge.as_bytes()).err().unwrap().kind(), io::ErrorKind::InvalidData);
+        let huge = format!("PF\n{} {}\n-1.0\n", usize::MAX / 8, 4);
+        assert_eq!(Canvas::decode(huge.as_bytes()).err().unwrap().kind(), io::ErrorKind::InvalidData);
+        let sizes: [(usize, usize); 3] = [(usize::MAX / 2, 3), (1, 2305843009213693952), (1, 1000000000)];
+        for (height, width) in sizes {
+            let huge = format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n", height, width);
+            assert_eq!(Canvas::decode(huge.as_bytes()).err().unwrap().kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    //Tests that damaged png files are rejected
+    fn read_invalid_png() {
+        let bytes = encode_png(&gradient_canvas(4, 4), 8);
+        let mut corrupted = bytes.clone();
+        corrupted[40] ^= 0xff;
+        assert!(Canvas::decode(&corrupted).is_err());
+        assert!(Canvas::decode(&bytes[0..bytes.len() - 12]).is_err());
+
+        //A header claiming a huge image is rejected before the canvas is allocated
+        let mut huge = bytes.clone();
+        huge[16..24].copy_from_slice(&[0xff; 8]);
+        let checksum = crc32(&huge[12..29]);
+        huge[29..33].copy_from_slice(&checksum.to_be_bytes());
+        assert_eq!(Canvas::decode(&huge).err().unwrap().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    //Tests loading a file from disk and comparing canvases
+    fn load_and_compare() {
+        let path = std::env::temp_dir().join("rust_ray_tracer_load.png");
+        let path = path.to_str().unwrap();
+        let canvas = gradient_canvas(6, 5);
+        canvas.save(path).unwrap();
+        let loaded = Canvas::load(path).unwrap();
+        fs::remove_file(path).unwrap();
+        assert!(loaded.max_difference(&canvas).unwrap() < 1.0 / 255.0);
+        assert_eq!(loaded.max_difference(&Canvas::new(5, 6)), None);
+        assert!(Canvas::load("tests/missing.png").is_err());
+    }
+
+    #[test]
+    //Tests a render of a deterministic scene against the stored reference image
+    fn render_matches_reference() {
+        let file = SceneFile::load("tests/golden.scene").unwrap();
+        let mut canvas = Canvas::new(file.camera.width, file.camera.height);
+        Camera::render_parallel(&file.camera(), &file.scene, &mut canvas, 0);
+        //The render goes through the same encoding as the reference so highlights are clamped the same way
+        let rendered = Canvas::decode(&encode_png(&canvas, 8)).unwrap();
+        let reference = Canvas::load("tests/golden.png").unwrap();
+        assert!(rendered.max_difference(&reference).unwrap() <= 1.0 / 255.0);
+    }
+
+    #[test]
+    //Tests the tone mapping operators
+    fn tone_map_operators() {
+        assert_eq!(ToneMap::Clamp.apply(2.5), 2.5);
+        assert_eq!(ToneMap::Clamp.apply(-1.0), 0.0);
+        assert_eq!(ToneMap::Reinhard.apply(1.0), 0.5);
+        assert_eq!(ToneMap::Reinhard.apply(3.0), 0.75);
+        assert_eq!(ToneMap::Aces.apply(0.0), 0.0);
+        assert!((ToneMap::Aces.apply(1.0) - 0.8038).abs() < 0.001);
+        assert_eq!(ToneMap::Aces.apply(1000.0), 1.0);
+        //Both curves keep increasing so bright values stay distinct
+        assert!(ToneMap::Reinhard.apply(10.0) < ToneMap::Reinhard.apply(20.0));
+        assert!(ToneMap::Aces.apply(2.0) < ToneMap::Aces.apply(4.0));
+        assert_eq!(ToneMap::from_name("aces"), Some(ToneMap::Aces));
+        assert_eq!(ToneMap::from_name("filmic"), None);
+    }
+
+    #[test]
+    //Tests the sRGB transfer curve
+    fn srgb_curve() {
+        assert_eq!(srgb_encode(0.0), 0.0);
+        assert!((srgb_encode(1.0) - 1.0).abs() < 0.0001);
+        assert!((srgb_encode(0.18) - 0.4614).abs() < 0.001);
+        assert!((srgb_encode(0.002) - 0.02584).abs() < 0.0001);
+        for value in &[0.001, 0.01, 0.2, 0.5, 0.9] {
+            assert!((srgb_decode(srgb_encode(*value)) - value).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    //Tests exposure, tone mapping and gamma applied together
+    fn tone_settings() {
+        let settings = ToneSettings::default();
+        assert!(settings.is_identity());
+        assert_eq!(settings.apply(&Color::new(2.0, -1.0, 0.5)), Color::new(2.0, 0.0, 0.5));
+
+        let settings = ToneSettings {
+            exposure: 1.0,
+            tone_map: ToneMap::Reinhard,
+            srgb: false,
+        };
+        assert_eq!(settings.apply(&Color::new(0.5, 1.5, 0.0)), Color::new(0.5, 0.75, 0.0));
+
+        let settings = ToneSettings {
+            exposure: -1.0,
+            tone_map: ToneMap::Clamp,
+            srgb: true,
+        };
+        let result = settings.apply(&Color::new(0.36, 4.0, 0.0));
+        assert!((result.0 - 0.4614).abs() < 0.001);
+        assert!((result.1 - 1.0).abs() < 0.0001);
+
+        let canvas = settings.apply_canvas(&gradient_canvas(3, 2));
+        assert_eq!(canvas.get(2, 1), Some(&settings.apply(gradient_canvas(3, 2).get(2, 1).unwrap())));
+    }
+
+    #[test]
+    //Tests that tone settings are used when saving and that the default keeps values unclamped
+    fn save_with_tone_mapping() {
+        let path = std::env::temp_dir().join("rust_ray_tracer_tone.pfm");
+        let path = path.to_str().unwrap();
+        let mut canvas = Canvas::new(1, 1);
+        canvas.set(Color::new(3.0, -0.5, 1.0), 0, 0);
+
+        canvas.save(path).unwrap();
+        assert_eq!(Canvas::load(path).unwrap(), canvas);
+
+        let mut settings = ImageSettings::default();
+        settings.tone.tone_map = ToneMap::Reinhard;
+        canvas.save_with(path, &settings).unwrap();
+        assert_eq!(Canvas::load(path).unwrap().get(0, 0), Some(&Color::new(0.75, 0.0, 0.5)));
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use rust_ray_tracer::core::color::*;
+    use rust_ray_tracer::core::matrix::Matrix4x4;
+    use rust_ray_tracer::materials::material::Material;
+    use rust_ray_tracer::objects::plane::Plane;
+    use rust_ray_tracer::objects::sphere::Sphere;
+    use rust_ray_tracer::ray_tracing::ray::Ray;
+    use rust_ray_tracer::world::integrator::*;
+    use rust_ray_tracer::world::scene::Scene;
+
+    #[test]
+    //Tests that the Whitted and flat integrators match the shading functions of Scene
+    fn scene_integrators() {
+        let scene = Scene::default();
+        let ray = Ray::new((0.0, 0.0, -5.0), (0.0, 0.0, 1.0));
+        assert_eq!(Whitted.color(ray.clone(), &scene, 5), Scene::compute_color(ray.clone(), &scene, 5));
+        assert_eq!(Flat.color(ray.clone(), &scene, 5), Some(Color::new(0.8, 1.0, 0.6)));
+        let miss = Ray::new((0.0, 5.0, -5.0), (0.0, 0.0, 1.0));
+        for kind in ["whitted", "path", "flat", "normals", "depth", "ao"] {
+            let integrator = IntegratorKind::from_name(kind).unwrap().integrator();
+            assert_eq!(integrator.color(miss.clone(), &scene, 5), None);
+        }
+    }
+
+    #[test]
+    //Tests the normal and depth debug integrators on the front of a sphere
+    fn normals_and_depth() {
+        let scene = Scene::default();
+        let ray = Ray::new((0.0, 0.0, -5.0), (0.0, 0.0, 1.0));
+        assert_eq!(Normals.color(ray.clone(), &scene, 5), Some(Color::new(0.5, 0.5, 0.0)));
+        assert_eq!(Depth.color(ray, &scene, 5), Some(Color::new(4.0, 4.0, 4.0)));
+    }
+
+    #[test]
+    //Tests that ambient occlusion darkens a floor under a sphere but not in the open
+    fn ambient_occlusion() {
+        let mut scene = Scene::new();
+        scene.objects.push(Box::new(Plane::new(Matrix4x4::identity(), Material::default())));
+        scene.objects.push(Box::new(Sphere::new(Matrix4x4::translation(0.0, 1.2, 0.0), Material::default())));
+        let occlusion = AmbientOcclusion {
+            samples: 64,
+            distance: 2.0,
+        };
+        let open = occlusion.color(Ray::new((10.0, 5.0, 0.0), (0.0, -1.0, 0.0)), &scene, 5).unwrap();
+        assert_eq!(open, WHITE);
+        let under = occlusion.color(Ray::new((0.3, 0.1, -3.0), (0.0, -0.1, 3.0)), &scene, 5).unwrap();
+        assert!(under.0 < 0.8);
+    }
+}
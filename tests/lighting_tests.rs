@@ -10,12 +10,16 @@ mod tests {
     use rust_ray_tracer::objects::plane::Plane;
     use rust_ray_tracer::objects::object::*;
     use rust_ray_tracer::world::scene::Scene;
-    use rust_ray_tracer::materials::material::Material;
+    use rust_ray_tracer::materials::material::*;
     use rust_ray_tracer::core::matrix::Matrix4x4;
     use rust_ray_tracer::ray_tracing::ray::Ray;
     use rust_ray_tracer::core::comp::Comp;
     use rust_ray_tracer::ray_tracing::intersection::Intersection;
     use rust_ray_tracer::materials::patterns::*;
+    use rust_ray_tracer::misc::random::reseed;
+    use rust_ray_tracer::objects::group::Group;
+    use rust_ray_tracer::objects::triangle::Triangle;
+    use std::f32::consts::FRAC_1_SQRT_2;
 
     //Tests shadows when sphere does not block the light source from the point
     #[test]
@@ -193,7 +197,7 @@ mod tests {
             )
         ];
         let comps = Comp::compute_vars(intersections[0].clone(), &ray, &intersections);
-        let color = refracted_color(&scene, &comps, 5);
+        let color = specular_color(&scene, &comps, &*comps.bsdf(), 5);
         assert_eq!(color, BLACK);
     }
 
@@ -227,6 +231,7 @@ mod tests {
                     Material::default(),
                 )),
             ],
+            bvh: None,
         };
 
         let ray = Ray::new((0.0, 0.0, -5.0), (0.0, 0.0, 1.0));
@@ -247,7 +252,7 @@ mod tests {
         ];
 
         let comps = Comp::compute_vars(intersections[0].clone(), &ray, &intersections);
-        let color = refracted_color(&scene, &comps, 0);
+        let color = specular_color(&scene, &comps, &*comps.bsdf(), 0);
         assert_eq!(color, BLACK);
     }
 
@@ -282,6 +287,7 @@ mod tests {
                     Material::default(),
                 )),
             ],
+            bvh: None,
         };
 
         let ray = Ray::new((0.0, 0.0, (2.0 as f32).sqrt() / 2.0), (0.0, 1.0, 0.0));
@@ -302,7 +308,7 @@ mod tests {
         ];
 
         let comps = Comp::compute_vars(intersections[1].clone(), &ray, &intersections);
-        let color = refracted_color(&scene, &comps, 5);
+        let color = specular_color(&scene, &comps, &*comps.bsdf(), 5);
         assert_eq!(color, BLACK);
     }
 
@@ -339,6 +345,7 @@ mod tests {
                     material,
                 )),
             ],
+            bvh: None,
         };
 
         let ray = Ray::new((0.0, 0.0, 0.1), (0.0, 1.0, 0.0));
@@ -371,7 +378,7 @@ mod tests {
         ];
 
         let comps = Comp::compute_vars(intersections[2].clone(), &ray, &intersections);
-        let color = refracted_color(&scene, &comps, 5);
+        let color = specular_color(&scene, &comps, &*comps.bsdf(), 5);
         assert_eq!(color.round(), Color(0.0, 0.9988, 0.0472).round());
     }
 
@@ -423,6 +430,7 @@ mod tests {
                 Box::new(floor),
                 Box::new(ball),
             ],
+            bvh: None,
         };
 
         let ray = Ray::new((0.0, 0.0, -3.0), (0.0, -((2.0 as f32).sqrt() / 2.0), (2.0 as f32).sqrt() / 2.0));
@@ -440,4 +448,215 @@ mod tests {
         let color = Scene::scene_lighting(&scene, &comps, 5);
         assert_eq!(color.round(), Color(0.93642, 0.68642, 0.68642).round());
     }
+
+    #[test]
+    //Tests the Fresnel approximation and that the GGX distribution covers the hemisphere exactly once
+    fn microfacet_terms() {
+        let f0 = Color(0.04, 0.5, 1.0);
+        assert_eq!(fresnel_schlick(&f0, 1.0), f0);
+        assert_eq!(fresnel_schlick(&f0, 0.0), WHITE);
+        for alpha in [0.3, 1.0] {
+            //Projected area of the microfacets, integrated over the hemisphere one ring of directions at a time
+            let steps = 20000;
+            let mut total = 0.0;
+            for i in 0..steps {
+                let theta = (i as f32 + 0.5) / steps as f32 * std::f32::consts::FRAC_PI_2;
+                let ring = 2.0 * std::f32::consts::PI * theta.sin() * (std::f32::consts::FRAC_PI_2 / steps as f32);
+                total += ggx_distribution(alpha, theta.cos()) * theta.cos() * ring;
+            }
+            assert!((total - 1.0).abs() < 0.01);
+        }
+        assert_eq!(smith_masking(0.5, 1.0), 1.0);
+        assert!(smith_masking(0.5, 0.1) < smith_masking(0.1, 0.1));
+    }
+
+    #[test]
+    //Tests lighting rough plastic and smooth metal with the microfacet model
+    fn microfacet_lighting_test() {
+        let light: Box<dyn Light> = Box::new(PointLight::new(WHITE, Vec4::new(0.0, 0.0, -10.0, 1.0)));
+        let point = Vec4::new(0.0, 0.0, 0.0, 1.0);
+        let n_vec = Vec4::new(0.0, 0.0, -1.0, 0.0);
+        let mut material = Material::default();
+        material.model = ReflectionModel::Microfacet;
+        material.ambient = 0.0;
+        material.roughness = 1.0;
+        //Rough plastic facing the light is lit about as much as a Phong surface with a diffuse of 1
+        let e_vec = Vec4::new(0.0, 0.0, -1.0, 0.0);
+        let plastic = lighting(&material, &Matrix4x4::identity(), &light, &point, &e_vec, &n_vec, 1.0, &vec![], None);
+        assert!(plastic.0 > 0.9 && plastic.0 < 1.05);
+        assert_eq!(plastic.0, plastic.2);
+
+        //Smooth metal only reflects light tinted by its color, so it is dark away from the highlight
+        material.color = Color(1.0, 0.5, 0.0);
+        material.metallic = 1.0;
+        material.roughness = 0.2;
+        let highlight = lighting(&material, &Matrix4x4::identity(), &light, &point, &e_vec, &n_vec, 1.0, &vec![], None);
+        assert!(highlight.0 > 1.0);
+        assert_eq!(highlight.2, 0.0);
+        assert!((highlight.1 / highlight.0 - 0.5).abs() < 0.01);
+        let off_axis = Vec4::new(0.0, 0.8, -0.6, 0.0);
+        let dark = lighting(&material, &Matrix4x4::identity(), &light, &point, &off_axis, &n_vec, 1.0, &vec![], None);
+        assert!(dark.0 < 0.05);
+    }
+
+    //Creates a scene with a surface at y = 0 and a small glowing sphere centered on a point
+    fn glossy_test_scene(surface: Material, glow_center: (f32, f32, f32)) -> Scene {
+        let mut glow = Material::default();
+        glow.ambient = 0.0;
+        glow.diffuse = 0.0;
+        glow.specular = 0.0;
+        glow.emission = WHITE;
+        let (x, y, z) = glow_center;
+        let mut scene = Scene::new();
+        scene.objects.push(Box::new(Plane::new(Matrix4x4::identity(), surface)));
+        scene.objects.push(Box::new(Sphere::new(Matrix4x4::translation(x, y, z) * Matrix4x4::scaling(0.3, 0.3, 0.3), glow)));
+        scene
+    }
+
+    //Finds the shading variables where a ray hits the surface of a glossy test scene
+    fn glossy_test_comps(scene: &Scene, ray: &Ray) -> Comp {
+        let intersections = Ray::intersect_scene(scene, ray.clone());
+        Comp::compute_vars(Intersection::hit(&intersections).unwrap(), ray, &intersections)
+    }
+
+    #[test]
+    //Tests that rough surfaces spread their reflections around the perfect reflection
+    fn glossy_reflection() {
+        let mut surface = Material::default();
+        surface.reflectivity = 1.0;
+        let ray = Ray::new((0.0, 1.0, -1.0), (0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2));
+        let scene = glossy_test_scene(surface.clone(), (0.0, 1.0, 1.0));
+        let comps = glossy_test_comps(&scene, &ray);
+        assert_eq!(specular_color(&scene, &comps, &*comps.bsdf(), 5), WHITE);
+
+        surface.roughness = 0.6;
+        surface.glossy_samples = 64;
+        let scene = glossy_test_scene(surface, (0.0, 1.0, 1.0));
+        let comps = glossy_test_comps(&scene, &ray);
+        let color = specular_color(&scene, &comps, &*comps.bsdf(), 5);
+        assert!(color.0 > 0.05 && color.0 < 0.95);
+        assert_eq!(specular_color(&scene, &comps, &*comps.bsdf(), 0), BLACK);
+    }
+
+    #[test]
+    //Tests that frosted glass blurs what is seen through it
+    fn frosted_glass() {
+        let mut surface = Material::default();
+        surface.transparency = 1.0;
+        surface.refractive_index = 1.5;
+        let ray = Ray::new((0.0, 2.0, 0.0), (0.0, -1.0, 0.0));
+        let scene = glossy_test_scene(surface.clone(), (0.0, -2.0, 0.0));
+        let comps = glossy_test_comps(&scene, &ray);
+        assert_eq!(specular_color(&scene, &comps, &*comps.bsdf(), 5), WHITE);
+
+        surface.roughness = 0.6;
+        surface.glossy_samples = 64;
+        let scene = glossy_test_scene(surface, (0.0, -2.0, 0.0));
+        let comps = glossy_test_comps(&scene, &ray);
+        let color = specular_color(&scene, &comps, &*comps.bsdf(), 5);
+        assert!(color.0 > 0.05 && color.0 < 0.95);
+    }
+
+    #[test]
+    //Tests that emissive objects with finite bounds become lights sampled on their surface
+    fn object_light_surface() {
+        let mut glow = Material::default();
+        glow.emission = Color::new(2.0, 2.0, 2.0);
+        let sphere = Sphere::new(Matrix4x4::translation(0.0, 3.0, 0.0) * Matrix4x4::scaling(2.0, 2.0, 2.0), glow.clone());
+        let light = ObjectLight::new(Box::new(sphere), 16).unwrap();
+        assert_eq!(light.get_intensity(), &Color::new(2.0, 2.0, 2.0));
+        assert_eq!(light.get_position(), &Vec4::new(0.0, 3.0, 0.0, 1.0));
+        assert_eq!(light.get_positions().len(), 16);
+        for position in light.get_positions() {
+            assert!((Vec4::magnitude(&(&position - light.get_position())) - 2.0).abs() < 0.01);
+        }
+        //Only points on the side facing the target are picked
+        let target = Vec4::new(0.0, 10.0, 0.0, 1.0);
+        for _ in 0..16 {
+            assert!(light.sample_point(&target).1 > 3.0);
+        }
+
+        //A quad made of two triangles in a group
+        let mut quad = Group::new(Matrix4x4::translation(0.0, 2.0, 0.0), glow.clone());
+        let corners = [
+            Vec4::new(-1.0, 0.0, -1.0, 1.0),
+            Vec4::new(1.0, 0.0, -1.0, 1.0),
+            Vec4::new(1.0, 0.0, 1.0, 1.0),
+            Vec4::new(-1.0, 0.0, 1.0, 1.0),
+        ];
+        quad.add_object(Box::new(Triangle::new(corners[0].clone(), corners[1].clone(), corners[2].clone(), glow.clone())));
+        quad.add_object(Box::new(Triangle::new(corners[0].clone(), corners[2].clone(), corners[3].clone(), glow.clone())));
+        let light = ObjectLight::new(Box::new(quad), 16).unwrap();
+        for position in light.get_positions() {
+            assert!((position.1 - 2.0).abs() < 0.01);
+            assert!(position.0.abs() <= 1.0 && position.2.abs() <= 1.0);
+        }
+
+        assert!(ObjectLight::new(Box::new(Plane::new(Matrix4x4::identity(), glow)), 16).is_none());
+        assert!(ObjectLight::new(Box::new(Sphere::new(Matrix4x4::identity(), Material::default())), 16).is_none());
+    }
+
+    #[test]
+    //Tests that emissive objects in a scene light it with soft shadows
+    fn object_light_soft_shadows() {
+        reseed(5);
+        let mut glow = Material::default();
+        glow.emission = WHITE;
+        let mut scene = Scene::new();
+        scene.objects.push(Box::new(Plane::new(Matrix4x4::identity(), Material::default())));
+        scene.objects.push(Box::new(Sphere::new(Matrix4x4::translation(0.0, 4.0, 0.0), glow)));
+        scene.objects.push(Box::new(Sphere::new(Matrix4x4::translation(0.0, 2.0, 0.0) * Matrix4x4::scaling(0.3, 0.3, 0.3), Material::default())));
+        scene.add_object_lights();
+        assert_eq!(scene.light_sources.len(), 1);
+        let light = &scene.light_sources[0];
+        assert!(light.emitter().is_some());
+        assert_eq!(light.light_intensity(&Vec4::new(6.0, 0.01, 0.0, 1.0), &scene, 0.0), 1.0);
+        let penumbra = light.light_intensity(&Vec4::new(0.0, 0.01, 0.0, 1.0), &scene, 0.0);
+        assert!(penumbra > 0.0 && penumbra < 1.0);
+    }
+
+    #[test]
+    //Tests that spot lights only light points inside their cone, fading out across the falloff
+    fn spot_light_cone() {
+        let light = SpotLight::new(WHITE, Vec4::new(0.0, 1.0, 0.0, 1.0), Vec4::new(0.0, -2.0, 0.0, 0.0), 45.0, 10.0);
+        assert_eq!(light.direction, Vec4::new(0.0, -1.0, 0.0, 0.0));
+        assert_eq!(light.get_positions(), vec![Vec4::new(0.0, 1.0, 0.0, 1.0)]);
+        assert_eq!(light.attenuation(&Vec4::new(0.0, 0.0, 0.0, 1.0)), 1.0);
+        assert_eq!(light.attenuation(&Vec4::new(2.0, 0.0, 0.0, 1.0)), 0.0);
+        //A point 40 degrees from the direction is halfway across the falloff
+        let halfway = light.attenuation(&Vec4::new(40.0_f32.to_radians().tan(), 0.0, 0.0, 1.0));
+        assert!((halfway - 0.5).abs() < 0.001);
+        assert_eq!(SpotLight::new(WHITE, Vec4::new(0.0, 1.0, 0.0, 1.0), Vec4::new(0.0, -1.0, 0.0, 0.0), 20.0, 30.0).falloff, 20.0);
+
+        //Surfaces outside the cone only get ambient light
+        let material = Material::default();
+        let light: Box<dyn Light> = Box::new(light);
+        let n_vec = Vec4::new(0.0, 1.0, 0.0, 0.0);
+        let e_vec = Vec4::new(0.0, 1.0, 0.0, 0.0);
+        let inside = lighting(&material, &Matrix4x4::identity(), &light, &Vec4::new(0.0, 0.0, 0.0, 1.0), &e_vec, &n_vec, 1.0, &vec![], None);
+        let outside = lighting(&material, &Matrix4x4::identity(), &light, &Vec4::new(2.0, 0.0, 0.0, 1.0), &e_vec, &n_vec, 1.0, &vec![], None);
+        assert_eq!(inside, Color::new(1.9, 1.9, 1.9));
+        assert_eq!(outside, Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    //Tests that directional lights cast parallel shadows, which are soft when the light has an angular diameter
+    fn directional_light_shadows() {
+        reseed(9);
+        let mut scene = Scene::new();
+        scene.objects.push(Box::new(Plane::new(Matrix4x4::identity(), Material::default())));
+        scene.objects.push(Box::new(Sphere::new(Matrix4x4::translation(0.0, 50.0, 0.0), Material::default())));
+        let sun = DirectionalLight::new(WHITE, Vec4::new(0.0, -1.0, 0.0, 0.0), 0.0, 16);
+        assert_eq!(sun.get_positions(), vec![Vec4::new(0.0, SUN_DISTANCE, 0.0, 1.0)]);
+        //The shadow is as wide as the sphere however far away it is
+        assert_eq!(sun.light_intensity(&Vec4::new(0.9, 0.01, 0.0, 1.0), &scene, 0.0), 0.0);
+        assert_eq!(sun.light_intensity(&Vec4::new(1.1, 0.01, 0.0, 1.0), &scene, 0.0), 1.0);
+        assert_eq!(sun.sample_point(&Vec4::new(3.0, 0.0, 0.0, 1.0)), Vec4::new(3.0, SUN_DISTANCE, 0.0, 1.0));
+
+        //A sun 4 degrees across is only partly hidden behind a sphere 2.3 degrees across
+        let sun = DirectionalLight::new(WHITE, Vec4::new(0.0, -1.0, 0.0, 0.0), 4.0, 64);
+        let penumbra = sun.light_intensity(&Vec4::new(0.0, 0.01, 0.0, 1.0), &scene, 0.0);
+        assert!(penumbra > 0.0 && penumbra < 1.0);
+        assert_eq!(sun.light_intensity(&Vec4::new(5.0, 0.01, 0.0, 1.0), &scene, 0.0), 1.0);
+    }
 }
@@ -246,4 +246,28 @@ mod tests {
         );
         assert_eq!(transform.round(), expected_result.round());
     }
+
+    #[test]
+    //Tests blending two matrices
+    fn matrix_lerp() {
+        let start = Matrix4x4::translation(2.0, 0.0, 0.0);
+        let end = Matrix4x4::scaling(3.0, 1.0, 1.0);
+        assert_eq!(start.lerp(&end, 0.0), start);
+        assert_eq!(start.lerp(&end, 1.0), end);
+        assert_eq!(
+            start.lerp(&end, 0.5),
+            Matrix4x4::new((2.0, 0.0, 0.0, 1.0), (0.0, 1.0, 0.0, 0.0), (0.0, 0.0, 1.0, 0.0), (0.0, 0.0, 0.0, 1.0))
+        );
+    }
+
+    #[test]
+    //Tests that the affine inverse matches the general inverse
+    fn affine_inverse() {
+        let matrix = Matrix4x4::translation(1.0, -2.0, 3.0)
+            * Matrix4x4::rotation(Axis::Y, 30.0)
+            * Matrix4x4::scaling(2.0, 0.5, 1.5)
+            * Matrix4x4::shearing(0.5, 0.0, 0.0, 0.2, 0.0, 0.0);
+        assert_eq!(matrix.affine_inverse().unwrap().round(), matrix.inverse().unwrap().round());
+        assert_eq!(Matrix4x4::scaling(0.0, 1.0, 1.0).affine_inverse(), None);
+    }
 }
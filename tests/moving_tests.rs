@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+    use rust_ray_tracer::core::comp::Comp;
+    use rust_ray_tracer::core::matrix::Matrix4x4;
+    use rust_ray_tracer::core::vector::Vec4;
+    use rust_ray_tracer::materials::material::Material;
+    use rust_ray_tracer::objects::group::Group;
+    use rust_ray_tracer::objects::moving::*;
+    use rust_ray_tracer::objects::object::*;
+    use rust_ray_tracer::objects::sphere::Sphere;
+    use rust_ray_tracer::ray_tracing::intersection::Intersection;
+    use rust_ray_tracer::ray_tracing::ray::Ray;
+
+    //Creates a unit sphere which moves from the origin to x = 4 between times 0 and 1
+    fn moving_sphere() -> Moving {
+        Moving::new(
+            Box::new(Sphere::default()),
+            MotionPath::linear(Matrix4x4::identity(), Matrix4x4::translation(4.0, 0.0, 0.0)),
+        )
+    }
+
+    #[test]
+    //Tests blending transforms between keyframes
+    fn motion_path() {
+        let path = MotionPath::new(vec![
+            (2.0, Matrix4x4::translation(0.0, 4.0, 0.0)),
+            (0.0, Matrix4x4::identity()),
+            (1.0, Matrix4x4::translation(2.0, 0.0, 0.0)),
+        ]);
+        assert_eq!(path.track.keys[0].time, 0.0);
+        assert_eq!(path.at(-1.0), Matrix4x4::identity());
+        assert_eq!(path.at(0.5), Matrix4x4::translation(1.0, 0.0, 0.0));
+        assert_eq!(path.at(1.5).round(), Matrix4x4::translation(1.0, 2.0, 0.0).round());
+        assert_eq!(path.at(3.0), Matrix4x4::translation(0.0, 4.0, 0.0));
+        assert_eq!(MotionPath::new(vec![]).at(1.0), Matrix4x4::identity());
+    }
+
+    #[test]
+    //Tests that rays at different times see the object in different places
+    fn intersect_at_time() {
+        let sphere = moving_sphere();
+        let ray = Ray::new((4.0, 0.0, -5.0), (0.0, 0.0, 1.0));
+        assert!(sphere.intersect(&ray).is_none());
+        let intersections = sphere.intersect(&ray.clone().with_time(1.0)).unwrap();
+        assert_eq!(intersections[0].t, 4.0);
+        assert_eq!(intersections[0].normal, Vec4::new(0.0, 0.0, -1.0, 0.0));
+        assert_eq!(intersections[0].hit, Vec4::new(4.0, 0.0, -1.0, 1.0));
+        let halfway = sphere.intersect(&Ray::new((2.0, 0.0, -5.0), (0.0, 0.0, 1.0)).with_time(0.5)).unwrap();
+        assert_eq!(halfway[0].t, 4.0);
+    }
+
+    #[test]
+    //Tests that the normal is found where the object is when the shutter opens
+    fn normal_at_shutter_open() {
+        let sphere = Moving::new(
+            Box::new(Sphere::default()),
+            MotionPath::linear(Matrix4x4::translation(2.0, 0.0, 0.0), Matrix4x4::translation(4.0, 0.0, 0.0)),
+        );
+        assert_eq!(sphere.normal(&Vec4::new(2.0, 1.0, 0.0, 1.0), None, None), Vec4::new(0.0, 1.0, 0.0, 0.0));
+        assert_eq!(sphere.normal(&Vec4::new(3.0, 0.0, 0.0, 1.0), None, None), Vec4::new(1.0, 0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    //Tests that the bounds cover the whole path
+    fn moving_bounds() {
+        let bounds = moving_sphere().parent_space_bounds();
+        assert_eq!(bounds.min, Vec4::new(-1.0, -1.0, -1.0, 1.0));
+        assert_eq!(bounds.max, Vec4::new(5.0, 1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    //Tests that a moving object inside a group matches a still object in the same place
+    fn moving_in_group() {
+        let mut moving_group = Group::new(Matrix4x4::scaling(2.0, 2.0, 2.0), Material::default());
+        moving_group.add_object(Box::new(Moving::new(
+            Box::new(Sphere::new(Matrix4x4::translation(0.0, 1.0, 0.0), Material::default())),
+            MotionPath::linear(Matrix4x4::identity(), Matrix4x4::translation(2.0, 0.0, 0.0)),
+        )));
+        let mut still_group = Group::new(Matrix4x4::scaling(2.0, 2.0, 2.0), Material::default());
+        still_group.add_object(Box::new(Sphere::new(Matrix4x4::translation(1.0, 1.0, 0.0), Material::default())));
+
+        let ray = Ray::new((2.5, 2.5, -10.0), (0.0, 0.0, 1.0)).with_time(0.5);
+        let moving = moving_group.intersect(&ray).unwrap();
+        let still = still_group.intersect(&ray).unwrap();
+        assert_eq!(moving.len(), still.len());
+        for (a, b) in moving.iter().zip(still.iter()) {
+            assert!((a.t - b.t).abs() < 0.0001);
+            assert_eq!(a.normal.round(), b.normal.round());
+        }
+    }
+
+    #[test]
+    //Tests that shading uses the object's transform at the ray's time
+    fn comps_at_time() {
+        let sphere = moving_sphere();
+        let ray = Ray::new((4.0, 0.0, -5.0), (0.0, 0.0, 1.0)).with_time(1.0);
+        let intersections = sphere.intersect(&ray).unwrap();
+        let comps = Comp::compute_vars(intersections[0].clone(), &ray, &intersections);
+        assert_eq!(comps.time, 1.0);
+        assert_eq!(comps.parent_inverses, vec![Matrix4x4::translation(-4.0, 0.0, 0.0)]);
+        assert_eq!(world_to_object(&comps.parent_inverses, &comps.point).round(), Vec4::new(0.0, 0.0, -1.0, 1.0));
+        assert_eq!(Intersection::hit(&intersections).unwrap().t, 4.0);
+    }
+}
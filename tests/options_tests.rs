@@ -0,0 +1,177 @@
+#[cfg(test)]
+mod tests {
+    use rust_ray_tracer::image::tone::ToneMap;
+    use rust_ray_tracer::misc::options::RenderOptions;
+    use rust_ray_tracer::world::camera::{Projection, Tile};
+    use rust_ray_tracer::world::integrator::IntegratorKind;
+    use rust_ray_tracer::world::sampler::*;
+
+    //Splits a command line into arguments
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    //Tests the default options
+    fn default_options() {
+        let options = RenderOptions::parse(&args("scene.scene")).unwrap();
+        assert_eq!(options.scene_path, "scene.scene");
+        assert_eq!(options.output_path, "image.ppm");
+        assert_eq!(options.bit_depth, 8);
+        assert!(options.tone.is_identity());
+        assert_eq!(options.supersample, 1);
+        assert_eq!(options.depth, 5);
+        assert_eq!(options.threads, 0);
+        assert_eq!(options.width, None);
+        assert!(!options.quick);
+    }
+
+    #[test]
+    //Tests parsing every option
+    fn all_options() {
+        let line = "-o out.png --bit-depth 16 --exposure -1.5 --tonemap aces --srgb --width 640 --height 480 --fov 60 -s 3 -d 2 -t 8 -q scene.scene";
+        let options = RenderOptions::parse(&args(line)).unwrap();
+        assert_eq!(options.output_path, "out.png");
+        assert_eq!(options.bit_depth, 16);
+        assert_eq!(options.tone.exposure, -1.5);
+        assert_eq!(options.tone.tone_map, ToneMap::Aces);
+        assert!(options.tone.srgb);
+        assert_eq!(options.width, Some(640));
+        assert_eq!(options.height, Some(480));
+        assert_eq!(options.fov, Some(60.0));
+        assert_eq!(options.supersample, 3);
+        assert_eq!(options.depth, 2);
+        assert_eq!(options.threads, 8);
+        assert!(options.quick);
+    }
+
+    #[test]
+    //Tests the stochastic sampling options
+    fn sampling_options() {
+        let options = RenderOptions::parse(&args("--samples 8 --filter gaussian --sampler random --seed 9 a.scene")).unwrap();
+        assert_eq!(options.samples, Some(8));
+        assert_eq!(options.sampler.samples, 8);
+        assert_eq!(options.sampler.filter, Filter::Gaussian);
+        assert_eq!(options.sampler.pattern, SamplePattern::Random);
+        assert_eq!(options.sampler.seed, 9);
+        assert!(RenderOptions::parse(&args("--filter gaussian a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--samples 4 -s 2 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--samples 4 --filter sinc a.scene")).is_err());
+    }
+
+    #[test]
+    //Tests the projection option
+    fn projection_option() {
+        let options = RenderOptions::parse(&args("--projection fisheye --fov 220 a.scene")).unwrap();
+        assert_eq!(options.projection, Some(Projection::Fisheye));
+        assert_eq!(options.fov, Some(220.0));
+        assert_eq!(RenderOptions::parse(&args("a.scene")).unwrap().projection, None);
+    }
+
+    #[test]
+    //Tests the lens options
+    fn lens_options() {
+        let options = RenderOptions::parse(&args("--aperture 0.1 --focal-distance 4 --samples 16 a.scene")).unwrap();
+        assert_eq!(options.aperture, Some(0.1));
+        assert_eq!(options.focal_distance, Some(4.0));
+        assert!(RenderOptions::parse(&args("--aperture -0.1 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--focal-distance 0 a.scene")).is_err());
+    }
+
+    #[test]
+    //Tests the adaptive anti-aliasing options
+    fn adaptive_options() {
+        let options = RenderOptions::parse(&args("--adaptive 0.05 --adaptive-depth 3 a.scene")).unwrap();
+        assert_eq!(options.adaptive, Some(AdaptiveSettings { threshold: 0.05, max_depth: 3 }));
+        let options = RenderOptions::parse(&args("--adaptive 0.2 a.scene")).unwrap();
+        assert_eq!(options.adaptive.unwrap().max_depth, 2);
+        assert_eq!(RenderOptions::parse(&args("a.scene")).unwrap().adaptive, None);
+        assert!(RenderOptions::parse(&args("--adaptive-depth 3 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--adaptive 0.1 -s 2 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--adaptive -1 a.scene")).is_err());
+    }
+
+    #[test]
+    //Tests that bad arguments produce errors
+    fn invalid_options() {
+        assert!(RenderOptions::parse(&args("")).is_err());
+        assert!(RenderOptions::parse(&args("a.scene b.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--width ten a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--width 0 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--fov 400 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--fov nan a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--exposure inf a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--focal-distance inf a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--adaptive nan a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--projection cylindrical a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--bit-depth 12 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--tonemap filmic a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("a.scene --output")).is_err());
+        assert!(RenderOptions::parse(&args("--bogus a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--help")).unwrap().help);
+    }
+
+    #[test]
+    //Tests the animation frame count option
+    fn frames_option() {
+        assert_eq!(RenderOptions::parse(&args("--frames 24 a.scene")).unwrap().frames, Some(24));
+        assert_eq!(RenderOptions::parse(&args("a.scene")).unwrap().frames, None);
+        assert!(RenderOptions::parse(&args("--frames 0 a.scene")).is_err());
+    }
+
+    #[test]
+    //Tests the progressive rendering options
+    fn progressive_options() {
+        let options = RenderOptions::parse(&args("--progressive 64 --snapshot 2.5 --seed 3 a.scene")).unwrap();
+        let progressive = options.progressive.unwrap();
+        assert_eq!(progressive.sampler.samples, 64);
+        assert_eq!(progressive.sampler.seed, 3);
+        assert_eq!(progressive.snapshot_interval, 2.5);
+        assert_eq!(progressive.time_limit, None);
+        let options = RenderOptions::parse(&args("--progressive 4 --time-limit 60 a.scene")).unwrap();
+        assert_eq!(options.progressive.unwrap().time_limit, Some(60.0));
+        assert!(RenderOptions::parse(&args("--progressive 4 --samples 4 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--time-limit 60 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--progressive 4 --snapshot -1 a.scene")).is_err());
+    }
+
+    #[test]
+    //Tests the checkpoint options
+    fn checkpoint_options() {
+        let options = RenderOptions::parse(&args("--checkpoint render.ckpt --checkpoint-interval 5 a.scene")).unwrap();
+        assert_eq!(options.checkpoint, Some(String::from("render.ckpt")));
+        assert_eq!(options.checkpoint_interval, 5.0);
+        assert_eq!(RenderOptions::parse(&args("--checkpoint c a.scene")).unwrap().checkpoint_interval, 60.0);
+        assert!(RenderOptions::parse(&args("--checkpoint-interval 5 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--checkpoint c -q a.scene")).is_err());
+    }
+
+    #[test]
+    //Tests the crop window options
+    fn crop_options() {
+        let options = RenderOptions::parse(&args("--crop 10,20,30,40 --cropped a.scene")).unwrap();
+        assert_eq!(options.crop, Some(Tile { x: 10, y: 20, width: 30, height: 40 }));
+        assert!(options.cropped);
+        assert!(!RenderOptions::parse(&args("--crop 0,0,1,1 a.scene")).unwrap().cropped);
+        assert!(RenderOptions::parse(&args("--crop 10,20,30 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--crop 10,20,0,40 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--crop -1,20,5,5 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--crop 4294967295,0,5,5 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--crop 0,2147483648,5,5 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--crop 10,0,2147483647,1 a.scene")).is_ok());
+        assert!(RenderOptions::parse(&args("--cropped a.scene")).is_err());
+    }
+
+    #[test]
+    //Tests choosing an integrator and the options it can not be used with
+    fn integrator_option() {
+        assert_eq!(RenderOptions::parse(&args("a.scene")).unwrap().integrator, IntegratorKind::Whitted);
+        let options = RenderOptions::parse(&args("--integrator path --samples 64 a.scene")).unwrap();
+        assert_eq!(options.integrator, IntegratorKind::PathTracer);
+        assert_eq!(RenderOptions::parse(&args("--integrator ao a.scene")).unwrap().integrator, IntegratorKind::AmbientOcclusion);
+        assert!(RenderOptions::parse(&args("--integrator phong a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--integrator normals --quick a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--integrator path --adaptive 0.1 a.scene")).is_err());
+        assert!(RenderOptions::parse(&args("--integrator depth --adaptive 0.1 a.scene")).is_ok());
+    }
+}
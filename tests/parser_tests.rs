@@ -8,7 +8,7 @@ mod tests {
     //Tests obj vertex parsing
     fn obj_vertex_parsing() {
         let file = File::open("tests/test1.obj");
-        let result = Parser::parse_obj(file.unwrap());
+        let result = Parser::parse_obj(file.unwrap()).unwrap();
         assert_eq!(result.vertices.len(), 4);
     }
 
@@ -16,7 +16,7 @@ mod tests {
     //Tests obj triangle parsing
     fn obj_triangle_parsing() {
         let file = File::open("tests/test1.obj");
-        let result = Parser::parse_obj(file.unwrap());
+        let result = Parser::parse_obj(file.unwrap()).unwrap();
         assert_eq!(&result.triangles.len(), &2);
         let t1 = result.triangles[0].clone();
         let t2 = result.triangles[1].clone();
@@ -32,7 +32,7 @@ mod tests {
     //Tests obj polygon parsing
     fn obj_polygon_parsing() {
         let file = File::open("tests/test2.obj");
-        let result = Parser::parse_obj(file.unwrap());
+        let result = Parser::parse_obj(file.unwrap()).unwrap();
         assert_eq!(&result.triangles.len(), &3);
         let t1 = result.triangles[0].clone();
         let t2 = result.triangles[1].clone();
@@ -51,7 +51,7 @@ mod tests {
     #[test]
     fn smooth_obj_parsing() {
         let file = File::open("tests/test3.obj");
-        let result = Parser::parse_obj(file.unwrap());
+        let result = Parser::parse_obj(file.unwrap()).unwrap();
         assert_eq!(&result.smooth_triangles.len(), &2);
         let t1 = result.smooth_triangles[0].clone();
         let t2 = result.smooth_triangles[1].clone();
@@ -63,4 +63,13 @@ mod tests {
         assert_eq!(&t1.n3, &result.normals[1]);
         assert_eq!(t1, t2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    //Tests that malformed obj files return the line of the error
+    fn obj_errors() {
+        let error = Parser::parse_obj(File::open("tests/bad1.obj").unwrap()).err().unwrap();
+        assert_eq!(error.line, 3);
+        let error = Parser::parse_obj(File::open("tests/bad2.obj").unwrap()).err().unwrap();
+        assert_eq!(error.line, 5);
+    }
+}
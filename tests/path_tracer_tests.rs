@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use rust_ray_tracer::core::color::*;
+    use rust_ray_tracer::core::matrix::Matrix4x4;
+    use rust_ray_tracer::core::vector::Vec4;
+    use rust_ray_tracer::materials::material::Material;
+    use rust_ray_tracer::misc::random::*;
+    use rust_ray_tracer::objects::plane::Plane;
+    use rust_ray_tracer::objects::sphere::Sphere;
+    use rust_ray_tracer::ray_tracing::ray::Ray;
+    use rust_ray_tracer::world::lighting::*;
+    use rust_ray_tracer::world::path_tracer::*;
+    use rust_ray_tracer::world::scene::Scene;
+
+    //Averages the colors of many paths along a ray
+    fn average_path_color(ray: &Ray, scene: &Scene, paths: usize, max_bounces: i32) -> Color {
+        let mut total = BLACK;
+        for _ in 0..paths {
+            total = total + path_color(ray.clone(), scene, max_bounces).unwrap();
+        }
+        total * (1.0 / paths as f32)
+    }
+
+    #[test]
+    //Tests that emissive surfaces are seen by both the path tracer and compute_color, and that misses give None
+    fn emissive_surface() {
+        let mut material = Material::default();
+        material.diffuse = 0.0;
+        material.emission = Color(2.0, 1.0, 0.5);
+        let mut scene = Scene::new();
+        scene.objects.push(Box::new(Sphere::new(Matrix4x4::identity(), material)));
+        let ray = Ray::new((0.0, 0.0, -5.0), (0.0, 0.0, 1.0));
+        assert_eq!(path_color(ray.clone(), &scene, 5), Some(Color(2.0, 1.0, 0.5)));
+        assert_eq!(Scene::compute_color(ray, &scene, 5), Some(Color(2.0, 1.0, 0.5)));
+        assert_eq!(path_color(Ray::new((0.0, 5.0, -5.0), (0.0, 0.0, 1.0)), &scene, 5), None);
+    }
+
+    #[test]
+    //Tests that light bouncing around inside a glowing sphere adds up to the amount it should
+    fn indirect_light_converges() {
+        reseed(7);
+        let mut material = Material::default();
+        material.diffuse = 0.5;
+        material.emission = WHITE;
+        let mut scene = Scene::new();
+        scene.objects.push(Box::new(Sphere::new(Matrix4x4::identity(), material)));
+        //Every bounce returns half of the light, so the total is 1 + 1/2 + 1/4 + ... = 2
+        let color = average_path_color(&Ray::new((0.0, 0.0, 0.0), (0.0, 0.0, 1.0)), &scene, 4000, 1000);
+        assert!((color.0 - 2.0).abs() < 0.1);
+    }
+
+    #[test]
+    //Tests that light bounced off a red object tints a white floor next to it
+    fn color_bleeding() {
+        reseed(3);
+        let mut red = Material::default();
+        red.color = Color(1.0, 0.0, 0.0);
+        let mut scene = Scene::new();
+        scene.light_sources.push(Box::new(PointLight::new(WHITE, Vec4::new(0.0, 10.0, 0.0, 1.0))));
+        scene.objects.push(Box::new(Plane::new(Matrix4x4::identity(), Material::default())));
+        scene.objects.push(Box::new(Sphere::new(Matrix4x4::translation(1.5, 1.0, 0.0), red)));
+        let ray = Ray::new((0.2, 5.0, 0.0), (0.0, -1.0, 0.0));
+        let whitted = Scene::compute_color(ray.clone(), &scene, 5).unwrap();
+        assert_eq!(whitted.0, whitted.1);
+        let color = average_path_color(&ray, &scene, 1000, 5);
+        assert!(color.0 > color.1 * 1.05);
+        assert!((color.1 - color.2).abs() < 0.05);
+    }
+
+    #[test]
+    //Tests that light from emissive objects which are lights in the scene is only counted once
+    fn object_lights_counted_once() {
+        reseed(11);
+        let mut glow = Material::default();
+        glow.emission = WHITE;
+        let mut scene = Scene::new();
+        scene.objects.push(Box::new(Plane::new(Matrix4x4::identity(), Material::default())));
+        scene.objects.push(Box::new(Sphere::new(Matrix4x4::translation(0.0, 4.0, 0.0), glow)));
+        scene.add_object_lights();
+        //The floor beneath the lamp is lit by about its full intensity scaled by the diffuse of 0.9, and counting the lamp
+        //again where bounces hit it would add about 0.05
+        let ray = Ray::new((0.0, 1.0, -5.0), (0.0, -1.0, 5.0));
+        let color = average_path_color(&ray, &scene, 500, 5);
+        assert!(color.0 > 0.85 && color.0 < 0.935);
+        //The lamp itself is still seen
+        let lamp = path_color(Ray::new((0.0, 4.0, -5.0), (0.0, 0.0, 1.0)), &scene, 5).unwrap();
+        assert!(lamp.0 >= 1.0);
+    }
+
+    #[test]
+    //Tests that emissive objects without an ObjectLight still light the scene along bounces
+    fn emissive_plane_without_light() {
+        reseed(13);
+        let mut glow = Material::default();
+        glow.diffuse = 0.0;
+        glow.emission = WHITE;
+        let mut scene = Scene::new();
+        scene.objects.push(Box::new(Plane::new(Matrix4x4::identity(), glow.clone())));
+        scene.objects.push(Box::new(Sphere::new(Matrix4x4::translation(0.0, 2.0, 0.0), Material::default())));
+        scene.objects.push(Box::new(Sphere::new(Matrix4x4::translation(20.0, 20.0, 20.0) * Matrix4x4::scaling(0.1, 0.1, 0.1), glow)));
+        scene.add_object_lights();
+        assert_eq!(scene.light_sources.len(), 1);
+        //The lower side of the sphere faces away from the small lamp and only sees the glowing floor
+        let color = average_path_color(&Ray::new((0.0, 1.5, -5.0), (0.0, 0.0, 1.0)), &scene, 500, 5);
+        assert!(color.0 > 0.2);
+    }
+}
@@ -34,4 +34,13 @@ mod tests {
         let vector = Object::normal(&s, &Vec4::new(1.0, 0.0, 0.0, 1.0), None, None);
         assert_eq!(vector, Vec4::new(1.0, 0.0, 0.0, 0.0))
     }
-}
\ No newline at end of file
+
+    #[test]
+    //Tests that a ray keeps its time when transformed
+    fn ray_time() {
+        let ray = Ray::new((1.0, 2.0, 3.0), (0.0, 1.0, 0.0));
+        assert_eq!(ray.time, 0.0);
+        let ray = ray.with_time(0.25);
+        assert_eq!(Ray::transform(&ray, &Matrix4x4::translation(1.0, 0.0, 0.0)).time, 0.25);
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    use rust_ray_tracer::core::color::Color;
+    use rust_ray_tracer::misc::random::*;
+    use rust_ray_tracer::world::sampler::*;
+
+    #[test]
+    //Tests the shape of each reconstruction filter
+    fn filter_weights() {
+        assert_eq!(Filter::Box.weight(0.4, -0.4), 1.0);
+        assert_eq!(Filter::Box.weight(0.6, 0.0), 0.0);
+        assert_eq!(Filter::Tent.weight(0.5, 0.0), 0.5);
+        assert_eq!(Filter::Tent.weight(0.5, 0.5), 0.25);
+        assert!(Filter::Gaussian.weight(0.0, 0.0) > Filter::Gaussian.weight(0.5, 0.0));
+        assert_eq!(Filter::Gaussian.weight(1.5, 0.0), 0.0);
+        assert!((Filter::Mitchell.weight(0.0, 0.0) - (8.0f32 / 9.0).powi(2)).abs() < 0.0001);
+        assert!(Filter::Mitchell.weight(1.5, 0.0) < 0.0);
+        assert!(Filter::Mitchell.weight(2.0, 0.0).abs() < 0.0001);
+        assert_eq!(Filter::from_name("mitchell"), Some(Filter::Mitchell));
+        assert_eq!(Filter::from_name("lanczos"), None);
+    }
+
+    #[test]
+    //Tests that stratified samples land one per cell inside the filter footprint
+    fn stratified_offsets() {
+        let settings = SamplerSettings::new(16);
+        let offsets = settings.offsets(3, 4);
+        assert_eq!(offsets.len(), 16);
+        let mut cells: Vec<(i32, i32)> = offsets
+            .iter()
+            .map(|(x, y)| {
+                assert!(x.abs() <= 0.5 && y.abs() <= 0.5);
+                (((x + 0.5) * 4.0).floor() as i32, ((y + 0.5) * 4.0).floor() as i32)
+            })
+            .collect();
+        cells.sort();
+        cells.dedup();
+        assert_eq!(cells.len(), 16);
+
+        //Counts which are not square still produce the requested number of samples
+        assert_eq!(SamplerSettings::new(5).offsets(0, 0).len(), 5);
+    }
+
+    #[test]
+    //Tests that samples only depend on the seed and the pixel
+    fn seeded_offsets() {
+        let mut settings = SamplerSettings::new(8);
+        settings.pattern = SamplePattern::Random;
+        settings.filter = Filter::Mitchell;
+        let first = settings.offsets(10, 20);
+        random_float();
+        assert_eq!(settings.offsets(10, 20), first);
+        assert!(first.iter().all(|(x, y)| x.abs() <= 2.0 && y.abs() <= 2.0));
+        assert_ne!(settings.offsets(11, 20), first);
+        settings.seed = 1;
+        assert_ne!(settings.offsets(10, 20), first);
+        assert_ne!(pixel_seed(0, 1, 0), pixel_seed(0, 0, 1));
+    }
+
+    #[test]
+    //Tests the contrast measure used by adaptive anti-aliasing
+    fn adaptive_contrast() {
+        let settings = AdaptiveSettings::default();
+        let dark = Color::new(0.2, 0.2, 0.2);
+        assert!((AdaptiveSettings::contrast(&dark, &Color::new(0.2, 0.5, 0.25)) - 0.3).abs() < 0.0001);
+        assert_eq!(AdaptiveSettings::contrast(&Color::new(1.0, 1.0, 1.0), &Color::new(4.0, 2.0, 1.0)), 0.0);
+        assert!(settings.differs(&dark, &Color::new(0.35, 0.2, 0.2)));
+        assert!(!settings.differs(&dark, &Color::new(0.25, 0.2, 0.2)));
+    }
+
+    #[test]
+    //Tests combining the samples of a pixel
+    fn sample_sums() {
+        let mut sum = SampleSum::default();
+        assert_eq!(sum.color(), Color::new(0.0, 0.0, 0.0));
+        sum.add(Color::new(1.0, 0.0, 0.0), 3.0);
+        let mut other = SampleSum::default();
+        other.add(Color::new(0.0, 1.0, 0.0), 1.0);
+        sum.merge(&other);
+        assert_eq!(sum.count, 2);
+        assert_eq!(sum.color(), Color::new(0.75, 0.25, 0.0));
+        let mut cancelled = SampleSum::default();
+        cancelled.add(Color::new(1.0, 1.0, 1.0), 1.0);
+        cancelled.add(Color::new(0.0, 0.0, 0.0), -1.0);
+        assert_eq!(cancelled.color(), Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    //Tests that progressive passes double the samples until the limit
+    fn progressive_passes() {
+        let mut settings = ProgressiveSettings::default();
+        settings.sampler.samples = 12;
+        assert_eq!(settings.pass_sizes(), vec![1, 1, 2, 4, 4]);
+        settings.sampler.samples = 1;
+        assert_eq!(settings.pass_sizes(), vec![1]);
+        assert_ne!(settings.pass_sampler(0, 1).seed, settings.pass_sampler(1, 1).seed);
+        assert_eq!(settings.pass_sampler(2, 2).samples, 2);
+    }
+}
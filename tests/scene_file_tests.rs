@@ -0,0 +1,354 @@
+#[cfg(test)]
+mod tests {
+    use rust_ray_tracer::core::color::Color;
+    use rust_ray_tracer::core::matrix::Matrix4x4;
+    use rust_ray_tracer::core::vector::Vec4;
+    use rust_ray_tracer::materials::material::ReflectionModel;
+    use rust_ray_tracer::objects::csg::*;
+    use rust_ray_tracer::objects::group::Group;
+    use rust_ray_tracer::objects::moving::Moving;
+    use rust_ray_tracer::objects::sphere::Sphere;
+    use rust_ray_tracer::objects::triangle::Triangle;
+    use rust_ray_tracer::world::camera::Projection;
+    use rust_ray_tracer::world::scene_file::*;
+    use std::path::Path;
+
+    //Parses scene text relative to the tests directory
+    fn parse(text: &str) -> Result<SceneFile, SceneError> {
+        SceneFile::parse(text, Path::new("tests"))
+    }
+
+    #[test]
+    //Tests loading the camera block
+    fn camera_block() {
+        let file = parse("camera\n  width 40\n  height 20\n  fov 60\n  from 0 1 -5\n  to 0 1 0\n  up 0 1 0\nend\n").unwrap();
+        assert_eq!(file.camera.width, 40);
+        assert_eq!(file.camera.height, 20);
+        assert_eq!(file.camera.fov, 60.0);
+        assert_eq!(file.camera.from, Vec4(0.0, 1.0, -5.0, 1.0));
+        let camera = file.camera();
+        assert_eq!(camera.hsize, 40);
+        assert_eq!(camera.vsize, 20);
+        assert_eq!(camera.aperture, 0.0);
+        assert_eq!(camera.focal_distance, 5.0);
+    }
+
+    #[test]
+    //Tests loading camera projections
+    fn camera_projection() {
+        let file = parse("camera\n  projection fisheye\n  fov 200\nend\n").unwrap();
+        assert_eq!(file.camera.projection, Projection::Fisheye);
+        assert_eq!(file.camera().projection, Projection::Fisheye);
+        assert_eq!(parse("camera\nend\n").unwrap().camera.projection, Projection::Perspective);
+        assert!(parse("camera\n  projection pinhole\nend").is_err());
+        assert!(parse("camera\n  fov 200\nend").is_err());
+        assert!(parse("camera\n  projection orthographic\n  fov 180\nend").is_err());
+    }
+
+    #[test]
+    //Tests that a camera looking at its own position is rejected
+    fn camera_same_from_and_to() {
+        assert_eq!(parse("sphere\nend\ncamera\n  from 1 2 3\n  to 1 2 3\nend").err().unwrap().line, 3);
+    }
+
+    #[test]
+    //Tests that a camera whose up vector is parallel to its view direction is rejected
+    fn camera_up_parallel_to_view() {
+        assert_eq!(parse("camera\n  from 0 0 0\n  to 0 5 0\n  up 0 -2 0\nend").err().unwrap().line, 1);
+        assert_eq!(parse("camera\n  up 0 0 0\nend").err().unwrap().line, 1);
+    }
+
+    #[test]
+    //Tests loading the camera shutter and objects with keyframes
+    fn motion_blocks() {
+        let text = "
+            camera
+              shutter 0 0.5
+            end
+            sphere
+              translate 0 1 0
+              keyframe 1
+                translate 2 0 0
+              end
+            end
+        ";
+        let file = parse(text).unwrap();
+        assert_eq!(file.camera.shutter, (0.0, 0.5));
+        assert_eq!(file.camera().shutter_close, 0.5);
+        let moving = file.scene.objects[0].as_any().downcast_ref::<Moving>().unwrap();
+        assert_eq!(moving.path.track.keys.len(), 2);
+        assert_eq!(moving.path.at(0.5), Matrix4x4::translation(1.0, 0.0, 0.0));
+        assert_eq!(moving.object.get_transform(), &Matrix4x4::translation(0.0, 1.0, 0.0));
+        assert!(parse("sphere\nend").unwrap().scene.objects[0].as_any().downcast_ref::<Sphere>().is_some());
+        assert!(parse("camera\n  shutter 1 0\nend").is_err());
+        assert_eq!(parse("sphere\n  keyframe 1\n    color 1 0 0\n  end\nend").err().unwrap().line, 3);
+    }
+
+    #[test]
+    //Tests loading a camera with a lens
+    fn camera_lens() {
+        let file = parse("camera
+  aperture 0.2
+  focal_distance 3.5
+end
+").unwrap();
+        assert_eq!(file.camera.aperture, 0.2);
+        assert_eq!(file.camera.focal_distance, Some(3.5));
+        let camera = file.camera();
+        assert_eq!(camera.aperture, 0.2);
+        assert_eq!(camera.focal_distance, 3.5);
+        assert_eq!(parse("camera
+  aperture -1
+end").err().unwrap().line, 1);
+        assert_eq!(parse("camera
+  focal_distance 0
+end").err().unwrap().line, 1);
+    }
+
+    #[test]
+    //Tests loading lights
+    fn light_blocks() {
+        let text = "
+            light point
+              position -10 10 -10
+              intensity 1 0.5 1
+            end
+            light area # soft shadows
+              corner 0 0 0
+              uvec 2 0 0
+              usteps 4
+              vvec 0 0 1
+              vsteps 2
+            end
+            light spot
+              position 0 5 0
+              direction 0 -2 0
+              angle 20
+              falloff 0
+            end
+            light directional
+              direction 0 -1 0
+              angular_diameter 0.5
+            end
+        ";
+        let file = parse(text).unwrap();
+        assert_eq!(file.scene.light_sources.len(), 4);
+        assert_eq!(file.scene.light_sources[0].get_intensity(), &Color(1.0, 0.5, 1.0));
+        assert_eq!(file.scene.light_sources[1].get_positions().len(), 8);
+        let spot = &file.scene.light_sources[2];
+        assert_eq!(spot.get_position(), &Vec4(0.0, 5.0, 0.0, 1.0));
+        assert_eq!(spot.attenuation(&Vec4(0.0, 0.0, 0.0, 1.0)), 1.0);
+        assert_eq!(spot.attenuation(&Vec4(5.0, 0.0, 0.0, 1.0)), 0.0);
+        assert!(file.scene.light_sources[3].get_position().1 > 1000.0);
+        assert!(parse("light spot\n  angle 0\nend").is_err());
+        assert!(parse("light spot\n  direction 0 0 0\nend").is_err());
+        assert!(parse("light directional\n  samples 0\nend").is_err());
+        assert!(parse("light directional\n  corner 0 0 0\nend").is_err());
+    }
+
+    #[test]
+    //Tests named materials, extended materials and transforms
+    fn materials_and_transforms() {
+        let text = "
+            material red
+              color 1 0 0
+              ambient 0.5
+              pattern stripes
+                color 1 0 0
+                color 1 1 1
+              end
+            end
+            material shiny_red extends red
+              reflectivity 0.8
+              emission 0.5 0 0
+              model microfacet
+              metallic 1
+              roughness 0.25
+              glossy_samples 4
+            end
+            sphere
+              material shiny_red
+              scale 2 2 2
+              translate 0 1 0
+            end
+        ";
+        let file = parse(text).unwrap();
+        let sphere = file.scene.objects[0].as_any().downcast_ref::<Sphere>().unwrap();
+        assert_eq!(sphere.material.color, Color(1.0, 0.0, 0.0));
+        assert_eq!(sphere.material.ambient, 0.5);
+        assert_eq!(sphere.material.reflectivity, 0.8);
+        assert_eq!(sphere.material.emission, Color(0.5, 0.0, 0.0));
+        //The emissive sphere lights the scene
+        assert_eq!(file.scene.light_sources.len(), 1);
+        assert!(file.scene.light_sources[0].emitter().is_some());
+        assert_eq!(sphere.material.model, ReflectionModel::Microfacet);
+        assert_eq!((sphere.material.metallic, sphere.material.roughness), (1.0, 0.25));
+        assert_eq!(sphere.material.glossy_samples, 4);
+        assert!(sphere.material.pattern.is_some());
+        assert_eq!(sphere.transform, Matrix4x4::translation(0.0, 1.0, 0.0) * Matrix4x4::scaling(2.0, 2.0, 2.0));
+    }
+
+    #[test]
+    //Tests loading groups, csg trees, triangles and obj files
+    fn nested_objects() {
+        let text = "
+            group
+              translate 0 1 0
+              sphere
+              end
+              csg difference
+                cube
+                end
+                sphere
+                  material
+                    color 0 0 1
+                  end
+                end
+              end
+              obj test1.obj
+              end
+            end
+            triangle
+              p1 0 1 0
+              p2 -1 0 0
+              p3 1 0 0
+              translate 0 0 2
+            end
+        ";
+        let file = parse(text).unwrap();
+        assert_eq!(file.scene.objects.len(), 2);
+        let group = file.scene.objects[0].as_any().downcast_ref::<Group>().unwrap();
+        assert_eq!(group.objects.len(), 3);
+        let csg = group.objects[1].as_any().downcast_ref::<CSG>().unwrap();
+        assert_eq!(csg.operation, Operation::Difference);
+        let obj = group.objects[2].as_any().downcast_ref::<Group>().unwrap();
+        assert_eq!(obj.objects.len(), 2);
+        let triangle = file.scene.objects[1].as_any().downcast_ref::<Triangle>().unwrap();
+        assert_eq!(triangle.p1, Vec4(0.0, 1.0, 2.0, 1.0));
+    }
+
+    #[test]
+    //Tests that errors report the line they were found on
+    fn error_line_numbers() {
+        assert_eq!(parse("sphere\n  scale 1 x 1\nend").err().unwrap().line, 2);
+        assert_eq!(parse("\n\nteapot\nend").err().unwrap().line, 3);
+        assert_eq!(parse("camera\n  width 10\n\ncube\nend").err().unwrap().line, 4);
+        assert_eq!(parse("sphere\n  material missing\nend").err().unwrap().line, 2);
+        assert_eq!(parse("# comment\ncsg union\n  sphere\n  end\nend").err().unwrap().line, 2);
+        assert_eq!(parse("light point\n  position 1 2\nend").err().unwrap().line, 2);
+        assert_eq!(parse("sphere\n").err().unwrap().line, 1);
+        assert_eq!(parse("obj missing.obj\nend").err().unwrap().line, 1);
+        assert_eq!(parse("sphere\nend\nobj bad1.obj\nend").err().unwrap().line, 3);
+        let error = parse("cube\n  radius 2\nend").err().unwrap();
+        assert_eq!(format!("{}", error), "line 2: unknown property 'radius' in cube");
+    }
+
+    #[test]
+    //Tests loading the example scene from disk
+    fn load_example_scene() {
+        let file = SceneFile::load("scenes/showcase.scene").unwrap();
+        assert_eq!(file.scene.objects.len(), 4);
+        assert_eq!(file.scene.light_sources.len(), 2);
+        assert!(SceneFile::load("scenes/missing.scene").is_err());
+    }
+
+    #[test]
+    //Tests reading keyframed camera, light and material properties at different times
+    fn keyframed_properties() {
+        let text = "
+            camera
+              from 0 0 -4
+              shutter 0 0.5
+              keyframe 10
+                from 0 0 -8
+                fov 90
+              end
+              keyframe 5 step
+                fov 60
+              end
+            end
+            light point
+              position 0 10 0
+              keyframe 4 ease_in
+                position 0 10 4
+              end
+            end
+            material glow
+              ambient 0
+              keyframe 2
+                ambient 1
+              end
+            end
+            sphere
+              material glow
+            end
+        ";
+        let file = SceneFile::parse_at(text, Path::new("tests"), 2.0).unwrap();
+        assert_eq!(file.camera.from, Vec4(0.0, 0.0, -4.8, 1.0));
+        assert_eq!(file.camera.fov, 60.0);
+        assert_eq!(file.camera.shutter, (2.0, 2.5));
+        assert_eq!(file.scene.light_sources[0].get_position(), &Vec4(0.0, 10.0, 1.0, 1.0));
+        assert_eq!(file.scene.objects[0].get_material().ambient, 1.0);
+        let file = SceneFile::parse_at(text, Path::new("tests"), 7.5).unwrap();
+        assert_eq!(file.camera.fov, 75.0);
+        let file = parse(text).unwrap();
+        assert_eq!(file.camera.from, Vec4(0.0, 0.0, -4.0, 1.0));
+        assert_eq!(file.camera.fov, 60.0);
+        assert_eq!(file.scene.objects[0].get_material().ambient, 0.0);
+    }
+
+    #[test]
+    //Tests errors in keyframe blocks
+    fn keyframe_errors() {
+        assert_eq!(parse("camera
+  keyframe 1
+    width 10
+  end
+end").err().unwrap().line, 3);
+        assert_eq!(parse("camera
+  keyframe 1 wobble
+  end
+end").err().unwrap().line, 2);
+        assert_eq!(parse("camera
+  keyframe nan
+  end
+end").err().unwrap().line, 2);
+        assert_eq!(parse("camera
+  keyframe 1
+    from 0 0
+  end
+end").err().unwrap().line, 3);
+        assert_eq!(parse("light point
+  keyframe 1
+    corner 0 0 0
+  end
+end").err().unwrap().line, 3);
+        assert!(parse("material a
+  keyframe 1
+    pattern stripes
+    end
+  end
+end").is_err());
+        assert!(parse("camera
+  keyframe 1
+    fov 10
+  end
+  keyframe 1
+    fov 20
+  end
+end").is_err());
+    }
+
+    #[test]
+    //Tests object keyframes with curves
+    fn object_keyframe_curves() {
+        let file = parse("sphere
+  keyframe 2 ease_in
+    translate 4 0 0
+  end
+end").unwrap();
+        let moving = file.scene.objects[0].as_any().downcast_ref::<Moving>().unwrap();
+        assert_eq!(moving.path.at(1.0), Matrix4x4::translation(1.0, 0.0, 0.0));
+        assert_eq!(moving.path.at(2.0), Matrix4x4::translation(4.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,235 @@
+#[cfg(test)]
+mod tests {
+    use rust_ray_tracer::core::canvas::Canvas;
+    use rust_ray_tracer::core::color::Color;
+    use rust_ray_tracer::core::matrix::Matrix4x4;
+    use rust_ray_tracer::core::vector::Vec4;
+    use rust_ray_tracer::image::tone::srgb_decode;
+    use rust_ray_tracer::materials::material::Material;
+    use rust_ray_tracer::materials::patterns::*;
+    use rust_ray_tracer::materials::texture::*;
+    use rust_ray_tracer::misc::axis::Axis;
+    use rust_ray_tracer::objects::group::Group;
+    use rust_ray_tracer::objects::object::Object;
+    use rust_ray_tracer::objects::parser::Parser;
+    use rust_ray_tracer::objects::sphere::Sphere;
+    use rust_ray_tracer::objects::triangle::Triangle;
+    use rust_ray_tracer::ray_tracing::ray::Ray;
+    use rust_ray_tracer::world::scene::Scene;
+    use rust_ray_tracer::world::scene_file::SceneFile;
+    use std::f32::consts::FRAC_1_SQRT_2;
+    use std::fs;
+    use std::fs::File;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    //Checks that texture coordinates are close to the expected values
+    fn assert_uv(result: (f32, f32), expected: (f32, f32)) {
+        assert!((result.0 - expected.0).abs() < 0.0001, "{:?} != {:?}", result, expected);
+        assert!((result.1 - expected.1).abs() < 0.0001, "{:?} != {:?}", result, expected);
+    }
+
+    //Checks that two colors are close
+    fn assert_color(result: &Color, expected: &Color) {
+        assert!(
+            (result.0 - expected.0).abs() < 0.0001 && (result.1 - expected.1).abs() < 0.0001 && (result.2 - expected.2).abs() < 0.0001,
+            "{:?} != {:?}",
+            result,
+            expected
+        );
+    }
+
+    //A 2x2 image with red and green on the top row and blue and white on the bottom
+    fn test_image() -> Arc<Canvas> {
+        Arc::new(Canvas::load("tests/texture.ppm").unwrap())
+    }
+
+    #[test]
+    //Tests spherical mapping of points on a unit sphere
+    fn spherical_mapping() {
+        assert_uv(spherical_map(&Vec4::new(0.0, 0.0, -1.0, 1.0)), (0.0, 0.5));
+        assert_uv(spherical_map(&Vec4::new(1.0, 0.0, 0.0, 1.0)), (0.25, 0.5));
+        assert_uv(spherical_map(&Vec4::new(0.0, 0.0, 1.0, 1.0)), (0.5, 0.5));
+        assert_uv(spherical_map(&Vec4::new(-1.0, 0.0, 0.0, 1.0)), (0.75, 0.5));
+        assert_uv(spherical_map(&Vec4::new(0.0, 1.0, 0.0, 1.0)), (0.5, 1.0));
+        assert_uv(spherical_map(&Vec4::new(0.0, -1.0, 0.0, 1.0)), (0.5, 0.0));
+        assert_uv(spherical_map(&Vec4::new(FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0, 1.0)), (0.25, 0.75));
+    }
+
+    #[test]
+    //Tests planar and cylindrical mapping
+    fn planar_and_cylindrical_mapping() {
+        assert_uv(planar_map(&Vec4::new(0.25, 0.0, 0.5, 1.0)), (0.25, 0.5));
+        assert_uv(planar_map(&Vec4::new(-0.25, 0.0, -0.5, 1.0)), (0.75, 0.5));
+        assert_uv(planar_map(&Vec4::new(1.25, 0.5, 0.5, 1.0)), (0.25, 0.5));
+        assert_uv(cylindrical_map(&Vec4::new(0.0, 0.0, -1.0, 1.0)), (0.0, 0.0));
+        assert_uv(cylindrical_map(&Vec4::new(0.0, 0.5, -1.0, 1.0)), (0.0, 0.5));
+        assert_uv(cylindrical_map(&Vec4::new(FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2, 1.0)), (0.125, 0.5));
+        assert_uv(cylindrical_map(&Vec4::new(-FRAC_1_SQRT_2, -0.25, -FRAC_1_SQRT_2, 1.0)), (0.875, 0.75));
+    }
+
+    #[test]
+    //Tests that each cube face maps the same relative point to the same coordinates
+    fn cube_mapping() {
+        let points = [
+            Vec4::new(-0.5, 0.5, 1.0, 1.0),
+            Vec4::new(0.5, 0.5, -1.0, 1.0),
+            Vec4::new(-1.0, 0.5, -0.5, 1.0),
+            Vec4::new(1.0, 0.5, 0.5, 1.0),
+            Vec4::new(-0.5, 1.0, -0.5, 1.0),
+            Vec4::new(-0.5, -1.0, 0.5, 1.0),
+        ];
+        for point in points.iter() {
+            assert_uv(cube_map(point), (0.25, 0.75));
+        }
+    }
+
+    #[test]
+    //Tests bilinear sampling between and at pixel centers
+    fn bilinear_sampling() {
+        let image = test_image();
+        assert_color(&sample_bilinear(&image, 0.25, 0.75, true), &Color::new(1.0, 0.0, 0.0));
+        assert_color(&sample_bilinear(&image, 0.75, 0.25, true), &Color::new(1.0, 1.0, 1.0));
+        assert_color(&sample_bilinear(&image, 0.5, 0.75, true), &Color::new(0.5, 0.5, 0.0));
+        assert_color(&sample_bilinear(&image, 0.5, 0.5, true), &Color::new(0.5, 0.5, 0.5));
+        //Horizontal wrapping blends the left and right columns at the edge
+        assert_color(&sample_bilinear(&image, 0.0, 0.75, true), &Color::new(0.5, 0.5, 0.0));
+        //Without vertical wrapping the top row is not blended with the bottom row
+        assert_color(&sample_bilinear(&image, 0.25, 1.0, false), &Color::new(1.0, 0.0, 0.0));
+        assert_color(&sample_bilinear(&image, 0.25, 1.0, true), &Color::new(0.5, 0.0, 0.5));
+    }
+
+    #[test]
+    //Tests an image pattern with a mapping and a transform
+    fn image_pattern() {
+        let pattern = ImagePattern::new(test_image(), UvMapping::Planar, Matrix4x4::scaling(2.0, 2.0, 2.0));
+        assert_color(&pattern.color_at(&Vec4::new(0.25, 0.0, 0.75, 1.0)), &Color::new(1.0, 0.0, 0.0));
+        let result = pattern.color_at_object(&vec![], &Matrix4x4::identity(), &Vec4::new(1.5, 0.0, 0.5, 1.0));
+        assert_color(&result, &Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    //Tests that surface mapped patterns use the coordinates stored on the object
+    fn surface_mapping() {
+        let pattern = ImagePattern::new(test_image(), UvMapping::Surface, Matrix4x4::identity());
+        let point = Vec4::new(0.25, 0.0, 0.25, 1.0);
+        let result = pattern.color_at_uv(&vec![], &Matrix4x4::identity(), &point, Some((0.25, 0.75)));
+        assert_color(&result, &Color::new(1.0, 0.0, 0.0));
+        //Objects without texture coordinates fall back to planar mapping
+        let result = pattern.color_at_uv(&vec![], &Matrix4x4::identity(), &point, None);
+        assert_color(&result, &Color::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    //Tests interpolating triangle texture coordinates
+    fn triangle_texture_coordinates() {
+        let mut triangle = Triangle::default();
+        assert_eq!(triangle.texture_uv(Some(0.5), Some(0.25)), None);
+        triangle.texture_coords = Some([(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]);
+        assert_uv(triangle.texture_uv(Some(0.5), Some(0.25)).unwrap(), (0.5, 0.25));
+        assert_uv(triangle.texture_uv(Some(0.0), Some(0.0)).unwrap(), (0.0, 0.0));
+        assert_eq!(triangle.texture_uv(None, None), None);
+    }
+
+    #[test]
+    //Tests reading texture vertices from an obj file
+    fn obj_texture_vertices() {
+        let result = Parser::parse_obj(File::open("tests/test4.obj").unwrap()).unwrap();
+        assert_eq!(result.vertices.len(), 4);
+        assert_eq!(result.texture_coords.len(), 4);
+        assert_eq!(result.texture_coords[2], (1.0, 1.0));
+        assert_eq!(result.triangles.len(), 2);
+        assert_eq!(result.smooth_triangles.len(), 2);
+        assert_eq!(result.triangles[1].texture_coords, Some([(0.0, 0.0), (1.0, 1.0), (0.0, 1.0)]));
+        assert_eq!(result.smooth_triangles[0].texture_coords, Some([(0.0, 0.0), (1.0, 1.0), (0.0, 1.0)]));
+        //Negative indices count back from the last vertex
+        assert_eq!(result.smooth_triangles[1].p1, result.vertices[0]);
+        assert_eq!(result.smooth_triangles[1].texture_coords, None);
+    }
+
+    #[test]
+    //Tests that a textured triangle is shaded with the color from its texture coordinates
+    fn textured_triangle_shading() {
+        let mut material = Material::default();
+        material.ambient = 1.0;
+        material.diffuse = 0.0;
+        material.specular = 0.0;
+        material.pattern = Some(Box::new(ImagePattern::new(test_image(), UvMapping::Surface, Matrix4x4::identity())));
+        let mut triangle = Triangle::new(
+            Vec4::new(-1.0, -1.0, 0.0, 1.0),
+            Vec4::new(1.0, -1.0, 0.0, 1.0),
+            Vec4::new(-1.0, 1.0, 0.0, 1.0),
+            material,
+        );
+        triangle.texture_coords = Some([(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]);
+        let mut scene = Scene::new();
+        scene.light_sources = Scene::default().light_sources;
+        scene.objects.push(Box::new(triangle));
+
+        let ray = Ray::new((-0.5, 0.5, -5.0), (0.0, 0.0, 1.0));
+        assert_color(&Scene::compute_color(ray, &scene, 1).unwrap(), &Color::new(1.0, 0.0, 0.0));
+        let ray = Ray::new((-0.5, -0.5, -5.0), (0.0, 0.0, 1.0));
+        assert_color(&Scene::compute_color(ray, &scene, 1).unwrap(), &Color::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    //Tests that triangles loaded from an obj file into a group are shaded with their texture coordinates
+    fn textured_obj_shading() {
+        let mut material = Material::default();
+        material.ambient = 1.0;
+        material.diffuse = 0.0;
+        material.specular = 0.0;
+        material.pattern = Some(Box::new(ImagePattern::new(test_image(), UvMapping::Surface, Matrix4x4::identity())));
+        let mut group = Group::new(Matrix4x4::identity(), material);
+        Parser::parse_obj(File::open("tests/test4.obj").unwrap()).unwrap().convert_to_group(&mut group);
+        let mut scene = Scene::new();
+        scene.light_sources = Scene::default().light_sources;
+
+        let ray = Ray::new((-0.75, 0.75, -5.0), (0.0, 0.0, 1.0));
+        let intersections = group.intersect(&ray).unwrap();
+        let hit = &intersections[0];
+        assert_uv(hit.object.texture_uv(hit.u, hit.v).unwrap(), (0.125, 0.375));
+        scene.objects.push(Box::new(group));
+
+        let ray = Ray::new((-0.5, 1.5, -5.0), (0.0, 0.0, 1.0));
+        assert_color(&Scene::compute_color(ray, &scene, 1).unwrap(), &Color::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    //Tests that 8 bit textures are converted from sRGB to linear colors while 16 bit textures are left as they are
+    fn srgb_textures() {
+        let path = std::env::temp_dir().join("rust_ray_tracer_srgb_texture.ppm");
+        let path = path.to_str().unwrap();
+        fs::write(path, "P3\n1 1\n255\n128 0 255\n").unwrap();
+        assert_color(Canvas::load(path).unwrap().get(0, 0).unwrap(), &Color::new(128.0 / 255.0, 0.0, 1.0));
+        assert_color(Canvas::load_texture(path).unwrap().get(0, 0).unwrap(), &Color::new(srgb_decode(128.0 / 255.0), 0.0, 1.0));
+        assert!((srgb_decode(128.0 / 255.0) - 0.2158605).abs() < 0.0001);
+
+        fs::write(path, "P3\n1 1\n65535\n32768 0 65535\n").unwrap();
+        assert_color(Canvas::load_texture(path).unwrap().get(0, 0).unwrap(), &Color::new(32768.0 / 65535.0, 0.0, 1.0));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    //Tests loading an image pattern from a scene file
+    fn scene_file_image_pattern() {
+        let text = "
+            sphere
+              material
+                pattern image texture.ppm
+                  mapping spherical
+                  rotate y 90
+                end
+              end
+            end
+        ";
+        let file = SceneFile::parse(text, Path::new("tests")).unwrap();
+        let sphere = file.scene.objects[0].as_any().downcast_ref::<Sphere>().unwrap();
+        let pattern = sphere.material.pattern.as_ref().unwrap();
+        let expected = ImagePattern::new(test_image(), UvMapping::Spherical, Matrix4x4::rotation(Axis::Y, 90.0));
+        assert!(pattern.as_any().downcast_ref::<ImagePattern>().unwrap() == &expected);
+
+        assert_eq!(SceneFile::parse("sphere\n  material\n    pattern image missing.png\n    end\n  end\nend", Path::new("tests")).err().unwrap().line, 3);
+        assert_eq!(SceneFile::parse("sphere\n  material\n    pattern image texture.ppm\n      mapping torus\n    end\n  end\nend", Path::new("tests")).err().unwrap().line, 4);
+    }
+}